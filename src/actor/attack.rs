@@ -19,32 +19,8 @@ impl Attack {
         Self { damage, hit_chance }
     }
 
-    pub fn from_name(name: ActorName) -> Self {
-        use ActorName as A;
-
-        #[cfg(not(feature = "op_monsters"))]
-        let (damage, hit_chance) = match name {
-            A::Warrior => (35..61, 0.8),
-            A::Priestess => (25..46, 0.7),
-            A::Theif => (20..41, 0.8),
-            A::Ogre => (30..61, 0.6),
-            A::Goblin => (15..31, 0.8),
-            A::Skeleton => (30..51, 0.8),
-            A::UnknownJim => (0..1, 0.0),
-        };
-
-        #[cfg(feature = "op_monsters")]
-        let (damage, hit_chance) = match name {
-            A::Warrior => (35..61, 0.8),
-            A::Priestess => (25..46, 0.7),
-            A::Theif => (20..41, 0.8),
-            A::Ogre => (3000..6100, 1.0),
-            A::Goblin => (1500..10000, 1.0),
-            A::Skeleton => (3000..5100, 1.0),
-            A::UnknownJim => (0..u32::MAX, 0.0),
-        };
-
-        Self::new(damage, hit_chance)
+    pub fn from_definition(definition: &ActorDefinition) -> Self {
+        Self::new(definition.damage.clone(), definition.hit_chance)
     }
 
     /// Simulates an attack using the rng and returns the
@@ -56,6 +32,18 @@ impl Attack {
             .map(|d| AttackDamage::Hit(d))
             .unwrap_or(AttackDamage::Miss)
     }
+
+    /// Simulates an attack against a target, letting the target
+    /// try to block it if it would have hit.
+    pub fn conduct_against(&self, rng: &mut impl Rng, block_chance: BlockChance) -> AttackOutcome {
+        match self.conduct(rng) {
+            AttackDamage::Hit(_) if rng.random_bool(block_chance.0 as f64) => {
+                AttackOutcome::Blocked
+            }
+            AttackDamage::Hit(damage) => AttackOutcome::Hit(damage),
+            AttackDamage::Miss => AttackOutcome::Miss,
+        }
+    }
 }
 
 /// The damage done by an attack. An attack that does 0 damage is considered a miss.
@@ -65,13 +53,22 @@ pub enum AttackDamage {
     Miss,
 }
 
+/// The result of an attack after the target tried to block it.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum AttackOutcome {
+    Hit(NonZero<u32>),
+    Blocked,
+    Miss,
+}
+
 /// The chance the actor has to block an attack in combat.
 /// Should be between 0.0 and 1.0
 #[derive(Component, Deref, DerefMut, Clone, Copy, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct BlockChance(pub f32);
 
-/// Determines the order of turns in combat. Higher numbers means they will go sooner.
+/// Determines the turns in combat. Higher numbers means they go sooner and more often.
+/// See [`TurnOrder`](crate::game::TurnOrder).
 #[derive(Component, Deref, DerefMut, Clone, Copy, Serialize, Deserialize)]
 pub struct AttackSpeed(pub u32);
 
@@ -80,31 +77,13 @@ impl AttackSpeed {
         Self(speed)
     }
 
-    pub fn from_name(name: ActorName) -> Self {
-        use ActorName as A;
-        Self(match name {
-            A::Warrior => 4,
-            A::Priestess => 5,
-            A::Theif => 6,
-            A::Ogre => 2,
-            A::Goblin => 5,
-            A::Skeleton => 3,
-            A::UnknownJim => 1,
-        })
+    pub fn from_definition(definition: &ActorDefinition) -> Self {
+        Self(definition.speed)
     }
 }
 
 impl BlockChance {
-    pub fn from_name(name: ActorName) -> Self {
-        use ActorName as A;
-        Self(match name {
-            A::Warrior => 0.5,
-            A::Priestess => 0.1,
-            A::Theif => 0.3,
-            A::Ogre => 0.2,
-            A::Goblin => 0.4,
-            A::Skeleton => 0.2,
-            A::UnknownJim => 0.1,
-        })
+    pub fn from_definition(definition: &ActorDefinition) -> Self {
+        Self(definition.block_chance)
     }
 }
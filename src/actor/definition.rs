@@ -0,0 +1,155 @@
+use crate::game::{AbilityName, EnemyBrain};
+use crate::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::num::NonZero;
+use std::ops::Range;
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+/// The actor definitions used by the game.
+/// Balance variants are kept as alternate files in `assets/actors`.
+#[cfg(not(feature = "op_monsters"))]
+const ACTOR_DEFINITIONS: &str = include_str!("../../assets/actors/default.ron");
+#[cfg(feature = "op_monsters")]
+const ACTOR_DEFINITIONS: &str = include_str!("../../assets/actors/op_monsters.ron");
+
+/// Everything needed to make a given actor.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ActorDefinition {
+    /// The max health of the actor.
+    pub health: NonZero<u32>,
+    /// The range of damage they can do.
+    pub damage: Range<u32>,
+    /// The chance the actor has to hit when they attack.
+    /// Should be between 0.0 and 1.0
+    pub hit_chance: f32,
+    /// The chance the actor has to block an attack.
+    /// Should be between 0.0 and 1.0
+    pub block_chance: f32,
+    /// Higher numbers means they will go sooner in combat.
+    pub speed: u32,
+    /// The asset path to the sprite sheet.
+    pub sprite: String,
+    pub atlas: AtlasDefinition,
+    pub animations: AnimationDefinitions,
+    /// What they can do in combat besides attacking,
+    /// in the order they are shown in the combat menu.
+    #[serde(default)]
+    pub abilities: Vec<AbilityName>,
+    /// How they fight as an enemy.
+    #[serde(default)]
+    pub brain: EnemyBrain,
+    /// The experience the party gets for defeating them.
+    #[serde(default)]
+    pub experience: u32,
+    /// How their stats go up each level in the party.
+    #[serde(default)]
+    pub growth: StatGrowth,
+}
+
+/// The layout of an actor's sprite sheet.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct AtlasDefinition {
+    /// The size of a single sprite in the sheet.
+    pub tile_size: UVec2,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl AtlasDefinition {
+    pub fn to_layout(&self) -> TextureAtlasLayout {
+        TextureAtlasLayout::from_grid(self.tile_size, self.columns, self.rows, None, None)
+    }
+}
+
+/// The sprite ranges for each of an actor's animations.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AnimationDefinitions {
+    pub normal: AnimationConfig,
+    pub damaged: AnimationConfig,
+    pub dead: AnimationConfig,
+}
+
+/// All of the actor definitions, looked up by name.
+#[derive(Resource, Clone, Debug)]
+pub struct ActorRegistry(HashMap<ActorName, ActorDefinition>);
+
+#[derive(Error, Debug)]
+pub enum ActorRegistryError {
+    #[error("Failed to parse actor definitions with `{0}`")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Actor `{0}` has no definition")]
+    Missing(ActorName),
+    #[error("Actor `{0}` has an empty damage range")]
+    EmptyDamage(ActorName),
+    #[error("Actor `{0}` has a hit or block chance outside of 0.0 to 1.0")]
+    InvalidChance(ActorName),
+}
+
+impl ActorRegistry {
+    /// Parses the definitions from RON, making sure every actor is defined.
+    pub fn from_ron(ron: &str) -> Result<Self, ActorRegistryError> {
+        let definitions: HashMap<ActorName, ActorDefinition> = ron::from_str(ron)?;
+
+        for name in ActorName::iter() {
+            match definitions.get(&name) {
+                Some(definition) if definition.damage.is_empty() => {
+                    return Err(ActorRegistryError::EmptyDamage(name));
+                }
+                Some(definition)
+                    if ![definition.hit_chance, definition.block_chance]
+                        .iter()
+                        .all(|chance| (0.0..=1.0).contains(chance)) =>
+                {
+                    return Err(ActorRegistryError::InvalidChance(name));
+                }
+                Some(_) => {}
+                None => return Err(ActorRegistryError::Missing(name)),
+            }
+        }
+
+        Ok(Self(definitions))
+    }
+
+    /// Gets the definition for an actor.
+    /// Every actor is checked to have one when the registry is made.
+    pub fn get(&self, name: ActorName) -> &ActorDefinition {
+        &self.0[&name]
+    }
+}
+
+impl Default for ActorRegistry {
+    fn default() -> Self {
+        Self::from_ron(ACTOR_DEFINITIONS).expect("The built in actor definitions should be valid")
+    }
+}
+
+#[cfg(test)]
+mod definition_tests {
+    use super::*;
+
+    #[test]
+    fn test_definition_files() {
+        ActorRegistry::from_ron(include_str!("../../assets/actors/default.ron")).unwrap();
+        ActorRegistry::from_ron(include_str!("../../assets/actors/op_monsters.ron")).unwrap();
+    }
+
+    #[test]
+    fn test_missing_actor() {
+        let err = ActorRegistry::from_ron("{}").unwrap_err();
+        assert!(matches!(err, ActorRegistryError::Missing(_)));
+    }
+
+    #[test]
+    fn test_invalid_chance() {
+        let ron = include_str!("../../assets/actors/default.ron");
+        for (field, chance) in [("hit_chance: 0.8", "1.5"), ("block_chance: 0.5", "-0.1")] {
+            let (name, _) = field.split_once(':').unwrap();
+            let ron = ron.replacen(field, &format!("{name}: {chance}"), 1);
+            let err = ActorRegistry::from_ron(&ron).unwrap_err();
+            assert!(matches!(err, ActorRegistryError::InvalidChance(_)));
+        }
+    }
+}
@@ -41,19 +41,8 @@ impl HealthBundle {
         }
     }
 
-    pub fn from_name(name: ActorName) -> Self {
-        use ActorName as A;
-        let max = match name {
-            A::Warrior => 125,
-            A::Priestess => 75,
-            A::Theif => 75,
-            A::Ogre => 200,
-            A::Goblin => 70,
-            A::Skeleton => 100,
-            A::UnknownJim => 1,
-        };
-
-        Self::new(NonZero::new(max).unwrap())
+    pub fn from_definition(definition: &ActorDefinition) -> Self {
+        Self::new(definition.health)
     }
 }
 
@@ -134,6 +123,15 @@ impl Health {
         debug_assert!(self.current.is_none_or(|curr| curr <= self.max));
     }
 
+    /// Raises the max health, and the current health with it if they are alive.
+    #[inline]
+    pub fn raise_max(&mut self, amount: u32) {
+        self.max = self.max.saturating_add(amount);
+        self.current = self.current.map(|curr| curr.saturating_add(amount));
+
+        debug_assert!(self.current.is_none_or(|curr| curr <= self.max));
+    }
+
     /// Damage the actor, killing them if they health would go below one.
     #[inline]
     pub fn damage(&mut self, amount: u32) {
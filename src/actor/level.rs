@@ -0,0 +1,150 @@
+//! Experience and levels for the party, and how their stats grow.
+use crate::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The experience needed to reach level 2.
+/// Each level after needs this much more than the last.
+const EXPERIENCE_PER_LEVEL: u32 = 100;
+
+/// How experienced a party member is.
+#[derive(Component, Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct Level {
+    pub level: u32,
+    /// The experience towards the next level.
+    pub experience: u32,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self {
+            level: 1,
+            experience: 0,
+        }
+    }
+}
+
+impl Level {
+    /// The experience needed to go from this level to the next.
+    pub fn experience_needed(&self) -> u32 {
+        EXPERIENCE_PER_LEVEL * self.level
+    }
+
+    /// Adds the experience, returning the number of levels gained.
+    pub fn gain(&mut self, experience: u32) -> u32 {
+        self.experience += experience;
+
+        let mut levels = 0;
+        while self.experience >= self.experience_needed() {
+            self.experience -= self.experience_needed();
+            self.level += 1;
+            levels += 1;
+        }
+        levels
+    }
+}
+
+/// How much an actor's stats go up each level.
+/// These are set for each actor in their [`ActorDefinition`].
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatGrowth {
+    pub health: u32,
+    /// Added to both ends of their damage range.
+    pub damage: u32,
+    pub hit_chance: f32,
+    pub speed: u32,
+}
+
+impl StatGrowth {
+    /// The growth over the given number of levels.
+    pub fn times(&self, levels: u32) -> Self {
+        Self {
+            health: self.health * levels,
+            damage: self.damage * levels,
+            hit_chance: self.hit_chance * levels as f32,
+            speed: self.speed * levels,
+        }
+    }
+
+    /// Raises the stats by this much.
+    /// Their current health goes up with their max, if they are alive.
+    pub fn apply(&self, health: &mut Health, attack: &mut Attack, speed: &mut AttackSpeed) {
+        health.raise_max(self.health);
+        attack.damage = attack.damage.start + self.damage..attack.damage.end + self.damage;
+        attack.hit_chance = (attack.hit_chance + self.hit_chance).min(1.0);
+        speed.0 += self.speed;
+    }
+
+    /// Describes the gains, as in "+15 health, +4 damage".
+    pub fn describe(&self) -> String {
+        let mut gains = Vec::new();
+        if self.health > 0 {
+            gains.push(format!("+{} health", self.health));
+        }
+        if self.damage > 0 {
+            gains.push(format!("+{} damage", self.damage));
+        }
+        if self.hit_chance > 0.0 {
+            gains.push(format!("+{:.0}% hit chance", self.hit_chance * 100.0));
+        }
+        if self.speed > 0 {
+            gains.push(format!("+{} speed", self.speed));
+        }
+        gains.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod level_tests {
+    use super::*;
+    use std::num::NonZero;
+
+    #[test]
+    fn test_gain_levels() {
+        let mut level = Level::default();
+        assert_eq!(level.gain(60), 0);
+        assert_eq!(level.gain(60), 1);
+        assert_eq!(
+            level,
+            Level {
+                level: 2,
+                experience: 20
+            }
+        );
+
+        // 200 to reach level 3, then 300 for level 4
+        assert_eq!(level.gain(480), 2);
+        assert_eq!(
+            level,
+            Level {
+                level: 4,
+                experience: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_growth() {
+        let growth = StatGrowth {
+            health: 10,
+            damage: 2,
+            hit_chance: 0.05,
+            speed: 1,
+        };
+        let mut health = Health::with_current(NonZero::new(40), NonZero::new(50).unwrap());
+        let mut attack = Attack::new(5..10, 0.9);
+        let mut speed = AttackSpeed(3);
+
+        growth.times(2).apply(&mut health, &mut attack, &mut speed);
+        assert_eq!(health.current(), NonZero::new(60));
+        assert_eq!(health.max().get(), 70);
+        assert_eq!(attack.damage, 9..14);
+        assert_eq!(attack.hit_chance, 1.0);
+        assert_eq!(speed.0, 5);
+        assert_eq!(
+            growth.describe(),
+            "+10 health, +2 damage, +5% hit chance, +1 speed"
+        );
+    }
+}
@@ -1,20 +1,36 @@
 mod attack;
+mod definition;
 mod health;
+mod level;
+mod party;
+mod status;
 
 pub use attack::*;
+pub use definition::*;
 pub use health::*;
+pub use level::*;
+pub use party::*;
+pub use status::*;
 
+use crate::game::{Abilities, Cooldowns};
 use crate::prelude::*;
 use bevy::prelude::*;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
-#[cfg(feature = "sqlite")]
 use std::num::NonZero;
 use strum::{Display, EnumIter};
 
 pub const ACTOR_LAYER: f32 = 1.0;
 
+pub struct ActorPlugin;
+
+impl Plugin for ActorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActorRegistry>();
+    }
+}
+
 /// The typical components for any given actor.
 #[derive(Bundle)]
 pub struct ActorBundle {
@@ -27,17 +43,23 @@ pub struct ActorBundle {
     pub transform: Transform,
     pub animation: AnimationBundle,
     pub block_chance: BlockChance,
+    pub status_effects: StatusEffects,
+    pub level: Level,
+    pub abilities: Abilities,
+    pub cooldowns: Cooldowns,
 }
 
 impl ActorBundle {
     pub fn from_name(
         asset_server: &AssetServer,
+        registry: &ActorRegistry,
         name: ActorName,
         team: Team,
         transform: Transform,
         alive: bool,
     ) -> Self {
-        let mut health = HealthBundle::from_name(name);
+        let definition = registry.get(name);
+        let mut health = HealthBundle::from_definition(definition);
 
         if !alive {
             health.health.kill();
@@ -48,11 +70,15 @@ impl ActorBundle {
             name,
             team,
             health,
-            attack: Attack::from_name(name),
-            speed: AttackSpeed::from_name(name),
+            attack: Attack::from_definition(definition),
+            speed: AttackSpeed::from_definition(definition),
             transform,
-            animation: AnimationBundle::from_name(asset_server, name),
-            block_chance: BlockChance::from_name(name),
+            animation: AnimationBundle::from_definition(asset_server, definition),
+            block_chance: BlockChance::from_definition(definition),
+            status_effects: StatusEffects::default(),
+            level: Level::default(),
+            abilities: Abilities::from_definition(definition),
+            cooldowns: Cooldowns::default(),
         }
     }
 }
@@ -62,12 +88,10 @@ pub struct Actor;
 
 #[cfg(feature = "sqlite")]
 pub fn save_actors(
-    components: Query<(&ActorName, &Team, &Health, &Attack, &AttackSpeed)>,
-    save_info: Res<SaveGame>,
-    db: NonSend<Database>,
+    db: &Database,
+    game_id: GameID,
+    actors: &[ActorSave],
 ) -> Result<(), DatabaseError> {
-    let game_id = save_info.game_id;
-
     db.connection.execute(
         "DELETE FROM PlayerActor WHERE game_id = :game_id",
         (game_id.0,),
@@ -82,7 +106,10 @@ pub fn save_actors(
             attack_damage_min,
             attack_damage_max,
             hit_chance,
-            attack_speed
+            attack_speed,
+            status_effects,
+            level,
+            experience
         )
         VALUES(
             :name,
@@ -92,24 +119,29 @@ pub fn save_actors(
             :attack_damage_min,
             :attack_damage_max,
             :hit_chance,
-            :attack_speed
+            :attack_speed,
+            :status_effects,
+            :level,
+            :experience
         );
     "#;
     let mut statement = db.connection.prepare(query)?;
 
-    for (name, team, health, attack, speed) in components {
-        let Team::Player = team else {
-            continue;
-        };
+    for actor in actors {
+        let status_effects = ron::to_string(&*actor.status_effects)
+            .map_err(|err| DatabaseError::ToSqlConversionFailure(Box::new(err)))?;
         statement.execute((
-            name.to_string(),
+            actor.name.to_string(),
             *game_id,
-            health.max(),
-            health.current(),
-            attack.damage.start,
-            attack.damage.end,
-            attack.hit_chance,
-            speed.0,
+            actor.health.max(),
+            actor.health.current(),
+            actor.attack.damage.start,
+            actor.attack.damage.end,
+            actor.attack.hit_chance,
+            actor.speed.0,
+            status_effects,
+            actor.level.level,
+            actor.level.experience,
         ))?;
     }
 
@@ -117,13 +149,7 @@ pub fn save_actors(
 }
 
 #[cfg(feature = "sqlite")]
-pub fn load_actors(
-    mut commands: Commands,
-    db: NonSend<Database>,
-    save_game: Res<SaveGame>,
-    asset_server: Res<AssetServer>,
-) -> Result<(), DatabaseError> {
-    let game_id = save_game.game_id;
+pub fn load_actors(db: &Database, game_id: GameID) -> Result<Vec<ActorSave>, DatabaseError> {
     let query = r#"
             SELECT
                 name,
@@ -132,7 +158,10 @@ pub fn load_actors(
                 attack_damage_max,
                 attack_damage_min,
                 attack_speed,
-                hit_chance
+                hit_chance,
+                status_effects,
+                level,
+                experience
             FROM PlayerActor WHERE PlayerActor.game_id = :game;
         "#;
 
@@ -142,8 +171,9 @@ pub fn load_actors(
             let name = row.get::<_, String>("name")?;
             let name = ron::from_str(&name).unwrap_or(ActorName::UnknownJim);
 
-            let health = HealthBundle::with_current(
-                row.get("health_curr")?,
+            let health = Health::with_current(
+                row.get::<_, Option<u32>>("health_curr")?
+                    .and_then(NonZero::new),
                 NonZero::new(row.get("health_max")?).unwrap_or(NonZero::new(1).unwrap()),
             );
             let attack = Attack::new(
@@ -151,28 +181,108 @@ pub fn load_actors(
                 row.get("hit_chance")?,
             );
             let speed = AttackSpeed::new(row.get("attack_speed")?);
-            // the actor will be placed after this
-            let transform = Transform::IDENTITY;
-            let animation = AnimationBundle::from_name(&asset_server, name);
-            let block_chance = BlockChance::from_name(name);
+            let status_effects = row.get::<_, String>("status_effects")?;
+            let status_effects = ron::from_str(&status_effects).map_err(|err| {
+                DatabaseError::FromSqlConversionFailure(
+                    7,
+                    rusqlite::types::Type::Text,
+                    Box::new(err),
+                )
+            })?;
 
-            Ok(ActorBundle {
-                actor: Actor,
+            Ok(ActorSave {
                 name,
-                team: Team::Player,
                 health,
                 attack,
                 speed,
-                transform,
-                animation,
-                block_chance,
+                status_effects: StatusEffects::new(status_effects),
+                level: Level {
+                    level: row.get("level")?,
+                    experience: row.get("experience")?,
+                },
             })
         })?
-        .for_each(|actor| {
-            commands.spawn(actor.unwrap());
-        });
+        .collect()
+}
 
-    Ok(())
+/// Gets every player actor to put in a save game.
+pub fn collect_actors(
+    components: Query<(
+        &ActorName,
+        &Team,
+        &Health,
+        &Attack,
+        &AttackSpeed,
+        &StatusEffects,
+        &Level,
+    )>,
+) -> Vec<ActorSave> {
+    components
+        .iter()
+        .filter(|(_, team, ..)| **team == Team::Player)
+        .map(
+            |(name, _, health, attack, speed, status_effects, level)| ActorSave {
+                name: *name,
+                health: *health,
+                attack: attack.clone(),
+                speed: *speed,
+                status_effects: status_effects.clone(),
+                level: *level,
+            },
+        )
+        .collect()
+}
+
+pub fn spawn_actors(
+    In(actors): In<Vec<ActorSave>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    registry: Res<ActorRegistry>,
+) {
+    for actor in actors {
+        commands.spawn(actor.into_bundle(&asset_server, &registry));
+    }
+}
+
+/// A player actor as it is kept in a save game.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActorSave {
+    pub name: ActorName,
+    pub health: Health,
+    pub attack: Attack,
+    pub speed: AttackSpeed,
+    /// Older saves have no status effects.
+    #[serde(default)]
+    pub status_effects: StatusEffects,
+    /// Older saves start from level 1.
+    #[serde(default)]
+    pub level: Level,
+}
+
+impl ActorSave {
+    /// Makes the actor's bundle. It is placed when the game starts.
+    pub fn into_bundle(self, asset_server: &AssetServer, registry: &ActorRegistry) -> ActorBundle {
+        let definition = registry.get(self.name);
+
+        ActorBundle {
+            actor: Actor,
+            name: self.name,
+            team: Team::Player,
+            health: HealthBundle::with_current(
+                self.health.current().map_or(0, NonZero::get),
+                self.health.max(),
+            ),
+            attack: self.attack,
+            speed: self.speed,
+            transform: Transform::IDENTITY,
+            animation: AnimationBundle::from_definition(asset_server, definition),
+            block_chance: BlockChance::from_definition(definition),
+            status_effects: self.status_effects,
+            level: self.level,
+            abilities: Abilities::from_definition(definition),
+            cooldowns: Cooldowns::default(),
+        }
+    }
 }
 
 /// The team the actor is in for combat.
@@ -204,16 +314,18 @@ pub enum ActorName {
 }
 
 impl ActorName {
-    pub fn get_enemies(rng: &mut impl Rng) -> Box<[ActorName]> {
-        let mon = rng.random_range(0..10);
+    /// The enemies in a combat room. A higher `density` makes
+    /// rooms with more than one enemy more likely, 1.0 is normal.
+    pub fn get_enemies(rng: &mut impl Rng, density: f64) -> Box<[ActorName]> {
+        let mon = rng.random_range(0..10) as f64;
         let mut enemies: Vec<ActorName> = Vec::new();
 
-        if mon < 1 {
+        if mon < density {
             // I know that's not how you do it but I'll fix it laterElijah. Ok I'm sorry
             for _ in 0..3 {
                 enemies.push(Self::get_rand_enemy(rng));
             }
-        } else if mon < 3 {
+        } else if mon < 3.0 * density {
             for _ in 0..2 {
                 enemies.push(Self::get_rand_enemy(rng));
             }
@@ -235,13 +347,3 @@ impl ActorName {
         }
     }
 }
-
-#[derive(Component, Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, Display)]
-pub enum SpecialAction {
-    #[strum(to_string = "Heal")]
-    HealTarget,
-    #[strum(to_string = "Crushing Blow")]
-    CrushingBlow,
-    #[strum(to_string = "Surprise Attack")]
-    SurpriseAttack,
-}
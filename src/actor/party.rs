@@ -0,0 +1,61 @@
+//! The classes the player can pick for their party.
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// Every class that can be in the party, in the order they stand.
+pub const PARTY_CLASSES: [ActorName; 3] =
+    [ActorName::Warrior, ActorName::Priestess, ActorName::Theif];
+
+/// The classes in the party, chosen when starting a new game.
+/// There is always at least one, and no class is in it twice.
+#[derive(Debug, PartialEq, Eq, Clone, Deref)]
+pub struct Party(Vec<ActorName>);
+
+impl Default for Party {
+    /// The full party.
+    fn default() -> Self {
+        Self(PARTY_CLASSES.to_vec())
+    }
+}
+
+impl Party {
+    /// Adds the class to the party, or takes them out if they are in it.
+    /// Returns false if they are the last one left, who can't be taken out.
+    pub fn toggle(&mut self, name: ActorName) -> bool {
+        match self.0.iter().position(|member| *member == name) {
+            Some(_) if self.0.len() == 1 => false,
+            Some(i) => {
+                self.0.remove(i);
+                true
+            }
+            None => {
+                self.0.push(name);
+                self.0
+                    .sort_by_key(|member| PARTY_CLASSES.iter().position(|class| class == member));
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod party_tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle() {
+        let mut party = Party::default();
+        assert!(party.toggle(ActorName::Warrior));
+        assert!(party.toggle(ActorName::Theif));
+        assert_eq!(*party, vec![ActorName::Priestess]);
+
+        // someone has to go in
+        assert!(!party.toggle(ActorName::Priestess));
+        assert_eq!(*party, vec![ActorName::Priestess]);
+
+        // classes keep their places
+        assert!(party.toggle(ActorName::Theif));
+        assert!(party.toggle(ActorName::Warrior));
+        assert_eq!(party, Party::default());
+    }
+}
@@ -0,0 +1,269 @@
+//! Effects that last on an actor for a few of their turns in combat.
+use crate::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+/// The damage done by poison at the start of each turn.
+const POISON_DAMAGE: u32 = 3;
+/// The damage done by bleeding at the start of each turn.
+const BLEED_DAMAGE: u32 = 6;
+/// The health healed by regen at the start of each turn.
+const REGEN_HEALING: u32 = 5;
+/// How much less likely a poisoned actor is to hit.
+const POISON_HIT_PENALTY: f32 = 0.15;
+/// How much more likely a shielded actor is to block.
+const SHIELD_BLOCK_BONUS: f32 = 0.3;
+/// How much more likely a guarding actor is to block.
+const GUARD_BLOCK_BONUS: f32 = 0.4;
+/// The speed of a hasted actor, as a percentage of their normal speed.
+const HASTE_SPEED_PERCENT: u32 = 150;
+/// The speed of a slowed actor, as a percentage of their normal speed.
+const SLOW_SPEED_PERCENT: u32 = 50;
+
+/// A kind of [`StatusEffect`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumIter, Display)]
+pub enum StatusKind {
+    /// Damages them each turn, and makes them miss more often.
+    Poison,
+    /// They skip their turns.
+    Stun,
+    /// Damages them more each turn.
+    Bleed,
+    /// Makes them more likely to block.
+    Shield,
+    /// Heals them each turn.
+    Regen,
+    /// Makes them faster, so they get turns more often.
+    Haste,
+    /// Makes them slower, so they get turns less often.
+    Slow,
+    /// Makes them more likely to block until their next turn.
+    /// This is from them taking the defend action.
+    Guard,
+}
+
+impl StatusKind {
+    /// The damage it does at the start of each turn.
+    pub fn damage(&self) -> u32 {
+        match self {
+            StatusKind::Poison => POISON_DAMAGE,
+            StatusKind::Bleed => BLEED_DAMAGE,
+            _ => 0,
+        }
+    }
+
+    /// The health it heals at the start of each turn.
+    pub fn healing(&self) -> u32 {
+        match self {
+            StatusKind::Regen => REGEN_HEALING,
+            _ => 0,
+        }
+    }
+
+    /// How an affected actor is described, as in "Ogre is bleeding".
+    pub fn describe(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "poisoned",
+            StatusKind::Stun => "stunned",
+            StatusKind::Bleed => "bleeding",
+            StatusKind::Shield => "shielded",
+            StatusKind::Regen => "regenerating",
+            StatusKind::Haste => "hasted",
+            StatusKind::Slow => "slowed",
+            StatusKind::Guard => "guarding",
+        }
+    }
+
+    /// The short label shown next to the health bars.
+    pub fn icon(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "PSN",
+            StatusKind::Stun => "STN",
+            StatusKind::Bleed => "BLD",
+            StatusKind::Shield => "SHD",
+            StatusKind::Regen => "RGN",
+            StatusKind::Haste => "HST",
+            StatusKind::Slow => "SLW",
+            StatusKind::Guard => "GRD",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            StatusKind::Poison => Color::srgb(0.4, 0.8, 0.2),
+            StatusKind::Stun => Color::srgb(0.9, 0.8, 0.2),
+            StatusKind::Bleed => Color::srgb(0.8, 0.1, 0.1),
+            StatusKind::Shield => Color::srgb(0.3, 0.6, 0.9),
+            StatusKind::Regen => Color::srgb(0.9, 0.5, 0.8),
+            StatusKind::Haste => Color::srgb(0.3, 0.9, 0.9),
+            StatusKind::Slow => Color::srgb(0.5, 0.4, 0.7),
+            StatusKind::Guard => Color::srgb(0.7, 0.7, 0.75),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// How many more of the actor's turns it lasts for.
+    pub turns: u32,
+}
+
+/// The effects on an actor, with at most one of each kind.
+/// These tick at the start of each of the actor's turns, see [`StatusEffects::tick`].
+#[derive(Component, Deref, Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct StatusEffects(Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn new(effects: Vec<StatusEffect>) -> Self {
+        let mut status = Self::default();
+        for effect in effects {
+            status.apply(effect.kind, effect.turns);
+        }
+        status
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.0.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// Adds the effect for `turns`, or makes it last that long
+    /// if they already have it and it would have ended sooner.
+    pub fn apply(&mut self, kind: StatusKind, turns: u32) {
+        if turns == 0 {
+            return;
+        }
+        match self.0.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => effect.turns = effect.turns.max(turns),
+            None => self.0.push(StatusEffect { kind, turns }),
+        }
+    }
+
+    /// Counts down a turn of every effect, removing the ones that wore off.
+    /// Returns the kinds of effect that were active for this turn.
+    pub fn tick(&mut self) -> Vec<StatusKind> {
+        let active = self.0.iter().map(|effect| effect.kind).collect();
+        for effect in &mut self.0 {
+            effect.turns -= 1;
+        }
+        self.0.retain(|effect| effect.turns > 0);
+        active
+    }
+
+    /// Their attack with the effects on it.
+    pub fn attack(&self, attack: &Attack) -> Attack {
+        let mut attack = attack.clone();
+        if self.has(StatusKind::Poison) {
+            attack.hit_chance = (attack.hit_chance - POISON_HIT_PENALTY).max(0.0);
+        }
+        attack
+    }
+
+    /// Their block chance with the effects on it.
+    pub fn block_chance(&self, block_chance: BlockChance) -> BlockChance {
+        let mut block_chance = block_chance.0;
+        if self.has(StatusKind::Shield) {
+            block_chance += SHIELD_BLOCK_BONUS;
+        }
+        if self.has(StatusKind::Guard) {
+            block_chance += GUARD_BLOCK_BONUS;
+        }
+        BlockChance(block_chance.min(1.0))
+    }
+
+    /// Their speed with the effects on it, which is never below 1.
+    pub fn speed(&self, speed: AttackSpeed) -> AttackSpeed {
+        let mut speed = speed.0;
+        if self.has(StatusKind::Haste) {
+            speed = speed * HASTE_SPEED_PERCENT / 100;
+        }
+        if self.has(StatusKind::Slow) {
+            speed = speed * SLOW_SPEED_PERCENT / 100;
+        }
+        AttackSpeed(speed.max(1))
+    }
+}
+
+#[cfg(test)]
+mod status_tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_refreshes() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusKind::Bleed, 2);
+        effects.apply(StatusKind::Bleed, 3);
+        effects.apply(StatusKind::Bleed, 1);
+        effects.apply(StatusKind::Stun, 0);
+
+        assert_eq!(
+            *effects,
+            vec![StatusEffect {
+                kind: StatusKind::Bleed,
+                turns: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn test_tick_wears_off() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusKind::Stun, 1);
+        effects.apply(StatusKind::Poison, 2);
+
+        assert_eq!(effects.tick(), vec![StatusKind::Stun, StatusKind::Poison]);
+        assert!(!effects.has(StatusKind::Stun));
+        assert_eq!(effects.tick(), vec![StatusKind::Poison]);
+        assert_eq!(effects.tick(), vec![]);
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn test_modifiers() {
+        let mut effects = StatusEffects::default();
+        let attack = Attack::new(1..5, 0.8);
+        assert_eq!(effects.attack(&attack).hit_chance, 0.8);
+        assert_eq!(effects.block_chance(BlockChance(0.1)).0, 0.1);
+
+        effects.apply(StatusKind::Poison, 1);
+        effects.apply(StatusKind::Shield, 1);
+        assert!(effects.attack(&attack).hit_chance < 0.8);
+        assert!(effects.block_chance(BlockChance(0.1)).0 > 0.1);
+        assert_eq!(effects.block_chance(BlockChance(0.9)).0, 1.0);
+    }
+
+    #[test]
+    fn test_guard_until_next_turn() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusKind::Shield, 2);
+        effects.apply(StatusKind::Guard, 1);
+        let guarded = effects.block_chance(BlockChance(0.1)).0;
+        assert!(guarded > 0.1 + SHIELD_BLOCK_BONUS);
+
+        // it wears off when their turn starts
+        effects.tick();
+        assert!(effects.block_chance(BlockChance(0.1)).0 < guarded);
+        assert!(!effects.has(StatusKind::Guard));
+    }
+
+    #[test]
+    fn test_speed() {
+        let mut effects = StatusEffects::default();
+        assert_eq!(effects.speed(AttackSpeed(4)).0, 4);
+
+        effects.apply(StatusKind::Haste, 1);
+        assert_eq!(effects.speed(AttackSpeed(4)).0, 6);
+
+        effects.apply(StatusKind::Slow, 2);
+        assert_eq!(effects.speed(AttackSpeed(4)).0, 3);
+
+        effects.tick();
+        assert_eq!(effects.speed(AttackSpeed(4)).0, 2);
+        assert_eq!(
+            effects.speed(AttackSpeed(1)).0,
+            1,
+            "never stops them acting"
+        );
+    }
+}
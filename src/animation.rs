@@ -44,10 +44,10 @@ pub struct AnimationBundle {
 }
 
 impl AnimationBundle {
-    pub fn from_name(asset_server: &AssetServer, name: ActorName) -> Self {
-        let sprite = name_to_sprite(asset_server, name);
+    pub fn from_definition(asset_server: &AssetServer, definition: &ActorDefinition) -> Self {
+        let sprite = definition_to_sprite(asset_server, definition);
 
-        let animations = AnimationConfigs::from_name(name);
+        let animations = AnimationConfigs::from_definition(definition);
 
         Self { sprite, animations }
     }
@@ -65,11 +65,17 @@ pub struct AnimationConfigs {
 }
 
 impl AnimationConfigs {
-    pub fn from_name(name: ActorName) -> Self {
+    pub fn from_definition(definition: &ActorDefinition) -> Self {
+        let AnimationDefinitions {
+            normal,
+            damaged,
+            dead,
+        } = definition.animations.clone();
+
         Self {
-            normal: AnimationConfig::from_name(ActiveAnimation::Normal, name),
-            damaged: AnimationConfig::from_name(ActiveAnimation::Damaged, name),
-            dead: AnimationConfig::from_name(ActiveAnimation::Dead, name),
+            normal,
+            damaged,
+            dead,
             active: ActiveAnimation::Normal,
             tick_count: 0,
             ticks_per_frame: NORMAL_TICK_SPEED,
@@ -108,7 +114,7 @@ pub enum ActiveAnimation {
 }
 
 /// The config for automating animation
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AnimationConfig {
     first_sprite_index: usize,
     last_sprite_index: usize,
@@ -121,47 +127,11 @@ impl AnimationConfig {
             last_sprite_index: last,
         }
     }
-
-    pub fn from_name(active: ActiveAnimation, name: ActorName) -> Self {
-        use ActiveAnimation as A;
-        use ActorName as C;
-        match (active, name) {
-            // TODO: Make Real stats Self stats accurate (I copied it from Theif)
-            (A::Normal, C::Warrior) => Self::new(0, 1),
-            (A::Damaged, C::Warrior) => Self::new(0, 1),
-            (A::Dead, C::Warrior) => Self::new(0, 1),
-
-            (A::Normal, C::Priestess) => Self::new(0, 1),
-            (A::Damaged, C::Priestess) => Self::new(0, 1),
-            (A::Dead, C::Priestess) => Self::new(0, 1),
-
-            (A::Normal, C::Theif) => Self::new(0, 1),
-            (A::Damaged, C::Theif) => Self::new(0, 1),
-            (A::Dead, C::Theif) => Self::new(0, 1),
-
-            (A::Normal, C::Ogre) => Self::new(0, 1),
-            (A::Damaged, C::Ogre) => Self::new(0, 1),
-            (A::Dead, C::Ogre) => Self::new(0, 1),
-
-            (A::Normal, C::Goblin) => Self::new(0, 1),
-            (A::Damaged, C::Goblin) => Self::new(0, 1),
-            (A::Dead, C::Goblin) => Self::new(0, 1),
-
-            (A::Normal, C::Skeleton) => Self::new(0, 1),
-            (A::Damaged, C::Skeleton) => Self::new(0, 1),
-            (A::Dead, C::Skeleton) => Self::new(0, 1),
-
-            (A::Normal, C::UnknownJim) => Self::new(0, 3),
-            (A::Damaged, C::UnknownJim) => Self::new(4, 4),
-            (A::Dead, C::UnknownJim) => Self::new(8, 8),
-        }
-    }
 }
 
-pub fn name_to_sprite(asset_server: &AssetServer, name: ActorName) -> Sprite {
-    let asset = asset_server.load(name_to_sprite_path(name));
-    let atlas_layout = name_to_atlas_layout(name);
-    let atlas_layout = asset_server.add(atlas_layout);
+pub fn definition_to_sprite(asset_server: &AssetServer, definition: &ActorDefinition) -> Sprite {
+    let asset = asset_server.load(&definition.sprite);
+    let atlas_layout = asset_server.add(definition.atlas.to_layout());
 
     let atlas = TextureAtlas {
         layout: atlas_layout,
@@ -171,38 +141,6 @@ pub fn name_to_sprite(asset_server: &AssetServer, name: ActorName) -> Sprite {
     Sprite::from_atlas_image(asset, atlas)
 }
 
-pub fn name_to_sprite_path(name: ActorName) -> String {
-    format!("embedded://assets/sprites/{}.png", name)
-}
-
-pub fn name_to_sprite_size(name: ActorName) -> UVec2 {
-    use ActorName as A;
-    match name {
-        A::Warrior => UVec2::new(32, 60),
-        A::Priestess => UVec2::new(32, 60),
-        A::Theif => UVec2::new(34, 60),
-        A::Ogre => UVec2::new(32, 60),
-        A::Goblin => UVec2::new(32, 60),
-        A::Skeleton => UVec2::new(32, 60),
-        A::UnknownJim => UVec2::new(32, 60),
-    }
-}
-
-pub fn name_to_atlas_layout(name: ActorName) -> TextureAtlasLayout {
-    use ActorName as A;
-    let (columns, rows) = match name {
-        A::Warrior => (2, 1),
-        A::Priestess => (2, 1),
-        A::Theif => (2, 1),
-        A::Ogre => (2, 1),
-        A::Goblin => (2, 1),
-        A::Skeleton => (2, 1),
-        A::UnknownJim => (4, 2),
-    };
-
-    TextureAtlasLayout::from_grid(name_to_sprite_size(name), columns, rows, None, None)
-}
-
 pub fn execute_animations(
     time: Res<Time>,
     mut frame_timer: ResMut<AnimationFrameTimer>,
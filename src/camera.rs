@@ -1,20 +1,24 @@
-use crate::generate_map::WORLD_MAP_ORIGIN;
+use crate::generate_map::{MapTilemap, WORLD_MAP_ORIGIN};
 use bevy::prelude::*;
 use bevy::render::{
     camera::RenderTarget,
     render_asset::RenderAssetUsages,
     render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
 };
+use bevy_ecs_tilemap::prelude::TilemapSize;
 
 pub const CAMERA_DEFAULT_SCALE: f32 = 1.00;
 pub const CAMERA_MAP_SCALE: f32 = 2.0;
+/// The biggest map radius that fits in the minimap at [`CAMERA_MAP_SCALE`].
+const CAMERA_MAP_RADIUS: u32 = 5;
 
 /// The plugin to enable the camera
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, camera_setup);
+        app.add_systems(Startup, camera_setup)
+            .add_systems(Update, fit_map_camera);
     }
 }
 
@@ -87,3 +91,15 @@ fn camera_setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
         Transform::from_translation(WORLD_MAP_ORIGIN),
     ));
 }
+
+/// Zooms the minimap out so bigger maps still fit in it.
+fn fit_map_camera(
+    map: Single<&TilemapSize, Added<MapTilemap>>,
+    mut projection: Single<&mut Projection, With<MapCameraMarker>>,
+) {
+    let radius = map.y.saturating_sub(1) / 2;
+    if let Projection::Orthographic(orthographic) = &mut **projection {
+        orthographic.scale =
+            CAMERA_MAP_SCALE * radius.max(CAMERA_MAP_RADIUS) as f32 / CAMERA_MAP_RADIUS as f32;
+    }
+}
@@ -547,6 +547,9 @@ pub struct Controls {
     pub zoom_out: InputList,
     pub pause: InputList,
     pub select: InputList,
+    pub defend: InputList,
+    pub wait: InputList,
+    pub flee: InputList,
 }
 
 impl Controls {
@@ -560,6 +563,9 @@ impl Controls {
             Control::ZoomOut => &mut self.zoom_out,
             Control::Pause => &mut self.pause,
             Control::Select => &mut self.select,
+            Control::Defend => &mut self.defend,
+            Control::Wait => &mut self.wait,
+            Control::Flee => &mut self.flee,
         }
     }
 
@@ -573,6 +579,9 @@ impl Controls {
             Control::ZoomOut => self.zoom_out,
             Control::Pause => self.pause,
             Control::Select => self.select,
+            Control::Defend => self.defend,
+            Control::Wait => self.wait,
+            Control::Flee => self.flee,
         }
     }
 
@@ -598,6 +607,9 @@ impl Controls {
             Control::ZoomOut => DEFAULT_ZOOM_OUT_CONTROLS,
             Control::Pause => DEFAULT_PAUSE_CONTROLS,
             Control::Select => DEFAULT_SELECT_CONTROLS,
+            Control::Defend => DEFAULT_DEFEND_CONTROLS,
+            Control::Wait => DEFAULT_WAIT_CONTROLS,
+            Control::Flee => DEFAULT_FLEE_CONTROLS,
         }
     }
 
@@ -613,6 +625,9 @@ impl Controls {
             Control::ZoomOut => DEFAULT_ZOOM_OUT_CONTROLS,
             Control::Pause => DEFAULT_PAUSE_CONTROLS,
             Control::Select => DEFAULT_SELECT_CONTROLS,
+            Control::Defend => DEFAULT_DEFEND_CONTROLS,
+            Control::Wait => DEFAULT_WAIT_CONTROLS,
+            Control::Flee => DEFAULT_FLEE_CONTROLS,
         }[i];
     }
 
@@ -631,6 +646,9 @@ impl Controls {
             zoom_out: db.get_kv(KEYBINDS_DB_TABLE, "zoom_out", DEFAULT_ZOOM_OUT_CONTROLS),
             pause: db.get_kv(KEYBINDS_DB_TABLE, "pause", DEFAULT_PAUSE_CONTROLS),
             select: db.get_kv(KEYBINDS_DB_TABLE, "select", DEFAULT_SELECT_CONTROLS),
+            defend: db.get_kv(KEYBINDS_DB_TABLE, "defend", DEFAULT_DEFEND_CONTROLS),
+            wait: db.get_kv(KEYBINDS_DB_TABLE, "wait", DEFAULT_WAIT_CONTROLS),
+            flee: db.get_kv(KEYBINDS_DB_TABLE, "flee", DEFAULT_FLEE_CONTROLS),
         }
     }
 
@@ -644,6 +662,9 @@ impl Controls {
         db.set_kv(KEYBINDS_DB_TABLE, "zoom_out", self.zoom_out)?;
         db.set_kv(KEYBINDS_DB_TABLE, "pause", self.pause)?;
         db.set_kv(KEYBINDS_DB_TABLE, "select", self.select)?;
+        db.set_kv(KEYBINDS_DB_TABLE, "defend", self.defend)?;
+        db.set_kv(KEYBINDS_DB_TABLE, "wait", self.wait)?;
+        db.set_kv(KEYBINDS_DB_TABLE, "flee", self.flee)?;
 
         Ok(())
     }
@@ -660,6 +681,9 @@ impl Default for Controls {
             zoom_out: DEFAULT_ZOOM_OUT_CONTROLS,
             pause: DEFAULT_PAUSE_CONTROLS,
             select: DEFAULT_SELECT_CONTROLS,
+            defend: DEFAULT_DEFEND_CONTROLS,
+            wait: DEFAULT_WAIT_CONTROLS,
+            flee: DEFAULT_FLEE_CONTROLS,
         }
     }
 }
@@ -696,6 +720,9 @@ impl Iterator for ControlsIter {
                 Control::ZoomOut => Keybind(Control::ZoomOut, self.controls.zoom_out),
                 Control::Pause => Keybind(Control::Pause, self.controls.pause),
                 Control::Select => Keybind(Control::Select, self.controls.select),
+                Control::Defend => Keybind(Control::Defend, self.controls.defend),
+                Control::Wait => Keybind(Control::Wait, self.controls.wait),
+                Control::Flee => Keybind(Control::Flee, self.controls.flee),
             };
 
             self.current = control.next();
@@ -716,6 +743,9 @@ pub enum Control {
     ZoomOut,
     Pause,
     Select,
+    Defend,
+    Wait,
+    Flee,
 }
 
 impl Control {
@@ -728,7 +758,10 @@ impl Control {
             Control::ZoomIn => Some(Control::ZoomOut),
             Control::ZoomOut => Some(Control::Pause),
             Control::Pause => Some(Control::Select),
-            Control::Select => None,
+            Control::Select => Some(Control::Defend),
+            Control::Defend => Some(Control::Wait),
+            Control::Wait => Some(Control::Flee),
+            Control::Flee => None,
         }
     }
 
@@ -742,6 +775,9 @@ impl Control {
             Control::ZoomOut => "Zoom Out",
             Control::Pause => "Pause",
             Control::Select => "Select",
+            Control::Defend => "Defend",
+            Control::Wait => "Wait",
+            Control::Flee => "Flee",
         }
     }
 }
@@ -779,6 +815,9 @@ const DEFAULT_SELECT_CONTROLS: InputList = [
     Some(Input::Mouse(MouseButton::Left)),
     Some(Input::Keyboard(KeyCode::KeyE)),
 ];
+const DEFAULT_DEFEND_CONTROLS: InputList = [Some(Input::Keyboard(KeyCode::KeyQ)), None];
+const DEFAULT_WAIT_CONTROLS: InputList = [Some(Input::Keyboard(KeyCode::KeyF)), None];
+const DEFAULT_FLEE_CONTROLS: InputList = [Some(Input::Keyboard(KeyCode::KeyR)), None];
 
 fn controls_sync(database: NonSend<Database>, controls: Res<Controls>) {
     match controls.to_database(&database) {
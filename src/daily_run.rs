@@ -0,0 +1,245 @@
+//! The daily run, where every player gets the same map each day,
+//! and the local leaderboard to compare how those runs went.
+use crate::game::GameState;
+use crate::generate_map::{GenerationSettings, parse_seed};
+use crate::prelude::*;
+use bevy::prelude::*;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+pub struct DailyRunPlugin;
+
+impl Plugin for DailyRunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Victory),
+            record_daily_result(RunOutcome::Victory),
+        )
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            record_daily_result(RunOutcome::GameOver),
+        );
+    }
+}
+
+/// How a run ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum RunOutcome {
+    Victory,
+    #[strum(to_string = "Game Over")]
+    GameOver,
+}
+
+/// How a daily run went, which is kept even if its save game is deleted.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DailyResult {
+    pub game_id: GameID,
+    pub date: NaiveDate,
+    pub outcome: RunOutcome,
+    /// The combat turns taken over the whole run.
+    pub turns: u64,
+    pub rooms_cleared: u64,
+    pub finished: DateTime<Utc>,
+}
+
+impl DailyResult {
+    /// Victories come first, with the fewest turns winning.
+    /// Otherwise the most rooms cleared wins, then the fewest turns.
+    /// Ties go to whoever finished first.
+    fn rank_key(&self) -> (bool, u64, u64, DateTime<Utc>) {
+        match self.outcome {
+            RunOutcome::Victory => (
+                false,
+                self.turns,
+                u64::MAX - self.rooms_cleared,
+                self.finished,
+            ),
+            RunOutcome::GameOver => (
+                true,
+                u64::MAX - self.rooms_cleared,
+                self.turns,
+                self.finished,
+            ),
+        }
+    }
+}
+
+/// How a database backend keeps the daily run leaderboard.
+pub trait LeaderboardBackend {
+    /// Stores the result of a daily run, unless a result was already
+    /// recorded for the same game. Only the first is kept, so a run
+    /// can't be won after losing it by going back to an earlier save.
+    fn record_daily_result(&self, result: &DailyResult) -> Result<(), DatabaseError>;
+    /// Every result for the day, in no particular order.
+    fn daily_results(&self, date: NaiveDate) -> Result<Vec<DailyResult>, DatabaseError>;
+}
+
+/// The results for the day, best first.
+pub fn leaderboard(
+    db: &impl LeaderboardBackend,
+    date: NaiveDate,
+) -> Result<Vec<DailyResult>, DatabaseError> {
+    let mut results = db.daily_results(date)?;
+    results.sort_by_key(DailyResult::rank_key);
+    Ok(results)
+}
+
+/// The day of the daily run being played now.
+///
+/// This is in UTC, so players in different timezones get the same map.
+pub fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+/// The seed text for the daily run of a day, which is hashed like any other word.
+pub fn daily_seed_text(date: NaiveDate) -> String {
+    format!("daily {date}")
+}
+
+/// The settings for the daily run of a day.
+/// Only the seed changes, so every run of the day is on the same map.
+pub fn daily_settings(date: NaiveDate) -> GenerationSettings {
+    let seed_text = daily_seed_text(date);
+    let seed = parse_seed(&seed_text)
+        .ok()
+        .flatten()
+        .expect("The daily seed must be a valid seed");
+
+    GenerationSettings {
+        seed,
+        seed_text,
+        daily_run: Some(date),
+        ..default()
+    }
+}
+
+/// Adds the result of the run to the leaderboard, if it is a daily run.
+fn record_daily_result(
+    outcome: RunOutcome,
+) -> impl Fn(Res<SaveGame>, Query<&RoomInfo>, NonSend<Database>) {
+    move |save, rooms, db| {
+        let Some(date) = save.daily_run else {
+            return;
+        };
+
+        let result = DailyResult {
+            game_id: save.game_id,
+            date,
+            outcome,
+            turns: save.turns,
+            rooms_cleared: rooms.iter().filter(|info| info.cleared).count() as u64,
+            finished: Utc::now(),
+        };
+
+        if let Err(err) = db.record_daily_result(&result) {
+            warn!("Failed to record the daily run with {err}");
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl LeaderboardBackend for Database {
+    fn record_daily_result(&self, result: &DailyResult) -> Result<(), DatabaseError> {
+        let outcome = ron::to_string(&result.outcome)
+            .map_err(|err| DatabaseError::ToSqlConversionFailure(Box::new(err)))?;
+
+        let query = "
+        INSERT OR IGNORE INTO DailyRun(
+            game_id,
+            date,
+            outcome,
+            turns,
+            rooms_cleared,
+            finished
+        ) VALUES(?1, ?2, ?3, ?4, ?5, ?6)";
+        self.connection.execute(
+            query,
+            (
+                result.game_id.0,
+                result.date,
+                outcome,
+                result.turns,
+                result.rooms_cleared,
+                result.finished,
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    fn daily_results(&self, date: NaiveDate) -> Result<Vec<DailyResult>, DatabaseError> {
+        self.connection
+            .prepare(
+                "SELECT game_id,date,outcome,turns,rooms_cleared,finished FROM DailyRun WHERE date = ?1",
+            )?
+            .query_map((date,), |row| {
+                let outcome: String = row.get(2)?;
+                Ok(DailyResult {
+                    game_id: GameID(row.get(0)?),
+                    date: row.get(1)?,
+                    outcome: ron::from_str(&outcome).map_err(|err| {
+                        DatabaseError::FromSqlConversionFailure(
+                            2,
+                            rusqlite::types::Type::Text,
+                            Box::new(err),
+                        )
+                    })?,
+                    turns: row.get(3)?,
+                    rooms_cleared: row.get(4)?,
+                    finished: row.get(5)?,
+                })
+            })?
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod daily_run_tests {
+    use super::*;
+
+    fn result(outcome: RunOutcome, turns: u64, rooms_cleared: u64) -> DailyResult {
+        DailyResult {
+            game_id: GameID(1),
+            date: NaiveDate::from_ymd_opt(2025, 5, 1).unwrap(),
+            outcome,
+            turns,
+            rooms_cleared,
+            finished: DateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_daily_seed() {
+        let day = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        let settings = daily_settings(day);
+        assert_eq!(settings.daily_run, Some(day));
+        assert_eq!(settings.seed, daily_settings(day).seed);
+        assert_ne!(settings.seed, daily_settings(day.succ_opt().unwrap()).seed);
+        assert_eq!(settings.map, default());
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut results = [
+            result(RunOutcome::GameOver, 10, 3),
+            result(RunOutcome::Victory, 40, 12),
+            result(RunOutcome::GameOver, 20, 8),
+            result(RunOutcome::Victory, 30, 9),
+        ];
+        results.sort_by_key(DailyResult::rank_key);
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| (result.outcome, result.turns))
+                .collect::<Vec<_>>(),
+            vec![
+                (RunOutcome::Victory, 30),
+                (RunOutcome::Victory, 40),
+                (RunOutcome::GameOver, 20),
+                (RunOutcome::GameOver, 10),
+            ]
+        );
+    }
+}
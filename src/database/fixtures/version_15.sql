@@ -0,0 +1,75 @@
+-- A version 15 database, before `generator` was added to SaveGame.
+BEGIN TRANSACTION;
+
+CREATE TABLE Version(
+  version INTEGER PRIMARY KEY
+) STRICT;
+
+INSERT INTO Version VALUES(15);
+
+CREATE TABLE Keybinds(
+    key   TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+) STRICT;
+
+CREATE TABLE Style(
+    key   TEXT PRIMARY KEY,
+    value ANY NOT NULL
+) STRICT;
+
+CREATE TABLE SaveGame(
+    game_id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    created        TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_saved     TEXT NOT NULL,
+    world_seed     INTEGER NOT NULL,
+    current_room_x INTEGER DEFAULT NULL,
+    current_room_y INTEGER DEFAULT NULL,
+    pillar_count   INTEGER DEFAULT 0,
+    FOREIGN KEY(game_id, current_room_x, current_room_y)
+        REFERENCES RoomInfo(game_id, position_x, position_y)
+        DEFERRABLE INITIALLY DEFERRED
+) STRICT;
+
+CREATE TABLE PlayerActor(
+    game_id           INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+    name              TEXT    NOT NULL,
+    health_max        INTEGER NOT NULL,
+    health_curr       INTEGER,
+    attack_damage_min INTEGER NOT NULL,
+    attack_damage_max INTEGER NOT NULL,
+    attack_speed      INTEGER NOT NULL,
+    hit_chance        REAL NOT NULL
+) STRICT;
+
+CREATE TABLE RoomInfo(
+    game_id    INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+    position_x INTEGER NOT NULL,
+    position_y INTEGER NOT NULL,
+    cleared    INTEGER NOT NULL,
+    r_type     TEXT    NOT NULL,
+    rng_seed   INTEGER NOT NULL,
+    revealed   INTEGER NOT NULL DEFAULT 0,
+    visited    INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY(game_id, position_x, position_y)
+) STRICT;
+
+CREATE TABLE Item(
+    game_id INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+    type    Text    NOT NULL
+) STRICT;
+
+CREATE TABLE SaveSnapshot(
+    snapshot_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id     INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+    kind        TEXT    NOT NULL,
+    saved       TEXT    NOT NULL,
+    data        TEXT    NOT NULL
+) STRICT;
+
+INSERT INTO SaveGame(game_id, created, last_saved, world_seed, current_room_x, current_room_y, pillar_count)
+    VALUES(1, '2025-05-01 12:00:00+00:00', '2025-05-01 12:30:00+00:00', 24301, 2, 3, 2);
+INSERT INTO RoomInfo VALUES(1, 2, 3, 1, 'Entrance', 7, 1, 1);
+INSERT INTO PlayerActor VALUES(1, 'Warrior', 75, 60, 25, 45, 4, 0.8);
+INSERT INTO Item VALUES(1, 'HealingPotion');
+
+COMMIT;
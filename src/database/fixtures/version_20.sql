@@ -0,0 +1,105 @@
+-- A version 20 database, before status effects were added.
+BEGIN TRANSACTION;
+
+CREATE TABLE Version(
+  version INTEGER PRIMARY KEY
+) STRICT;
+
+INSERT INTO Version VALUES(20);
+
+CREATE TABLE Keybinds(
+    key   TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+) STRICT;
+
+CREATE TABLE Style(
+    key   TEXT PRIMARY KEY,
+    value ANY NOT NULL
+) STRICT;
+
+CREATE TABLE SaveGame(
+    game_id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    created        TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_saved     TEXT NOT NULL,
+    world_seed     INTEGER NOT NULL,
+    current_room_x INTEGER DEFAULT NULL,
+    current_room_y INTEGER DEFAULT NULL,
+    pillar_count   INTEGER DEFAULT 0,
+    generator      TEXT NOT NULL DEFAULT 'Paths',
+    map_radius     INTEGER NOT NULL DEFAULT 5,
+    map_pillars    INTEGER NOT NULL DEFAULT 4,
+    room_weights   TEXT NOT NULL DEFAULT '(empty:1,combat:1,pit:1,item:1)',
+    enemy_density  REAL NOT NULL DEFAULT 1.0,
+    seed_text      TEXT NOT NULL DEFAULT '',
+    daily_run      TEXT DEFAULT NULL,
+    turns          INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY(game_id, current_room_x, current_room_y)
+        REFERENCES RoomInfo(game_id, position_x, position_y)
+        DEFERRABLE INITIALLY DEFERRED
+) STRICT;
+
+CREATE TABLE PlayerActor(
+    game_id           INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+    name              TEXT    NOT NULL,
+    health_max        INTEGER NOT NULL,
+    health_curr       INTEGER,
+    attack_damage_min INTEGER NOT NULL,
+    attack_damage_max INTEGER NOT NULL,
+    attack_speed      INTEGER NOT NULL,
+    hit_chance        REAL NOT NULL
+) STRICT;
+
+CREATE TABLE RoomInfo(
+    game_id    INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+    position_x INTEGER NOT NULL,
+    position_y INTEGER NOT NULL,
+    cleared    INTEGER NOT NULL,
+    r_type     TEXT    NOT NULL,
+    rng_seed   INTEGER NOT NULL,
+    revealed   INTEGER NOT NULL DEFAULT 0,
+    visited    INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY(game_id, position_x, position_y)
+) STRICT;
+
+CREATE TABLE Item(
+    game_id INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+    type    Text    NOT NULL
+) STRICT;
+
+CREATE TABLE SaveSnapshot(
+    snapshot_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id     INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+    kind        TEXT    NOT NULL,
+    saved       TEXT    NOT NULL,
+    data        TEXT    NOT NULL
+) STRICT;
+
+CREATE TABLE DailyRun(
+    game_id       INTEGER PRIMARY KEY,
+    date          TEXT    NOT NULL,
+    outcome       TEXT    NOT NULL,
+    turns         INTEGER NOT NULL,
+    rooms_cleared INTEGER NOT NULL,
+    finished      TEXT    NOT NULL
+) STRICT;
+
+CREATE TABLE RunStats(
+    game_id       INTEGER PRIMARY KEY,
+    outcome       TEXT DEFAULT NULL,
+    rooms_visited INTEGER NOT NULL,
+    fights_won    INTEGER NOT NULL,
+    pit_damage    INTEGER NOT NULL,
+    potions_used  INTEGER NOT NULL,
+    elapsed       REAL    NOT NULL,
+    actors        TEXT    NOT NULL
+) STRICT;
+
+INSERT INTO SaveGame(game_id, created, last_saved, world_seed, current_room_x, current_room_y, pillar_count, generator, map_radius, map_pillars, room_weights, enemy_density, seed_text, daily_run, turns)
+    VALUES(1, '2025-05-01 12:00:00+00:00', '2025-05-01 12:30:00+00:00', 24301, 2, 3, 2, 'Paths', 5, 4, '(empty:1,combat:1,pit:1,item:1)', 1.0, '5EED', '2025-05-01', 12);
+INSERT INTO RoomInfo VALUES(1, 2, 3, 1, 'Entrance', 7, 1, 1);
+INSERT INTO PlayerActor VALUES(1, 'Warrior', 75, 60, 25, 45, 4, 0.8);
+INSERT INTO Item VALUES(1, 'HealingPotion');
+INSERT INTO RunStats VALUES(1, NULL, 3, 1, 5, 0, 754.5, '[(name:Warrior,damage_dealt:40,damage_taken:15,misses:2,blocks:1)]');
+INSERT INTO DailyRun VALUES(1, '2025-05-01', 'GameOver', 12, 1, '2025-05-01 12:30:00+00:00');
+
+COMMIT;
@@ -0,0 +1,753 @@
+//! The key-value Database backend, used when SQLite isn't!
+//!
+//! Everything is stored as RON strings in a key-value store,
+//! grouped into tables like the SQLite backend.
+//! Natively this is a directory of RON files in the config directory.
+//! On wasm this is the browser's local storage with the `local_storage`
+//! feature, and otherwise it is kept in memory.
+use super::*;
+use crate::daily_run::{DailyResult, LeaderboardBackend};
+use crate::saving::{
+    GameID, SaveBackend, SaveData, SaveGameInfo, SnapshotID, SnapshotInfo, SnapshotKind,
+};
+use crate::stats::{RunStats, StatsBackend};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use thiserror::Error;
+
+type Version = u32;
+
+const STORAGE_VERSION: Version = 1;
+
+const VERSION_TABLE: &str = "Version";
+const SAVE_GAME_TABLE: &str = "SaveGame";
+const SNAPSHOT_TABLE: &str = "SaveSnapshot";
+const DAILY_RUN_TABLE: &str = "DailyRun";
+/// Holds the id the next new game is given.
+const GAME_ID_TABLE: &str = "GameID";
+const RUN_STATS_TABLE: &str = "RunStats";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Storage error occured: `{0}`")]
+    Storage(Box<str>),
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("File error occured: `{0}`")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to serialize value with error `{0}`")]
+    Serialize(#[from] ron::Error),
+    #[error("Failed to deserialize value with error `{0}`")]
+    Deserialize(#[from] ron::error::SpannedError),
+    #[error("Save game `{0}` not found!")]
+    SaveNotFound(i64),
+    #[error("Snapshot `{1}` of save game `{0}` not found!")]
+    SnapshotNotFound(i64, i64),
+    #[error("Version Incompatable found version `{0}`!")]
+    IncompatableVersion(Version),
+}
+pub type SetKvError = Error;
+
+/// A string key-value store, with the keys grouped into tables.
+pub trait KvStore {
+    fn get(&self, table: &str, key: &str) -> Result<Option<String>, Error>;
+    fn set(&self, table: &str, key: &str, value: &str) -> Result<(), Error>;
+    /// Removes the key if it exists.
+    fn remove(&self, table: &str, key: &str) -> Result<(), Error>;
+    /// Every key in the table, in no particular order.
+    fn keys(&self, table: &str) -> Result<Vec<String>, Error>;
+}
+
+/// The browser's local storage.
+#[cfg(all(target_arch = "wasm32", feature = "local_storage"))]
+pub struct WebStorage(web_sys::Storage);
+
+#[cfg(all(target_arch = "wasm32", feature = "local_storage"))]
+impl WebStorage {
+    /// Every key is prefixed with this, as other games
+    /// on the same site share our local storage.
+    const PREFIX: &str = "a-hex-befalls";
+
+    pub fn open() -> Result<Self, Error> {
+        web_sys::window()
+            .ok_or_else(|| Error::Storage("No browser window found".into()))?
+            .local_storage()
+            .map_err(js_error)?
+            .map(Self)
+            .ok_or_else(|| Error::Storage("Local storage is unavailable".into()))
+    }
+
+    fn table_prefix(table: &str) -> String {
+        format!("{}/{table}/", Self::PREFIX)
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "local_storage"))]
+fn js_error(err: impl std::fmt::Debug) -> Error {
+    Error::Storage(format!("{err:?}").into())
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "local_storage"))]
+impl KvStore for WebStorage {
+    fn get(&self, table: &str, key: &str) -> Result<Option<String>, Error> {
+        self.0
+            .get_item(&(Self::table_prefix(table) + key))
+            .map_err(js_error)
+    }
+
+    fn set(&self, table: &str, key: &str, value: &str) -> Result<(), Error> {
+        self.0
+            .set_item(&(Self::table_prefix(table) + key), value)
+            .map_err(js_error)
+    }
+
+    fn remove(&self, table: &str, key: &str) -> Result<(), Error> {
+        self.0
+            .remove_item(&(Self::table_prefix(table) + key))
+            .map_err(js_error)
+    }
+
+    fn keys(&self, table: &str) -> Result<Vec<String>, Error> {
+        let prefix = Self::table_prefix(table);
+        let length = self.0.length().map_err(js_error)?;
+
+        Ok((0..length)
+            .filter_map(|idx| self.0.key(idx).ok().flatten())
+            .filter_map(|key| key.strip_prefix(&prefix).map(String::from))
+            .collect())
+    }
+}
+
+/// A directory of RON files, with a
+/// directory for every table and a file for every key.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, Error> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    fn path(&self, table: &str, key: &str) -> PathBuf {
+        self.root.join(table).join(format!("{key}.ron"))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl KvStore for FileStorage {
+    fn get(&self, table: &str, key: &str) -> Result<Option<String>, Error> {
+        match std::fs::read_to_string(self.path(table, key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn set(&self, table: &str, key: &str, value: &str) -> Result<(), Error> {
+        std::fs::create_dir_all(self.root.join(table))?;
+        std::fs::write(self.path(table, key), value)?;
+        Ok(())
+    }
+
+    fn remove(&self, table: &str, key: &str) -> Result<(), Error> {
+        match std::fs::remove_file(self.path(table, key)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn keys(&self, table: &str) -> Result<Vec<String>, Error> {
+        let entries = match std::fs::read_dir(self.root.join(table)) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| name.strip_suffix(".ron"))
+                    .map(String::from)
+            })
+            .collect())
+    }
+}
+
+/// Keeps everything in memory, so it is lost when the game closes.
+#[derive(Default)]
+pub struct MemoryStorage(RefCell<HashMap<String, HashMap<String, String>>>);
+
+impl KvStore for MemoryStorage {
+    fn get(&self, table: &str, key: &str) -> Result<Option<String>, Error> {
+        Ok(self
+            .0
+            .borrow()
+            .get(table)
+            .and_then(|table| table.get(key))
+            .cloned())
+    }
+
+    fn set(&self, table: &str, key: &str, value: &str) -> Result<(), Error> {
+        self.0
+            .borrow_mut()
+            .entry(table.into())
+            .or_default()
+            .insert(key.into(), value.into());
+        Ok(())
+    }
+
+    fn remove(&self, table: &str, key: &str) -> Result<(), Error> {
+        if let Some(table) = self.0.borrow_mut().get_mut(table) {
+            table.remove(key);
+        }
+        Ok(())
+    }
+
+    fn keys(&self, table: &str) -> Result<Vec<String>, Error> {
+        Ok(self
+            .0
+            .borrow()
+            .get(table)
+            .map(|table| table.keys().cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+pub struct Database {
+    store: Box<dyn KvStore>,
+}
+
+impl Database {
+    pub fn open() -> Result<Self, Error> {
+        #[cfg(all(target_arch = "wasm32", feature = "local_storage"))]
+        let store: Box<dyn KvStore> = Box::new(WebStorage::open()?);
+
+        #[cfg(all(target_arch = "wasm32", not(feature = "local_storage")))]
+        let store: Box<dyn KvStore> = {
+            warn!("No storage available! Nothing will be kept when the game closes.");
+            Box::new(MemoryStorage::default())
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let store: Box<dyn KvStore> = {
+            let mut path = get_default_db_directory();
+            path.push("database");
+            match FileStorage::open(&path) {
+                Ok(store) => {
+                    info!("Using database directory at '{}'!", path.display());
+                    Box::new(store)
+                }
+                Err(err) => {
+                    warn!(
+                        "Failed to open database directory at '{}' with error: {err}",
+                        path.display()
+                    );
+                    Box::new(MemoryStorage::default())
+                }
+            }
+        };
+
+        Self::with_store(store)
+    }
+
+    /// Opens the database on top of the given store,
+    /// setting it up if it is empty.
+    pub fn with_store(store: Box<dyn KvStore>) -> Result<Self, Error> {
+        let db = Self { store };
+
+        match db.store.get(VERSION_TABLE, "version")? {
+            Some(version) => {
+                let version: Version = ron::from_str(&version)?;
+                if version != STORAGE_VERSION {
+                    error!(
+                        "Local storage is from version {version} when expected version {STORAGE_VERSION}!"
+                    );
+                    return Err(Error::IncompatableVersion(version));
+                }
+            }
+            None => {
+                info!("Local storage not found! Creating it!");
+                db.set_kv(VERSION_TABLE, "version", STORAGE_VERSION)?;
+            }
+        }
+
+        Ok(db)
+    }
+
+    pub fn get_kv<T>(&self, table: &str, key: &str, default: T) -> T
+    where
+        T: Serialize + DeserializeOwned + Clone,
+    {
+        match self.store.get(table, key) {
+            Err(err) => {
+                warn!("Failed to read key '{key}' from table '{table}' with error: {err}");
+                default
+            }
+            Ok(None) => {
+                warn!(
+                    "Key '{key}' not found in table '{table}' (this is expected first launch or after an update)."
+                );
+                if let Err(err) = self.set_kv(table, key, default.clone()) {
+                    warn!("Failed to set key '{key}' in table '{table}' with error: {err}")
+                }
+                default
+            }
+            Ok(Some(t)) => ron::from_str(&t).unwrap_or(default),
+        }
+    }
+
+    pub fn set_kv<T: Serialize>(&self, table: &str, key: &str, value: T) -> Result<(), SetKvError> {
+        let value = ron::to_string(&value)?;
+        self.store.set(table, key, &value)
+    }
+
+    fn save_ids(&self) -> Result<Vec<i64>, Error> {
+        Ok(self
+            .store
+            .keys(SAVE_GAME_TABLE)?
+            .iter()
+            .filter_map(|key| key.parse().ok())
+            .collect())
+    }
+
+    /// Gives out the id for a new game. Ids are never reused, as the daily
+    /// results and run stats of a game are kept after its save is deleted.
+    fn next_game_id(&self) -> Result<GameID, Error> {
+        let id = match self.store.get(GAME_ID_TABLE, "next")? {
+            Some(id) => ron::from_str(&id)?,
+            // Older storage has no counter, so start after every id already used.
+            None => {
+                let mut used = Vec::new();
+                for table in [SAVE_GAME_TABLE, DAILY_RUN_TABLE, RUN_STATS_TABLE] {
+                    used.extend(self.store.keys(table)?);
+                }
+                used.iter()
+                    .filter_map(|key| key.parse::<i64>().ok())
+                    .max()
+                    .map_or(1, |id| id + 1)
+            }
+        };
+
+        self.set_kv(GAME_ID_TABLE, "next", id + 1)?;
+        Ok(GameID(id))
+    }
+
+    /// The ids of every snapshot of a save game.
+    /// Snapshots are keyed by `<game_id>-<snapshot_id>`.
+    fn snapshot_ids(&self, game_id: GameID) -> Result<Vec<i64>, Error> {
+        let prefix = format!("{}-", game_id.0);
+        Ok(self
+            .store
+            .keys(SNAPSHOT_TABLE)?
+            .iter()
+            .filter_map(|key| key.strip_prefix(&prefix)?.parse().ok())
+            .collect())
+    }
+
+    fn read_snapshot_entry(&self, game_id: GameID, id: SnapshotID) -> Result<Snapshot, Error> {
+        let snapshot = self
+            .store
+            .get(SNAPSHOT_TABLE, &format!("{}-{}", game_id.0, id.0))?
+            .ok_or(Error::SnapshotNotFound(game_id.0, id.0))?;
+
+        Ok(ron::from_str(&snapshot)?)
+    }
+}
+
+/// Daily run results are keyed by their game id, and
+/// aren't removed when that game is deleted.
+impl LeaderboardBackend for Database {
+    fn record_daily_result(&self, result: &DailyResult) -> Result<(), Error> {
+        let key = result.game_id.0.to_string();
+        if self.store.get(DAILY_RUN_TABLE, &key)?.is_some() {
+            return Ok(());
+        }
+        self.set_kv(DAILY_RUN_TABLE, &key, result)
+    }
+
+    fn daily_results(&self, date: chrono::NaiveDate) -> Result<Vec<DailyResult>, Error> {
+        let mut results = Vec::new();
+        for key in self.store.keys(DAILY_RUN_TABLE)? {
+            let Some(result) = self.store.get(DAILY_RUN_TABLE, &key)? else {
+                continue;
+            };
+            let result: DailyResult = ron::from_str(&result)?;
+            if result.date == date {
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+}
+
+/// Run stats are keyed by their game id, and
+/// aren't removed when that game is deleted.
+impl StatsBackend for Database {
+    fn write_run_stats(&self, game_id: GameID, stats: &RunStats) -> Result<(), Error> {
+        self.set_kv(RUN_STATS_TABLE, &game_id.0.to_string(), stats)
+    }
+
+    fn read_run_stats(&self, game_id: GameID) -> Result<Option<RunStats>, Error> {
+        self.store
+            .get(RUN_STATS_TABLE, &game_id.0.to_string())?
+            .map(|stats| ron::from_str(&stats))
+            .transpose()
+            .map_err(Error::from)
+    }
+
+    fn all_run_stats(&self) -> Result<Vec<RunStats>, Error> {
+        let mut runs = Vec::new();
+        for key in self.store.keys(RUN_STATS_TABLE)? {
+            if let Some(stats) = self.store.get(RUN_STATS_TABLE, &key)? {
+                runs.push(ron::from_str(&stats)?);
+            }
+        }
+        Ok(runs)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    kind: SnapshotKind,
+    save: SaveData,
+}
+
+impl SaveBackend for Database {
+    fn new_save(&self, save: &SaveData) -> Result<GameID, Error> {
+        let game_id = self.next_game_id()?;
+        self.write_save(game_id, save)?;
+        Ok(game_id)
+    }
+
+    fn write_save(&self, game_id: GameID, save: &SaveData) -> Result<(), Error> {
+        self.set_kv(SAVE_GAME_TABLE, &game_id.0.to_string(), save)
+    }
+
+    fn read_save(&self, game_id: GameID) -> Result<SaveData, Error> {
+        let save = self
+            .store
+            .get(SAVE_GAME_TABLE, &game_id.0.to_string())?
+            .ok_or(Error::SaveNotFound(game_id.0))?;
+
+        Ok(ron::from_str(&save)?)
+    }
+
+    fn save_infos(&self) -> Result<Box<[SaveGameInfo]>, Error> {
+        let mut ids = self.save_ids()?;
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+
+        ids.into_iter()
+            .map(|id| {
+                let save = self.read_save(GameID(id))?;
+                Ok(SaveGameInfo {
+                    id: GameID(id),
+                    created: save.created.into(),
+                    last_saved: save.last_saved.into(),
+                    world_seed: save.seed,
+                    seed_text: save.seed_text,
+                })
+            })
+            .collect()
+    }
+
+    fn delete_save(&self, game_id: GameID) -> Result<(), Error> {
+        for id in self.snapshot_ids(game_id)? {
+            self.delete_snapshot(game_id, SnapshotID(id))?;
+        }
+        self.store.remove(SAVE_GAME_TABLE, &game_id.0.to_string())
+    }
+
+    fn add_snapshot(
+        &self,
+        game_id: GameID,
+        kind: SnapshotKind,
+        save: &SaveData,
+    ) -> Result<SnapshotID, Error> {
+        let id = self
+            .snapshot_ids(game_id)?
+            .into_iter()
+            .max()
+            .map_or(1, |id| id + 1);
+
+        self.set_kv(
+            SNAPSHOT_TABLE,
+            &format!("{}-{id}", game_id.0),
+            Snapshot {
+                kind,
+                save: save.clone(),
+            },
+        )?;
+
+        Ok(SnapshotID(id))
+    }
+
+    fn read_snapshot(&self, game_id: GameID, id: SnapshotID) -> Result<SaveData, Error> {
+        Ok(self.read_snapshot_entry(game_id, id)?.save)
+    }
+
+    fn snapshots(&self, game_id: GameID) -> Result<Box<[SnapshotInfo]>, Error> {
+        let mut ids = self.snapshot_ids(game_id)?;
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+
+        ids.into_iter()
+            .map(|id| {
+                let snapshot = self.read_snapshot_entry(game_id, SnapshotID(id))?;
+                Ok(SnapshotInfo {
+                    id: SnapshotID(id),
+                    kind: snapshot.kind,
+                    saved: snapshot.save.last_saved.into(),
+                })
+            })
+            .collect()
+    }
+
+    fn delete_snapshot(&self, game_id: GameID, id: SnapshotID) -> Result<(), Error> {
+        self.store
+            .remove(SNAPSHOT_TABLE, &format!("{}-{}", game_id.0, id.0))
+    }
+}
+
+#[cfg(test)]
+mod kv_tests {
+    use super::*;
+
+    /// Gets an empty directory to put a test's files in.
+    fn temp_dir(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("a-hex-befalls-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        path
+    }
+
+    fn open(path: &PathBuf) -> Database {
+        Database::with_store(Box::new(FileStorage::open(path).unwrap())).unwrap()
+    }
+
+    #[test]
+    fn test_kv() {
+        let path = temp_dir("kv");
+        let db = open(&path);
+
+        db.set_kv("Style", "font_size", 12.5).unwrap();
+        assert_eq!(db.get_kv("Style", "font_size", 0.0), 12.5);
+
+        // missing keys are set to the default
+        assert_eq!(db.get_kv("Style", "missing", 3), 3);
+        assert_eq!(
+            db.store.get("Style", "missing").unwrap().as_deref(),
+            Some("3")
+        );
+    }
+
+    #[test]
+    fn test_saves() {
+        let path = temp_dir("saves");
+        let db = open(&path);
+
+        let first = db.new_save(&SaveData::new(0xAA)).unwrap();
+        let second = db.new_save(&SaveData::new(0xBB)).unwrap();
+        assert_eq!(first.0, 1);
+        assert_eq!(second.0, 2);
+
+        let mut save = db.read_save(first).unwrap();
+        assert_eq!(save.seed, 0xAA);
+        save.pillar_count = 3;
+        db.write_save(first, &save).unwrap();
+
+        let infos = db.save_infos().unwrap();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].id.0, 2);
+        assert_eq!(infos[1].world_seed, 0xAA);
+
+        assert!(matches!(
+            db.read_save(GameID(5)),
+            Err(Error::SaveNotFound(5))
+        ));
+
+        // everything should still be there when opened again
+        drop(db);
+        let db = open(&path);
+        assert_eq!(db.read_save(first).unwrap().pillar_count, 3);
+        assert_eq!(db.read_save(second).unwrap().seed, 0xBB);
+
+        db.delete_save(first).unwrap();
+        assert!(db.read_save(first).is_err());
+        assert_eq!(db.save_infos().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        let db = Database::with_store(Box::new(MemoryStorage::default())).unwrap();
+
+        let game_id = db.new_save(&SaveData::new(0xCC)).unwrap();
+        assert_eq!(db.read_save(game_id).unwrap().seed, 0xCC);
+
+        db.delete_save(game_id).unwrap();
+        assert!(db.save_infos().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_snapshots() {
+        use crate::saving::{AUTOSAVE_SNAPSHOTS, restore_snapshot, write_snapshot};
+
+        let db = Database::with_store(Box::new(MemoryStorage::default())).unwrap();
+        let game_id = db.new_save(&SaveData::new(0xDD)).unwrap();
+        let other = db.new_save(&SaveData::new(0xEE)).unwrap();
+
+        let mut save = SaveData::new(0xDD);
+        for pillars in 0..AUTOSAVE_SNAPSHOTS as u64 + 2 {
+            save.pillar_count = pillars;
+            write_snapshot(&db, game_id, SnapshotKind::Autosave, &save).unwrap();
+        }
+        write_snapshot(&db, game_id, SnapshotKind::Manual, &save).unwrap();
+        write_snapshot(&db, game_id, SnapshotKind::Manual, &save).unwrap();
+        write_snapshot(&db, other, SnapshotKind::Manual, &save).unwrap();
+
+        let snapshots = db.snapshots(game_id).unwrap();
+        assert_eq!(snapshots.len(), AUTOSAVE_SNAPSHOTS + 1);
+        assert_eq!(snapshots[0].kind, SnapshotKind::Manual);
+
+        // the oldest autosave kept
+        let oldest = snapshots.last().unwrap().id;
+        restore_snapshot(&db, game_id, oldest).unwrap();
+        assert_eq!(db.read_save(game_id).unwrap().pillar_count, 2);
+
+        db.delete_save(game_id).unwrap();
+        assert!(db.snapshots(game_id).unwrap().is_empty());
+        assert_eq!(db.snapshots(other).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_daily_results() {
+        use crate::daily_run::{RunOutcome, leaderboard};
+        use chrono::{NaiveDate, Utc};
+
+        let db = Database::with_store(Box::new(MemoryStorage::default())).unwrap();
+        let day = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        let result = |game_id, date, outcome| DailyResult {
+            game_id: GameID(game_id),
+            date,
+            outcome,
+            turns: 10,
+            rooms_cleared: 4,
+            finished: Utc::now(),
+        };
+
+        db.record_daily_result(&result(1, day, RunOutcome::GameOver))
+            .unwrap();
+        db.record_daily_result(&result(2, day.succ_opt().unwrap(), RunOutcome::Victory))
+            .unwrap();
+        db.record_daily_result(&result(3, day, RunOutcome::Victory))
+            .unwrap();
+        // only the first result of a game is kept
+        db.record_daily_result(&result(1, day, RunOutcome::Victory))
+            .unwrap();
+        assert_eq!(
+            leaderboard(&db, day).unwrap()[1].outcome,
+            RunOutcome::GameOver
+        );
+
+        let results = leaderboard(&db, day).unwrap();
+        assert_eq!(
+            results.iter().map(|r| r.game_id.0).collect::<Vec<_>>(),
+            vec![3, 1]
+        );
+    }
+
+    #[test]
+    fn test_daily_results_kept_after_delete() {
+        use crate::daily_run::RunOutcome;
+        use chrono::{NaiveDate, Utc};
+
+        let db = Database::with_store(Box::new(MemoryStorage::default())).unwrap();
+        let day = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        let result = |game_id| DailyResult {
+            game_id,
+            date: day,
+            outcome: RunOutcome::GameOver,
+            turns: 10,
+            rooms_cleared: 4,
+            finished: Utc::now(),
+        };
+
+        let first = db.new_save(&SaveData::new(0x11)).unwrap();
+        db.record_daily_result(&result(first)).unwrap();
+        db.delete_save(first).unwrap();
+
+        // the new game doesn't get the deleted game's id
+        let second = db.new_save(&SaveData::new(0x11)).unwrap();
+        assert_ne!(first, second);
+        db.record_daily_result(&result(second)).unwrap();
+
+        let mut ids = db
+            .daily_results(day)
+            .unwrap()
+            .iter()
+            .map(|r| r.game_id.0)
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![first.0, second.0]);
+    }
+
+    #[test]
+    fn test_game_ids_from_older_storage() {
+        let db = Database::with_store(Box::new(MemoryStorage::default())).unwrap();
+        db.set_kv(SAVE_GAME_TABLE, "2", SaveData::new(0x22))
+            .unwrap();
+        db.set_kv(DAILY_RUN_TABLE, "7", ()).unwrap();
+        db.set_kv(RUN_STATS_TABLE, "9", RunStats::default())
+            .unwrap();
+
+        assert_eq!(db.new_save(&SaveData::new(0x22)).unwrap().0, 10);
+        assert_eq!(db.new_save(&SaveData::new(0x22)).unwrap().0, 11);
+    }
+
+    #[test]
+    fn test_run_stats() {
+        let db = Database::with_store(Box::new(MemoryStorage::default())).unwrap();
+        let game_id = db.new_save(&SaveData::new(0xFF)).unwrap();
+        assert_eq!(db.read_run_stats(game_id).unwrap(), None);
+
+        let mut stats = RunStats {
+            fights_won: 3,
+            ..default()
+        };
+        stats.actor(crate::prelude::ActorName::Theif).damage_dealt = 12;
+        db.write_run_stats(game_id, &stats).unwrap();
+        assert_eq!(db.read_run_stats(game_id).unwrap(), Some(stats.clone()));
+
+        db.delete_save(game_id).unwrap();
+        assert_eq!(db.all_run_stats().unwrap(), vec![stats.clone()]);
+
+        // the next game's stats don't replace the deleted game's
+        let next = db.new_save(&SaveData::new(0xFF)).unwrap();
+        assert_eq!(db.read_run_stats(next).unwrap(), None);
+        db.write_run_stats(next, &RunStats::default()).unwrap();
+        assert_eq!(db.read_run_stats(game_id).unwrap(), Some(stats));
+        assert_eq!(db.all_run_stats().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_future_version() {
+        let path = temp_dir("version");
+        let db = open(&path);
+        db.set_kv(VERSION_TABLE, "version", STORAGE_VERSION + 1)
+            .unwrap();
+        drop(db);
+
+        assert!(matches!(
+            Database::with_store(Box::new(FileStorage::open(&path).unwrap())),
+            Err(Error::IncompatableVersion(_))
+        ));
+    }
+}
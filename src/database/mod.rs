@@ -1,17 +1,17 @@
-//! TODO: Add wasm local storage backend
-
 #[cfg(not(feature = "sqlite"))]
-mod stub_backend;
+mod kv_backend;
 #[cfg(not(feature = "sqlite"))]
-pub use stub_backend::*;
+pub use kv_backend::*;
 
+#[cfg(feature = "sqlite")]
+mod schema;
 #[cfg(feature = "sqlite")]
 mod sqlite_backend;
 #[cfg(feature = "sqlite")]
 pub use sqlite_backend::*;
 
 use bevy::prelude::*;
-#[cfg(feature = "sqlite")]
+#[cfg(any(feature = "sqlite", not(target_arch = "wasm32")))]
 use std::path::PathBuf;
 
 pub struct DatabasePlugin;
@@ -37,8 +37,8 @@ pub trait ToDatabase {
 
 /// Gets the default database path in the user's home directory
 /// This isn't only for sqlite, but for anything that needs it.
-#[cfg(feature = "sqlite")]
-fn get_default_db_directory() -> PathBuf {
+#[cfg(any(feature = "sqlite", not(target_arch = "wasm32")))]
+pub fn get_default_db_directory() -> PathBuf {
     let project_dir =
         directories::ProjectDirs::from("com", "TeamCounterSpell", "A-Hex-Befalls-The-Hexagons");
     match project_dir.as_ref().map(|d| d.config_dir()) {
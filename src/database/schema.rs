@@ -0,0 +1,324 @@
+//! The SQLite schema and the migrations to get to it.
+//!
+//! [`TABLES`] is the current schema. It is used both to create
+//! new databases and to validate existing ones, so it is the only
+//! place a table needs to be changed.
+//!
+//! To change the schema:
+//! 1. Update [`TABLES`].
+//! 2. Add a [`Migration`] to the end of [`MIGRATIONS`] that turns
+//!    the last version into the new one. [`DB_VERSION`] is bumped by this.
+//! 3. Add a fixture of the old version to `fixtures/` and to the tests.
+pub type Version = i64;
+
+/// A single column in a [`Table`].
+pub struct Column {
+    pub name: &'static str,
+    /// The type as reported by SQLite, i.e. `INTEGER` or `TEXT`.
+    pub ctype: &'static str,
+    /// Anything after the type, like `NOT NULL` or `PRIMARY KEY`.
+    pub constraints: &'static str,
+}
+
+pub struct Table {
+    pub name: &'static str,
+    pub columns: &'static [Column],
+    /// Constraints on the whole table, like keys over several columns.
+    pub constraints: &'static [&'static str],
+}
+
+const fn column(name: &'static str, ctype: &'static str, constraints: &'static str) -> Column {
+    Column {
+        name,
+        ctype,
+        constraints,
+    }
+}
+
+const GAME_ID: Column = column(
+    "game_id",
+    "INTEGER",
+    "NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED",
+);
+
+/// The current schema of the database.
+pub const TABLES: &[Table] = &[
+    Table {
+        name: "Version",
+        columns: &[column("version", "INTEGER", "PRIMARY KEY")],
+        constraints: &[],
+    },
+    Table {
+        name: "Keybinds",
+        columns: &[
+            column("key", "TEXT", "PRIMARY KEY"),
+            column("value", "TEXT", "NOT NULL"),
+        ],
+        constraints: &[],
+    },
+    Table {
+        name: "Style",
+        columns: &[
+            column("key", "TEXT", "PRIMARY KEY"),
+            column("value", "ANY", "NOT NULL"),
+        ],
+        constraints: &[],
+    },
+    Table {
+        name: "SaveGame",
+        columns: &[
+            column("game_id", "INTEGER", "PRIMARY KEY AUTOINCREMENT"),
+            column("created", "TEXT", "NOT NULL DEFAULT CURRENT_TIMESTAMP"),
+            column("last_saved", "TEXT", "NOT NULL"),
+            column("world_seed", "INTEGER", "NOT NULL"),
+            column("current_room_x", "INTEGER", "DEFAULT NULL"),
+            column("current_room_y", "INTEGER", "DEFAULT NULL"),
+            column("pillar_count", "INTEGER", "DEFAULT 0"),
+            column("generator", "TEXT", "NOT NULL DEFAULT 'Paths'"),
+            column("map_radius", "INTEGER", "NOT NULL DEFAULT 5"),
+            column("map_pillars", "INTEGER", "NOT NULL DEFAULT 4"),
+            column(
+                "room_weights",
+                "TEXT",
+                "NOT NULL DEFAULT '(empty:1,combat:1,pit:1,item:1)'",
+            ),
+            column("enemy_density", "REAL", "NOT NULL DEFAULT 1.0"),
+            column("seed_text", "TEXT", "NOT NULL DEFAULT ''"),
+            column("daily_run", "TEXT", "DEFAULT NULL"),
+            column("turns", "INTEGER", "NOT NULL DEFAULT 0"),
+            column("run_stats", "TEXT", "DEFAULT NULL"),
+        ],
+        constraints: &["FOREIGN KEY(game_id, current_room_x, current_room_y)
+            REFERENCES RoomInfo(game_id, position_x, position_y)
+            DEFERRABLE INITIALLY DEFERRED"],
+    },
+    Table {
+        name: "PlayerActor",
+        columns: &[
+            GAME_ID,
+            column("name", "TEXT", "NOT NULL"),
+            column("health_max", "INTEGER", "NOT NULL"),
+            column("health_curr", "INTEGER", ""),
+            column("attack_damage_min", "INTEGER", "NOT NULL"),
+            column("attack_damage_max", "INTEGER", "NOT NULL"),
+            column("attack_speed", "INTEGER", "NOT NULL"),
+            column("hit_chance", "REAL", "NOT NULL"),
+            column("status_effects", "TEXT", "NOT NULL DEFAULT '[]'"),
+            column("level", "INTEGER", "NOT NULL DEFAULT 1"),
+            column("experience", "INTEGER", "NOT NULL DEFAULT 0"),
+        ],
+        constraints: &[],
+    },
+    Table {
+        name: "RoomInfo",
+        columns: &[
+            GAME_ID,
+            column("position_x", "INTEGER", "NOT NULL"),
+            column("position_y", "INTEGER", "NOT NULL"),
+            column("cleared", "INTEGER", "NOT NULL"),
+            column("r_type", "TEXT", "NOT NULL"),
+            column("rng_seed", "INTEGER", "NOT NULL"),
+            column("revealed", "INTEGER", "NOT NULL DEFAULT 0"),
+            column("visited", "INTEGER", "NOT NULL DEFAULT 0"),
+        ],
+        constraints: &["PRIMARY KEY(game_id, position_x, position_y)"],
+    },
+    Table {
+        name: "Item",
+        columns: &[GAME_ID, column("type", "TEXT", "NOT NULL")],
+        constraints: &[],
+    },
+    Table {
+        name: "SaveSnapshot",
+        columns: &[
+            column("snapshot_id", "INTEGER", "PRIMARY KEY AUTOINCREMENT"),
+            GAME_ID,
+            column("kind", "TEXT", "NOT NULL"),
+            column("saved", "TEXT", "NOT NULL"),
+            column("data", "TEXT", "NOT NULL"),
+        ],
+        constraints: &[],
+    },
+    Table {
+        // Not tied to a SaveGame, so results are kept when their game is deleted.
+        name: "DailyRun",
+        columns: &[
+            column("game_id", "INTEGER", "PRIMARY KEY"),
+            column("date", "TEXT", "NOT NULL"),
+            column("outcome", "TEXT", "NOT NULL"),
+            column("turns", "INTEGER", "NOT NULL"),
+            column("rooms_cleared", "INTEGER", "NOT NULL"),
+            column("finished", "TEXT", "NOT NULL"),
+        ],
+        constraints: &[],
+    },
+    Table {
+        // Also kept when the game is deleted, for the lifetime stats.
+        name: "RunStats",
+        columns: &[
+            column("game_id", "INTEGER", "PRIMARY KEY"),
+            column("outcome", "TEXT", "DEFAULT NULL"),
+            column("rooms_visited", "INTEGER", "NOT NULL"),
+            column("fights_won", "INTEGER", "NOT NULL"),
+            column("pit_damage", "INTEGER", "NOT NULL"),
+            column("potions_used", "INTEGER", "NOT NULL"),
+            column("elapsed", "REAL", "NOT NULL"),
+            column("actors", "TEXT", "NOT NULL"),
+        ],
+        constraints: &[],
+    },
+];
+
+/// A step that upgrades the database from version `from` to `from + 1`.
+pub struct Migration {
+    pub from: Version,
+    /// Run in the same transaction as every other step.
+    /// The version is updated after, so the script doesn't need to.
+    pub script: &'static str,
+}
+
+/// Every migration, oldest first.
+/// Each one must start from the version the last one ended on.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 11,
+        script: "ALTER TABLE SaveGame ADD COLUMN pillar_count INTEGER DEFAULT 0;",
+    },
+    Migration {
+        from: 12,
+        script: "
+        CREATE TABLE SaveSnapshot(
+            snapshot_id INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id     INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+            kind        TEXT    NOT NULL,
+            saved       TEXT    NOT NULL,
+            data        TEXT    NOT NULL
+        ) STRICT;
+        ",
+    },
+    Migration {
+        from: 13,
+        script: "ALTER TABLE RoomInfo ADD COLUMN revealed INTEGER NOT NULL DEFAULT 0;",
+    },
+    Migration {
+        from: 14,
+        // Cleared rooms must have been visited to be cleared.
+        script: "
+        ALTER TABLE RoomInfo ADD COLUMN visited INTEGER NOT NULL DEFAULT 0;
+        UPDATE RoomInfo SET visited = cleared;
+        ",
+    },
+    Migration {
+        from: 15,
+        // Every map before this was made by the path generator.
+        script: "ALTER TABLE SaveGame ADD COLUMN generator TEXT NOT NULL DEFAULT 'Paths';",
+    },
+    Migration {
+        from: 16,
+        // The defaults are the settings every map was made with before.
+        script: "
+        ALTER TABLE SaveGame ADD COLUMN map_radius INTEGER NOT NULL DEFAULT 5;
+        ALTER TABLE SaveGame ADD COLUMN map_pillars INTEGER NOT NULL DEFAULT 4;
+        ALTER TABLE SaveGame ADD COLUMN room_weights TEXT NOT NULL DEFAULT '(empty:1,combat:1,pit:1,item:1)';
+        ALTER TABLE SaveGame ADD COLUMN enemy_density REAL NOT NULL DEFAULT 1.0;
+        ",
+    },
+    Migration {
+        from: 17,
+        // Older games only have the number, which is shown in hex instead.
+        script: "ALTER TABLE SaveGame ADD COLUMN seed_text TEXT NOT NULL DEFAULT '';",
+    },
+    Migration {
+        from: 18,
+        // Turns weren't counted before, so older games start from none.
+        script: "
+        ALTER TABLE SaveGame ADD COLUMN daily_run TEXT DEFAULT NULL;
+        ALTER TABLE SaveGame ADD COLUMN turns INTEGER NOT NULL DEFAULT 0;
+        CREATE TABLE DailyRun(
+            game_id       INTEGER PRIMARY KEY,
+            date          TEXT    NOT NULL,
+            outcome       TEXT    NOT NULL,
+            turns         INTEGER NOT NULL,
+            rooms_cleared INTEGER NOT NULL,
+            finished      TEXT    NOT NULL
+        ) STRICT;
+        ",
+    },
+    Migration {
+        from: 19,
+        // Older games have no stats, so they start counting when next played.
+        script: "
+        CREATE TABLE RunStats(
+            game_id       INTEGER PRIMARY KEY,
+            outcome       TEXT DEFAULT NULL,
+            rooms_visited INTEGER NOT NULL,
+            fights_won    INTEGER NOT NULL,
+            pit_damage    INTEGER NOT NULL,
+            potions_used  INTEGER NOT NULL,
+            elapsed       REAL    NOT NULL,
+            actors        TEXT    NOT NULL
+        ) STRICT;
+        ",
+    },
+    Migration {
+        from: 20,
+        // Nobody had status effects before they were added.
+        script: "ALTER TABLE PlayerActor ADD COLUMN status_effects TEXT NOT NULL DEFAULT '[]';",
+    },
+    Migration {
+        from: 21,
+        // The party couldn't level up before, so they start from level 1.
+        script: "
+        ALTER TABLE PlayerActor ADD COLUMN level INTEGER NOT NULL DEFAULT 1;
+        ALTER TABLE PlayerActor ADD COLUMN experience INTEGER NOT NULL DEFAULT 0;
+        ",
+    },
+    Migration {
+        from: 22,
+        // Older games go on from the stats in RunStats instead.
+        script: "ALTER TABLE SaveGame ADD COLUMN run_stats TEXT DEFAULT NULL;",
+    },
+];
+
+/// The oldest version that can be migrated to [`DB_VERSION`].
+pub const MIN_VERSION_MIGRATEABLE: Version = MIGRATIONS[0].from;
+
+/// The current version of the database.
+pub const DB_VERSION: Version = MIN_VERSION_MIGRATEABLE + MIGRATIONS.len() as Version;
+
+/// Makes sure there are no gaps or repeats in the migrations.
+const _: () = {
+    let mut i = 0;
+    while i < MIGRATIONS.len() {
+        assert!(
+            MIGRATIONS[i].from == MIN_VERSION_MIGRATEABLE + i as Version,
+            "Migrations must be in order with no gaps"
+        );
+        i += 1;
+    }
+};
+
+/// The SQL that creates every table in [`TABLES`], at [`DB_VERSION`].
+pub fn create_schema() -> String {
+    let mut schema = String::from("BEGIN TRANSACTION;\n");
+
+    for table in TABLES {
+        let definitions = table
+            .columns
+            .iter()
+            .map(|c| format!("{} {} {}", c.name, c.ctype, c.constraints))
+            .chain(table.constraints.iter().map(|c| c.to_string()))
+            .collect::<Vec<_>>()
+            .join(",\n    ");
+
+        schema.push_str(&format!(
+            "CREATE TABLE {}(\n    {definitions}\n) STRICT;\n",
+            table.name
+        ));
+    }
+
+    schema.push_str(&format!("INSERT INTO Version VALUES({DB_VERSION});\n"));
+    schema.push_str("COMMIT;");
+
+    schema
+}
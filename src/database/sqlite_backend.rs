@@ -2,10 +2,10 @@
 //!
 //! TODO: Alert the user in the game when there is a database issue.
 //!       Be it at startup or at runtime.
+use super::schema::*;
 use super::*;
 
 use bevy::prelude::*;
-use const_format::formatcp;
 use rusqlite::Connection;
 use rusqlite::params;
 use serde::{Serialize, de::DeserializeOwned};
@@ -14,73 +14,6 @@ use thiserror::Error;
 
 pub type Error = rusqlite::Error;
 
-type Version = i64;
-
-const DB_VERSION: Version = 12;
-
-const ADD_SCHEMA: &str = formatcp!(
-    "
-    BEGIN TRANSACTION;
-
-    CREATE TABLE Version(
-      version INTEGER PRIMARY KEY
-    ) STRICT;
-
-    INSERT INTO Version VALUES({DB_VERSION});
-
-    CREATE TABLE Keybinds(
-        key   TEXT PRIMARY KEY,
-        value TEXT NOT NULL
-    ) STRICT;
-
-    CREATE TABLE Style(
-        key   TEXT PRIMARY KEY,
-        value ANY NOT NULL
-    ) STRICT;
-
-    CREATE TABLE SaveGame(
-        game_id        INTEGER PRIMARY KEY AUTOINCREMENT,
-        created        TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-        last_saved     TEXT NOT NULL,
-        world_seed     INTEGER NOT NULL,
-        current_room_x INTEGER DEFAULT NULL,
-        current_room_y INTEGER DEFAULT NULL,
-        pillar_count   INTEGER DEFAULT 0,
-        FOREIGN KEY(game_id, current_room_x, current_room_y)
-            REFERENCES RoomInfo(game_id, position_x, position_y)
-            DEFERRABLE INITIALLY DEFERRED
-    ) STRICT;
-
-    CREATE TABLE PlayerActor(
-        game_id           INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
-        name              TEXT NOT NULL,
-        health_max        INTEGER NOT NULL,
-        health_curr       INTEGER,
-        attack_damage_min INTEGER NOT NULL,
-        attack_damage_max INTEGER NOT NULL,
-        attack_speed      INTEGER NOT NULL,
-        hit_chance        REAL NOT NULL
-    ) STRICT;
-
-    CREATE TABLE RoomInfo(
-        game_id    INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
-        position_x INTEGER NOT NULL,
-        position_y INTEGER NOT NULL,
-        cleared    INTEGER NOT NULL,
-        r_type     TEXT    NOT NULL,
-        rng_seed   INTEGER NOT NULL,
-        PRIMARY KEY(game_id, position_x, position_y)
-    ) STRICT;
-
-    CREATE TABLE Item(
-        game_id INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
-        type    Text    NOT NULL
-    ) STRICT;
-
-    COMMIT;
-    "
-);
-
 pub struct Database {
     pub connection: Connection,
 }
@@ -150,7 +83,7 @@ impl Database {
             }
         } else {
             info!("Database not found! Creating it at '{}'!", path.display());
-            db.connection.execute_batch(ADD_SCHEMA)?;
+            db.connection.execute_batch(&create_schema())?;
         }
 
         info!("Running database validation checks.");
@@ -278,56 +211,19 @@ pub enum ValidateSchemaError {
     Error(#[from] Error),
 }
 
-const _: () = assert!(DB_VERSION == 12, "UPDATE VALIDATE SCRIPT");
+/// Checks the database matches [`TABLES`].
 fn validate_schema(db: &Database) -> Result<(), ValidateSchemaError> {
     db.connection
         .execute_batch("PRAGMA integrity_check; PRAGMA optimize; PRAGMA journal_mode=WAL;")?;
 
-    let game_id = ("game_id", "INTEGER");
-
-    validate_table(db, "Version", &[("version", "INTEGER")])?;
-    validate_table(db, "Keybinds", &[("key", "TEXT"), ("value", "TEXT")])?;
-    validate_table(db, "Style", &[("key", "TEXT"), ("value", "ANY")])?;
-    validate_table(
-        db,
-        "SaveGame",
-        &[
-            game_id,
-            ("created", "TEXT"),
-            ("last_saved", "TEXT"),
-            ("world_seed", "INTEGER"),
-            ("current_room_x", "INTEGER"),
-            ("current_room_y", "INTEGER"),
-            ("pillar_count", "INTEGER"),
-        ],
-    )?;
-    validate_table(
-        db,
-        "PlayerActor",
-        &[
-            game_id,
-            ("name", "TEXT"),
-            ("health_max", "INTEGER"),
-            ("health_curr", "INTEGER"),
-            ("attack_damage_min", "INTEGER"),
-            ("attack_damage_max", "INTEGER"),
-            ("attack_speed", "INTEGER"),
-            ("hit_chance", "REAL"),
-        ],
-    )?;
-    validate_table(
-        db,
-        "RoomInfo",
-        &[
-            game_id,
-            ("position_x", "INTEGER"),
-            ("position_y", "INTEGER"),
-            ("cleared", "INTEGER"),
-            ("r_type", "TEXT"),
-            ("rng_seed", "INTEGER"),
-        ],
-    )?;
-    validate_table(db, "Item", &[game_id, ("type", "TEXT")])?;
+    for table in TABLES {
+        let columns = table
+            .columns
+            .iter()
+            .map(|c| (c.name, c.ctype))
+            .collect::<Vec<_>>();
+        validate_table(db, table.name, &columns)?;
+    }
 
     Ok(())
 }
@@ -412,107 +308,62 @@ pub enum MigrationError {
     CheckVersionError(#[from] CheckVersionError),
 }
 
-const MIN_VERSION_MIGRATEABLE: Version = 11;
-/// Make sure the migrations are set up properly
-const _: () = assert!(DB_VERSION == 12, "UPDATE THE MIGRATION SCRIPT");
-
-/// MAINTENANCE: UPDATE EVERY DATABASE UPDGRADE
+/// Runs every migration from `from` up to [`DB_VERSION`] in a single transaction,
+/// so a failed step leaves the database untouched.
 fn migrate_database(db: &Database, from: Version) -> Result<(), MigrationError> {
-    assert!((MIN_VERSION_MIGRATEABLE..DB_VERSION).contains(&from));
+    let steps = usize::try_from(from - MIN_VERSION_MIGRATEABLE)
+        .ok()
+        .and_then(|start| MIGRATIONS.get(start..))
+        .filter(|steps| !steps.is_empty())
+        .ok_or(MigrationError::NoMigrationScript)?;
 
-    db.connection.execute_batch("BEGIN TRANSACTION")?;
+    let transaction = db.connection.unchecked_transaction()?;
 
-    let mut from = from;
-
-    if from == 11 {
-        db.connection.execute_batch(MIGRATE_FROM_11_TO_12)?;
-        from = 12;
+    for step in steps {
+        transaction.execute_batch(step.script)?;
+        transaction.execute("UPDATE Version SET version = ?1", [step.from + 1])?;
     }
 
-    assert_eq!(
-        from, DB_VERSION,
-        "Failed to find migration script to migrate fully."
-    );
-
     assert_eq!(
         check_version(db)?,
         VersionCompatability::Same,
-        "Migration script failed to update version"
+        "Migrations failed to reach the current version"
     );
 
-    db.connection.execute_batch("COMMIT")?;
+    transaction.commit()?;
 
     Ok(())
 }
 
-const MIGRATE_FROM_11_TO_12: &str = "
-    UPDATE Version SET version = 12;
-    ALTER TABLE SaveGame ADD COLUMN pillar_count INTEGER DEFAULT 0;
-";
-
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const VERSION_11_SCHEMA: &str = "
-    BEGIN TRANSACTION;
-
-    CREATE TABLE Version(
-      version INTEGER PRIMARY KEY
-    ) STRICT;
-
-    INSERT INTO Version VALUES(11);
-
-    CREATE TABLE Keybinds(
-        key   TEXT PRIMARY KEY,
-        value TEXT NOT NULL
-    ) STRICT;
-
-    CREATE TABLE Style(
-        key   TEXT PRIMARY KEY,
-        value ANY NOT NULL
-    ) STRICT;
-
-    CREATE TABLE SaveGame(
-        game_id        INTEGER PRIMARY KEY AUTOINCREMENT,
-        created        TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-        last_saved     TEXT NOT NULL,
-        world_seed     INTEGER NOT NULL,
-        current_room_x INTEGER DEFAULT NULL,
-        current_room_y INTEGER DEFAULT NULL,
-        FOREIGN KEY(game_id, current_room_x, current_room_y)
-            REFERENCES RoomInfo(game_id, position_x, position_y)
-            DEFERRABLE INITIALLY DEFERRED
-    ) STRICT;
-
-    CREATE TABLE PlayerActor(
-        game_id           INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
-        name              TEXT    NOT NULL,
-        health_max        INTEGER NOT NULL,
-        health_curr       INTEGER,
-        attack_damage_min INTEGER NOT NULL,
-        attack_damage_max INTEGER NOT NULL,
-        attack_speed      INTEGER NOT NULL,
-        hit_chance        REAL NOT NULL
-    ) STRICT;
-
-    CREATE TABLE RoomInfo(
-        game_id    INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
-        position_x INTEGER NOT NULL,
-        position_y INTEGER NOT NULL,
-        cleared    INTEGER NOT NULL,
-        r_type     TEXT    NOT NULL,
-        rng_seed   INTEGER NOT NULL,
-        PRIMARY KEY(game_id, position_x, position_y)
-    ) STRICT;
-
-    CREATE TABLE Item(
-        game_id INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
-        type    Text    NOT NULL
-    ) STRICT;
-
-    COMMIT;
-    ";
+    /// A database from every version that can be migrated,
+    /// each with a single save game in it.
+    const FIXTURES: &[(Version, &str)] = &[
+        (11, include_str!("fixtures/version_11.sql")),
+        (12, include_str!("fixtures/version_12.sql")),
+        (13, include_str!("fixtures/version_13.sql")),
+        (14, include_str!("fixtures/version_14.sql")),
+        (15, include_str!("fixtures/version_15.sql")),
+        (16, include_str!("fixtures/version_16.sql")),
+        (17, include_str!("fixtures/version_17.sql")),
+        (18, include_str!("fixtures/version_18.sql")),
+        (19, include_str!("fixtures/version_19.sql")),
+        (20, include_str!("fixtures/version_20.sql")),
+        (21, include_str!("fixtures/version_21.sql")),
+        (22, include_str!("fixtures/version_22.sql")),
+        (23, include_str!("fixtures/version_23.sql")),
+    ];
+
+    fn open_fixture(fixture: &str) -> Database {
+        let db = Database {
+            connection: Connection::open_in_memory().unwrap(),
+        };
+        db.connection.execute_batch(fixture).unwrap();
+        db
+    }
 
     #[test]
     pub fn test_validate() {
@@ -520,21 +371,304 @@ mod test {
             connection: Connection::open_in_memory().unwrap(),
         };
 
-        db.connection.execute_batch(ADD_SCHEMA).unwrap();
+        db.connection.execute_batch(&create_schema()).unwrap();
 
         validate_schema(&db).unwrap();
     }
 
+    #[test]
+    pub fn test_save_backend() {
+        use crate::prelude::*;
+        use crate::saving::SaveData;
+        use crate::spawn_map::RoomSave;
+        use crate::stats::RunStats;
+        use std::num::NonZero;
+
+        let db = Database {
+            connection: Connection::open_in_memory().unwrap(),
+        };
+        db.connection.execute_batch(&create_schema()).unwrap();
+
+        let game_id = db
+            .new_save(&SaveData {
+                seed_text: "5eed".into(),
+                ..SaveData::new(0x5eed)
+            })
+            .unwrap();
+
+        let mut save = db.read_save(game_id).unwrap();
+        assert_eq!(save.seed, 0x5eed);
+        assert_eq!(save.seed_text, "5eed");
+        assert!(save.current_room.is_none());
+
+        save.pillar_count = 2;
+        save.current_room = Some(UVec2::new(1, 2));
+        save.rooms.push(RoomSave {
+            position: UVec2::new(1, 2),
+            info: RoomInfo::from_type(RoomType::Pillar, 7),
+        });
+        save.actors.push(ActorSave {
+            name: ActorName::Warrior,
+            health: Health::new(NonZero::new(10).unwrap()),
+            attack: Attack::new(1..5, 0.5),
+            speed: AttackSpeed::new(3),
+            status_effects: StatusEffects::new(vec![StatusEffect {
+                kind: StatusKind::Bleed,
+                turns: 2,
+            }]),
+            level: Level {
+                level: 3,
+                experience: 40,
+            },
+        });
+        save.items.push(Item::HealingPotion);
+        let mut stats = RunStats {
+            rooms_visited: 4,
+            ..default()
+        };
+        stats.actor(ActorName::Warrior).damage_dealt = 12;
+        save.run_stats = Some(stats.clone());
+        db.write_save(game_id, &save).unwrap();
+
+        let loaded = db.read_save(game_id).unwrap();
+        assert_eq!(loaded.pillar_count, 2);
+        assert_eq!(loaded.current_room, Some(UVec2::new(1, 2)));
+        assert_eq!(loaded.rooms.len(), 1);
+        assert_eq!(loaded.rooms[0].info.r_type, RoomType::Pillar);
+        assert_eq!(loaded.actors.len(), 1);
+        assert_eq!(loaded.actors[0].name, ActorName::Warrior);
+        assert!(loaded.actors[0].status_effects.has(StatusKind::Bleed));
+        assert_eq!(loaded.actors[0].level.level, 3);
+        assert_eq!(loaded.actors[0].level.experience, 40);
+        assert_eq!(loaded.items, vec![Item::HealingPotion]);
+        assert_eq!(loaded.run_stats, Some(stats));
+
+        assert_eq!(db.save_infos().unwrap().len(), 1);
+        db.delete_save(game_id).unwrap();
+        assert!(db.save_infos().unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn test_map_settings() {
+        use crate::generate_map::{MapGeneratorKind, MapSettings};
+        use crate::room::RoomWeights;
+        use crate::saving::{SaveBackend, SaveData};
+
+        let db = Database {
+            connection: Connection::open_in_memory().unwrap(),
+        };
+        db.connection.execute_batch(&create_schema()).unwrap();
+
+        let map = MapSettings {
+            radius: 7,
+            pillars: 6,
+            room_weights: RoomWeights {
+                empty: 0,
+                combat: 3,
+                pit: 2,
+                item: 1,
+            },
+            enemy_density: 1.5,
+        };
+        let save = SaveData {
+            generator: MapGeneratorKind::WaveFunctionCollapse,
+            map: map.clone(),
+            ..SaveData::new(0x5eed)
+        };
+        let game_id = db.new_save(&save).unwrap();
+
+        let loaded = db.read_save(game_id).unwrap();
+        assert_eq!(loaded.generator, MapGeneratorKind::WaveFunctionCollapse);
+        assert_eq!(loaded.map, map);
+    }
+
+    #[test]
+    pub fn test_snapshots() {
+        use crate::saving::*;
+
+        let db = open_fixture(&create_schema());
+        let game_id = db.new_save(&SaveData::new(0x5eed)).unwrap();
+
+        let mut save = SaveData::new(0x5eed);
+        for pillars in 0..AUTOSAVE_SNAPSHOTS as u64 + 2 {
+            save.pillar_count = pillars;
+            write_snapshot(&db, game_id, SnapshotKind::Autosave, &save).unwrap();
+        }
+        write_snapshot(&db, game_id, SnapshotKind::Manual, &save).unwrap();
+        write_snapshot(&db, game_id, SnapshotKind::Manual, &save).unwrap();
+
+        let snapshots = db.snapshots(game_id).unwrap();
+        assert_eq!(snapshots.len(), AUTOSAVE_SNAPSHOTS + 1);
+        assert_eq!(snapshots[0].kind, SnapshotKind::Manual);
+
+        // the oldest autosave kept
+        let oldest = snapshots.last().unwrap().id;
+        restore_snapshot(&db, game_id, oldest).unwrap();
+        assert_eq!(db.read_save(game_id).unwrap().pillar_count, 2);
+
+        db.delete_save(game_id).unwrap();
+        assert!(db.snapshots(game_id).unwrap().is_empty());
+    }
+
     #[test]
     pub fn migrate_from_10() {
         let db = Database {
             connection: Connection::open_in_memory().unwrap(),
         };
 
-        db.connection.execute_batch(VERSION_11_SCHEMA).unwrap();
+        db.connection.execute_batch(FIXTURES[0].1).unwrap();
 
         migrate_database(&db, 11).unwrap();
 
         validate_schema(&db).unwrap();
     }
+
+    #[test]
+    pub fn test_fixture_for_every_version() {
+        let versions = FIXTURES.iter().map(|(v, _)| *v).collect::<Vec<_>>();
+        assert_eq!(
+            versions,
+            (MIN_VERSION_MIGRATEABLE..=DB_VERSION).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    pub fn test_migrate_fixtures() {
+        use crate::saving::SaveBackend;
+
+        for (version, fixture) in FIXTURES {
+            let db = open_fixture(fixture);
+
+            match check_version(&db).unwrap() {
+                VersionCompatability::Same => assert_eq!(*version, DB_VERSION),
+                VersionCompatability::Migratable(v) => {
+                    assert_eq!(v, *version);
+                    migrate_database(&db, v).unwrap();
+                }
+                other => panic!("Fixture for version {version} is {other:?}"),
+            }
+
+            assert_eq!(check_version(&db).unwrap(), VersionCompatability::Same);
+            validate_schema(&db).unwrap();
+
+            let infos = db.save_infos().unwrap();
+            assert_eq!(infos.len(), 1, "version {version}");
+            let save = db.read_save(infos[0].id).unwrap();
+            assert_eq!(save.seed, 0x5eed, "version {version}");
+            assert_eq!(save.rooms.len(), 1, "version {version}");
+            assert_eq!(save.actors.len(), 1, "version {version}");
+            assert_eq!(save.items.len(), 1, "version {version}");
+            assert_eq!(
+                save.run_stats.is_some(),
+                *version == DB_VERSION,
+                "version {version}"
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_daily_results() {
+        use crate::daily_run::{DailyResult, LeaderboardBackend, RunOutcome, leaderboard};
+        use crate::saving::{GameID, SaveBackend};
+        use chrono::{NaiveDate, Utc};
+
+        let db = open_fixture(FIXTURES.last().unwrap().1);
+        let day = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+
+        let save = db.read_save(GameID(1)).unwrap();
+        assert_eq!(save.daily_run, Some(day));
+        assert_eq!(save.turns, 12);
+
+        let victory = DailyResult {
+            game_id: GameID(2),
+            date: day,
+            outcome: RunOutcome::Victory,
+            turns: 30,
+            rooms_cleared: 10,
+            finished: Utc::now(),
+        };
+        db.record_daily_result(&victory).unwrap();
+        db.record_daily_result(&DailyResult {
+            game_id: GameID(3),
+            date: day.succ_opt().unwrap(),
+            ..victory.clone()
+        })
+        .unwrap();
+
+        let results = leaderboard(&db, day).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0], victory);
+        assert_eq!(results[1].outcome, RunOutcome::GameOver);
+        assert_eq!(results[1].turns, 12);
+
+        // only the first result of a game is kept
+        db.record_daily_result(&DailyResult {
+            game_id: GameID(1),
+            ..victory.clone()
+        })
+        .unwrap();
+        let results = leaderboard(&db, day).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].game_id, GameID(1));
+        assert_eq!(results[1].outcome, RunOutcome::GameOver);
+    }
+
+    #[test]
+    pub fn test_run_stats() {
+        use crate::prelude::*;
+        use crate::stats::{LifetimeStats, RunStats, StatsBackend};
+
+        let db = open_fixture(FIXTURES.last().unwrap().1);
+
+        let fixture = db.read_run_stats(GameID(1)).unwrap().unwrap();
+        assert_eq!(fixture.rooms_visited, 3);
+        assert_eq!(fixture.actors[0].name, ActorName::Warrior);
+        assert!(db.read_run_stats(GameID(2)).unwrap().is_none());
+
+        let mut stats = RunStats {
+            outcome: Some(crate::daily_run::RunOutcome::Victory),
+            potions_used: 2,
+            elapsed: std::time::Duration::from_secs(60),
+            ..default()
+        };
+        stats.actor(ActorName::Priestess).blocks = 4;
+        db.write_run_stats(GameID(2), &stats).unwrap();
+        assert_eq!(db.read_run_stats(GameID(2)).unwrap(), Some(stats));
+
+        // stats are kept for the lifetime stats when the game is deleted
+        db.delete_save(GameID(1)).unwrap();
+        let lifetime = LifetimeStats::from_runs(&db.all_run_stats().unwrap());
+        assert_eq!(lifetime.runs, 2);
+        assert_eq!(lifetime.victories, 1);
+        assert_eq!(lifetime.totals.potions_used, 2);
+    }
+
+    #[test]
+    pub fn test_failed_migration_rolls_back() {
+        // The current schema already has the columns older migrations add.
+        let db = open_fixture(FIXTURES.last().unwrap().1);
+        db.connection
+            .execute("UPDATE Version SET version = ?1", [MIN_VERSION_MIGRATEABLE])
+            .unwrap();
+
+        assert!(migrate_database(&db, MIN_VERSION_MIGRATEABLE).is_err());
+        assert_eq!(
+            check_version(&db).unwrap(),
+            VersionCompatability::Migratable(MIN_VERSION_MIGRATEABLE)
+        );
+    }
+
+    #[test]
+    pub fn test_no_migration() {
+        let db = open_fixture(FIXTURES.last().unwrap().1);
+
+        assert!(matches!(
+            migrate_database(&db, MIN_VERSION_MIGRATEABLE - 1),
+            Err(MigrationError::NoMigrationScript)
+        ));
+        assert!(matches!(
+            migrate_database(&db, DB_VERSION),
+            Err(MigrationError::NoMigrationScript)
+        ));
+    }
 }
@@ -0,0 +1,576 @@
+//! The abilities actors can use in combat instead of attacking,
+//! who they can be used on and what they do.
+use super::*;
+use crate::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum::Display;
+
+/// Every ability in the game.
+/// Actors are given theirs in their [`ActorDefinition`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, Display)]
+pub enum AbilityName {
+    #[strum(to_string = "Crushing Blow")]
+    CrushingBlow,
+    #[strum(to_string = "Shield Wall")]
+    ShieldWall,
+    #[strum(to_string = "Battle Cry")]
+    BattleCry,
+    #[strum(to_string = "Heal")]
+    HealTarget,
+    Prayer,
+    #[strum(to_string = "Surprise Attack")]
+    SurpriseAttack,
+    #[strum(to_string = "Fan of Knives")]
+    FanOfKnives,
+    Stab,
+    Smash,
+    Reassemble,
+}
+
+impl AbilityName {
+    /// What the ability does.
+    pub fn ability(&self) -> &'static Ability {
+        match self {
+            AbilityName::CrushingBlow => &Ability {
+                target: TargetRule::Enemy,
+                cooldown: 1,
+                effects: &[AbilityEffect::Strike {
+                    multiplier: 1.2,
+                    blockable: false,
+                }],
+            },
+            AbilityName::ShieldWall => &Ability {
+                target: TargetRule::User,
+                cooldown: 3,
+                effects: &[AbilityEffect::Afflict {
+                    kind: StatusKind::Shield,
+                    turns: 2,
+                }],
+            },
+            AbilityName::BattleCry => &Ability {
+                target: TargetRule::AllAllies,
+                cooldown: 4,
+                effects: &[AbilityEffect::Afflict {
+                    kind: StatusKind::Haste,
+                    turns: 3,
+                }],
+            },
+            AbilityName::HealTarget => &Ability {
+                target: TargetRule::Ally,
+                cooldown: 0,
+                effects: &[
+                    AbilityEffect::Heal {
+                        min: 15,
+                        max: 30,
+                        revive: true,
+                    },
+                    AbilityEffect::Afflict {
+                        kind: StatusKind::Regen,
+                        turns: 3,
+                    },
+                ],
+            },
+            AbilityName::Prayer => &Ability {
+                target: TargetRule::AllAllies,
+                cooldown: 3,
+                effects: &[AbilityEffect::Heal {
+                    min: 8,
+                    max: 16,
+                    revive: false,
+                }],
+            },
+            AbilityName::SurpriseAttack => &Ability {
+                target: TargetRule::Enemy,
+                cooldown: 2,
+                effects: &[
+                    AbilityEffect::Strike {
+                        multiplier: 1.0,
+                        blockable: true,
+                    },
+                    AbilityEffect::Afflict {
+                        kind: StatusKind::Poison,
+                        turns: 3,
+                    },
+                    AbilityEffect::ExtraTurn,
+                ],
+            },
+            AbilityName::FanOfKnives => &Ability {
+                target: TargetRule::AllEnemies,
+                cooldown: 3,
+                effects: &[
+                    AbilityEffect::Strike {
+                        multiplier: 0.6,
+                        blockable: true,
+                    },
+                    AbilityEffect::Afflict {
+                        kind: StatusKind::Slow,
+                        turns: 2,
+                    },
+                ],
+            },
+            AbilityName::Stab => &Ability {
+                target: TargetRule::Enemy,
+                cooldown: 0,
+                effects: &[
+                    AbilityEffect::Strike {
+                        multiplier: 1.0,
+                        blockable: false,
+                    },
+                    AbilityEffect::Afflict {
+                        kind: StatusKind::Bleed,
+                        turns: 3,
+                    },
+                ],
+            },
+            AbilityName::Smash => &Ability {
+                target: TargetRule::Enemy,
+                cooldown: 0,
+                effects: &[
+                    AbilityEffect::Strike {
+                        multiplier: 2.0,
+                        blockable: true,
+                    },
+                    AbilityEffect::Afflict {
+                        kind: StatusKind::Stun,
+                        turns: 1,
+                    },
+                    AbilityEffect::Recover,
+                ],
+            },
+            AbilityName::Reassemble => &Ability {
+                target: TargetRule::User,
+                cooldown: 0,
+                effects: &[AbilityEffect::Heal {
+                    min: 15,
+                    max: 30,
+                    revive: false,
+                }],
+            },
+        }
+    }
+}
+
+/// Who an ability can be used on.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum TargetRule {
+    /// A living actor on the other team.
+    Enemy,
+    /// Anyone on their own team, including the dead.
+    Ally,
+    /// Only the one using it.
+    User,
+    /// Every living actor on the other team at once.
+    AllEnemies,
+    /// Every living actor on their own team at once.
+    AllAllies,
+}
+
+impl TargetRule {
+    /// Whether a target has to be picked, rather than it
+    /// being used on the user or everyone it can be.
+    pub fn picks_target(&self) -> bool {
+        matches!(self, TargetRule::Enemy | TargetRule::Ally)
+    }
+
+    /// Whether someone on `target_team` can be targeted by a user on `team`.
+    /// [`TargetRule::User`] allows no one, as it only targets the user.
+    pub fn allows(&self, team: Team, target_team: Team, health: &Health) -> bool {
+        match self {
+            TargetRule::Enemy | TargetRule::AllEnemies => health.is_alive() && target_team != team,
+            TargetRule::Ally => target_team == team,
+            TargetRule::AllAllies => health.is_alive() && target_team == team,
+            TargetRule::User => false,
+        }
+    }
+}
+
+/// One thing an ability does, see [`Ability::use_on`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AbilityEffect {
+    /// An attack doing `multiplier` times their damage.
+    /// If it misses or is blocked, the effects after it don't happen,
+    /// and neither do the effects on the user.
+    Strike { multiplier: f32, blockable: bool },
+    /// Heals from `min` up to but not including `max`,
+    /// bringing them back from the dead if `revive`.
+    Heal { min: u32, max: u32, revive: bool },
+    /// Puts a status effect on the target.
+    Afflict { kind: StatusKind, turns: u32 },
+    /// The user goes again straight after.
+    ExtraTurn,
+    /// The user skips their next turn.
+    Recover,
+}
+
+impl AbilityEffect {
+    /// Describes the effect for the combat menu, as in "Heals 15-29 health".
+    pub fn describe(&self) -> String {
+        match *self {
+            AbilityEffect::Strike {
+                multiplier,
+                blockable,
+            } => {
+                let mut description = if multiplier == 1.0 {
+                    "Attacks".to_string()
+                } else {
+                    format!("Attacks for {:.0}% damage", multiplier * 100.0)
+                };
+                if !blockable {
+                    description.push_str(" that can't be blocked");
+                }
+                description
+            }
+            AbilityEffect::Heal { min, max, revive } => {
+                let mut description = format!("Heals {min}-{} health", max.saturating_sub(1));
+                if revive {
+                    description.push_str(", reviving the fallen");
+                }
+                description
+            }
+            AbilityEffect::Afflict { kind, turns } => match turns {
+                1 => format!("Leaves them {} for a turn", kind.describe()),
+                _ => format!("Leaves them {} for {turns} turns", kind.describe()),
+            },
+            AbilityEffect::ExtraTurn => "Goes again".to_string(),
+            AbilityEffect::Recover => "Skips the next turn".to_string(),
+        }
+    }
+}
+
+/// Something an actor can do on their turn, see [`AbilityName::ability`].
+#[derive(Debug, PartialEq)]
+pub struct Ability {
+    pub target: TargetRule,
+    /// How many of the user's turns they have to wait to use it again.
+    pub cooldown: u32,
+    /// Done to each target in order.
+    pub effects: &'static [AbilityEffect],
+}
+
+/// An actor an ability is used on.
+pub struct AbilityTarget<'a> {
+    pub combatant: Combatant,
+    pub health: &'a mut Health,
+    /// Their block chance with their status effects on it.
+    pub block_chance: BlockChance,
+    pub effects: &'a mut StatusEffects,
+}
+
+impl Ability {
+    pub fn has(&self, effect: AbilityEffect) -> bool {
+        self.effects.contains(&effect)
+    }
+
+    /// Describes every effect, as shown in the combat menu.
+    pub fn describe(&self) -> String {
+        self.effects
+            .iter()
+            .map(AbilityEffect::describe)
+            .collect::<Vec<_>>()
+            .join(". ")
+    }
+
+    /// Uses the ability on one of its targets, adding the [`CombatEvent`]s
+    /// of what happened. `attack` is the user's with their status effects on it.
+    /// Returns false if a strike missed or was blocked.
+    ///
+    /// Effects on the user, like [`AbilityEffect::ExtraTurn`], are left
+    /// to [`Ability::apply_to_user`] once it has been used on every target.
+    pub fn use_on(
+        &self,
+        events: &mut Vec<CombatEvent>,
+        user: Combatant,
+        attack: &Attack,
+        target: AbilityTarget,
+        rng: &mut impl Rng,
+    ) -> bool {
+        let combatant = target.combatant;
+
+        for effect in self.effects {
+            match *effect {
+                AbilityEffect::Strike {
+                    multiplier,
+                    blockable,
+                } => {
+                    let block_chance = match blockable {
+                        true => target.block_chance,
+                        false => BlockChance(0.0),
+                    };
+                    match attack.conduct_against(rng, block_chance) {
+                        AttackOutcome::Hit(damage) => {
+                            let damage = (damage.get() as f32 * multiplier) as u32;
+                            deal_damage(events, user, combatant, target.health, damage);
+                        }
+                        AttackOutcome::Blocked => {
+                            events.push(CombatEvent::Block {
+                                attacker: user,
+                                target: combatant,
+                            });
+                            return false;
+                        }
+                        AttackOutcome::Miss => {
+                            events.push(CombatEvent::Miss {
+                                attacker: user,
+                                target: combatant,
+                            });
+                            return false;
+                        }
+                    }
+                }
+                AbilityEffect::Heal { min, max, revive } => {
+                    let health_before = target.health.current().map_or(0, |h| h.get());
+                    let amount = rng.random_range(min..max);
+                    match revive {
+                        true => target.health.heal_or_revive(amount),
+                        false => target.health.heal(amount),
+                    }
+                    let health_after = target.health.current().map_or(0, |h| h.get());
+                    events.push(CombatEvent::Heal {
+                        healer: user,
+                        target: combatant,
+                        amount: health_after - health_before,
+                    });
+                }
+                AbilityEffect::Afflict { kind, turns } => {
+                    afflict(events, user, combatant, target.effects, kind, turns);
+                }
+                AbilityEffect::ExtraTurn | AbilityEffect::Recover => {}
+            }
+        }
+
+        true
+    }
+
+    /// Gives the user the effects of the ability on them,
+    /// which only happen if it `landed` on a target.
+    pub fn apply_to_user(&self, user: &mut EntityCommands, landed: bool) {
+        if !landed {
+            return;
+        }
+        if self.has(AbilityEffect::ExtraTurn) {
+            user.insert(ExtraTurn);
+        }
+        if self.has(AbilityEffect::Recover) {
+            user.insert(Recovering);
+        }
+    }
+}
+
+/// Marks an actor that used an ability letting them go again straight after.
+/// See [`AbilityEffect::ExtraTurn`].
+#[derive(Component, Debug)]
+pub struct ExtraTurn;
+
+/// The abilities an actor has, in the order they are shown in the combat menu.
+#[derive(Component, Debug, Default, Clone, Deref)]
+pub struct Abilities(pub Vec<AbilityName>);
+
+impl Abilities {
+    pub fn from_definition(definition: &ActorDefinition) -> Self {
+        Self(definition.abilities.clone())
+    }
+}
+
+/// The turns left before an actor can use each of their abilities again.
+/// These count down at the start of each of their turns, and are cleared between fights.
+#[derive(Component, Debug, Default, Clone)]
+pub struct Cooldowns(HashMap<AbilityName, u32>);
+
+impl Cooldowns {
+    /// Starts the cooldown of an ability that was just used.
+    pub fn start(&mut self, name: AbilityName) {
+        let cooldown = name.ability().cooldown;
+        if cooldown > 0 {
+            // The first tick is at the start of their next turn,
+            // which is already one of the turns they have to wait.
+            self.0.insert(name, cooldown + 1);
+        }
+    }
+
+    /// How many of their turns until they can use the ability, 0 if they can now.
+    pub fn remaining(&self, name: AbilityName) -> u32 {
+        self.0.get(&name).copied().unwrap_or(0)
+    }
+
+    pub fn ready(&self, name: AbilityName) -> bool {
+        self.remaining(name) == 0
+    }
+
+    /// Counts down a turn of every cooldown.
+    pub fn tick(&mut self) {
+        for turns in self.0.values_mut() {
+            *turns -= 1;
+        }
+        self.0.retain(|_, turns| *turns > 0);
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+#[cfg(test)]
+mod ability_tests {
+    use super::*;
+    use rand::SeedableRng;
+    use std::num::NonZero;
+
+    fn combatant(id: u32, name: ActorName, team: Team) -> Combatant {
+        Combatant {
+            entity: Entity::from_raw(id),
+            name,
+            team,
+        }
+    }
+
+    #[test]
+    fn test_strike() {
+        let mut rng = RandomSource::seed_from_u64(1);
+        let warrior = combatant(0, ActorName::Warrior, Team::Player);
+        let ogre = combatant(1, ActorName::Ogre, Team::Enemy);
+        let attack = Attack::new(10..11, 1.0);
+        let mut health = Health::new(NonZero::new(50).unwrap());
+        let mut effects = StatusEffects::default();
+
+        // a crushing blow can't be blocked, and does more damage
+        let mut events = Vec::new();
+        let landed = AbilityName::CrushingBlow.ability().use_on(
+            &mut events,
+            warrior,
+            &attack,
+            AbilityTarget {
+                combatant: ogre,
+                health: &mut health,
+                block_chance: BlockChance(1.0),
+                effects: &mut effects,
+            },
+            &mut rng,
+        );
+        assert!(landed);
+        assert_eq!(
+            events,
+            vec![CombatEvent::Attack {
+                attacker: warrior,
+                target: ogre,
+                damage: 12
+            }]
+        );
+        assert_eq!(health.current(), NonZero::new(38));
+    }
+
+    #[test]
+    fn test_blocked_strike_stops_effects() {
+        let mut rng = RandomSource::seed_from_u64(2);
+        let theif = combatant(0, ActorName::Theif, Team::Player);
+        let goblin = combatant(1, ActorName::Goblin, Team::Enemy);
+        let mut world = World::new();
+
+        for ability in [AbilityName::SurpriseAttack, AbilityName::Smash] {
+            let mut health = Health::new(NonZero::new(50).unwrap());
+            let mut effects = StatusEffects::default();
+            let mut events = Vec::new();
+            let landed = ability.ability().use_on(
+                &mut events,
+                theif,
+                &Attack::new(10..11, 1.0),
+                AbilityTarget {
+                    combatant: goblin,
+                    health: &mut health,
+                    block_chance: BlockChance(1.0),
+                    effects: &mut effects,
+                },
+                &mut rng,
+            );
+            assert_eq!(
+                events,
+                vec![CombatEvent::Block {
+                    attacker: theif,
+                    target: goblin
+                }]
+            );
+            assert!(effects.is_empty(), "{ability} afflicted after a block");
+            assert!(!landed);
+
+            // nor do the effects on the user
+            let user = world.spawn_empty().id();
+            ability
+                .ability()
+                .apply_to_user(&mut world.commands().entity(user), landed);
+            world.flush();
+            assert!(!world.entity(user).contains::<ExtraTurn>());
+            assert!(!world.entity(user).contains::<Recovering>());
+        }
+    }
+
+    #[test]
+    fn test_heal_revives() {
+        let mut rng = RandomSource::seed_from_u64(3);
+        let priestess = combatant(0, ActorName::Priestess, Team::Player);
+        let warrior = combatant(1, ActorName::Warrior, Team::Player);
+        let mut health = Health::with_current(None, NonZero::new(100).unwrap());
+        let mut effects = StatusEffects::default();
+
+        let mut events = Vec::new();
+        let landed = AbilityName::HealTarget.ability().use_on(
+            &mut events,
+            priestess,
+            &Attack::new(1..2, 1.0),
+            AbilityTarget {
+                combatant: warrior,
+                health: &mut health,
+                block_chance: BlockChance(0.0),
+                effects: &mut effects,
+            },
+            &mut rng,
+        );
+        assert!(landed);
+        assert!(health.is_alive());
+        assert!(effects.has(StatusKind::Regen));
+        assert!(matches!(
+            events[0],
+            CombatEvent::Heal { amount, .. } if (15..30).contains(&amount)
+        ));
+    }
+
+    #[test]
+    fn test_cooldowns() {
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.start(AbilityName::Stab);
+        assert!(cooldowns.ready(AbilityName::Stab));
+
+        // Shield Wall waits for 3 of their turns
+        cooldowns.start(AbilityName::ShieldWall);
+        for remaining in (1..=3).rev() {
+            cooldowns.tick();
+            assert_eq!(cooldowns.remaining(AbilityName::ShieldWall), remaining);
+        }
+        cooldowns.tick();
+        assert!(cooldowns.ready(AbilityName::ShieldWall));
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            AbilityName::Smash.ability().describe(),
+            "Attacks for 200% damage. Leaves them stunned for a turn. Skips the next turn"
+        );
+        assert_eq!(
+            AbilityName::HealTarget.ability().describe(),
+            "Heals 15-29 health, reviving the fallen. Leaves them regenerating for 3 turns"
+        );
+    }
+
+    #[test]
+    fn test_definition_abilities() {
+        let registry = ActorRegistry::default();
+        for name in PARTY_CLASSES {
+            assert!(!registry.get(name).abilities.is_empty(), "{name}");
+        }
+        assert_eq!(
+            registry.get(ActorName::Ogre).abilities,
+            vec![AbilityName::Smash]
+        );
+    }
+}
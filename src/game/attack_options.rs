@@ -2,14 +2,13 @@ use super::*;
 use crate::embed_asset;
 use crate::menu::*;
 use crate::prelude::*;
+use crate::stats::{RunStats, spawn_stats, stats_lines};
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
-use rand::Rng;
 use std::fmt;
 
 pub const BASIC_BUTTON_IMAGE_PATH: &str = "embedded://assets/sprites/Basic-button.png";
 pub const MOVE_BANNER_IMAGE_PATH: &str = "embedded://assets/sprites/Move Banner.png";
-pub const SPECIAL_MOVE_IMAGE_PATH: &str = "embedded://assets/sprites/Special Move.png";
 pub const BUTTON_IMAGE_PATH: &str = "embedded://assets/sprites/buttons.png";
 pub const GAMEOVER_IMAGE_PATH: &str = "embedded://assets/sprites/Game Over.png";
 pub const VICTORY_IMAGE_PATH: &str = "embedded://assets/sprites/Victory.png";
@@ -20,10 +19,11 @@ impl Plugin for AttackOptionsPlugin {
     fn build(&self, app: &mut App) {
         embed_asset!(app, "assets/sprites/Basic-button.png");
         embed_asset!(app, "assets/sprites/Move Banner.png");
-        embed_asset!(app, "assets/sprites/Special Move.png");
         embed_asset!(app, "assets/sprites/buttons.png");
         embed_asset!(app, "assets/sprites/Game Over.png");
         embed_asset!(app, "assets/sprites/Victory.png");
+
+        app.add_systems(Update, action_key.run_if(in_state(CombatState::SpawnMenu)));
     }
 }
 
@@ -33,11 +33,42 @@ pub struct AttackMenu;
 #[derive(Component)]
 pub struct TargetActor;
 
+/// A button in the attack menu for one of the acting actor's abilities.
+#[derive(Component)]
+struct AbilityButton(AbilityName);
+
+/// The actions that don't need a target, with the control for each.
+const ACTION_CONTROLS: [(Action, Control); 3] = [
+    (Action::Defend, Control::Defend),
+    (Action::Wait, Control::Wait),
+    (Action::Flee, Control::Flee),
+];
+
+/// A button in the attack menu for one of the [`ACTION_CONTROLS`].
+#[derive(Component)]
+struct ActionButton(Action);
+
+/// The [`ACTION_CONTROLS`] the acting actor can take.
+/// There is nowhere to flee to in the first room.
+fn available_actions(
+    previous_room: &Query<(), With<PreviousRoom>>,
+) -> impl Iterator<Item = (Action, Control)> {
+    let can_flee = !previous_room.is_empty();
+    ACTION_CONTROLS
+        .into_iter()
+        .filter(move |(action, _)| can_flee || !matches!(action, Action::Flee))
+}
+
 pub fn create_attack_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    style: Res<Style>,
+    keybinds: Res<Controls>,
     mut next_state: ResMut<NextState<CombatState>>,
+    active_actor: Single<(&Abilities, &Cooldowns), With<ActingActor>>,
+    previous_room: Query<(), With<PreviousRoom>>,
 ) {
+    let (abilities, cooldowns) = *active_actor;
     commands
         .spawn((
             Node {
@@ -50,6 +81,7 @@ pub fn create_attack_menu(
                 ..default()
             },
             AttackMenu,
+            StateScoped(CombatState::SpawnMenu),
         ))
         .with_children(|builder| {
             builder.spawn((
@@ -81,26 +113,109 @@ pub fn create_attack_menu(
                 ))
                 .observe(basic_attack);
 
+            for ability in abilities.iter() {
+                let remaining = cooldowns.remaining(*ability);
+                let (name, text_color) = match remaining {
+                    0 => (ability.to_string(), style.text_color),
+                    _ => (
+                        format!("{ability} ({remaining})"),
+                        style.text_color.with_alpha(0.5),
+                    ),
+                };
+
+                builder
+                    .spawn((
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::px(20.0, 20.0, 5.0, 5.0),
+                            margin: UiRect::vertical(Val::Px(2.0)),
+                            ..default()
+                        },
+                        Button,
+                        BackgroundColor(style.button_color),
+                        AbilityButton(*ability),
+                        children![
+                            (
+                                Text::new(name),
+                                style.font(28.0),
+                                TextColor(text_color),
+                                Pickable::IGNORE,
+                            ),
+                            (
+                                Text::new(ability.ability().describe()),
+                                style.font(14.0),
+                                TextColor(text_color),
+                                TextLayout::new_with_justify(JustifyText::Center),
+                                Pickable::IGNORE,
+                            )
+                        ],
+                    ))
+                    .observe(ability_click);
+            }
+
+            builder
+                .spawn(Node {
+                    column_gap: Val::Px(10.0),
+                    margin: UiRect::vertical(Val::Px(2.0)),
+                    ..default()
+                })
+                .with_children(|builder| {
+                    for (action, control) in available_actions(&previous_room) {
+                        builder
+                            .spawn((
+                                Node {
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    padding: UiRect::px(10.0, 10.0, 5.0, 5.0),
+                                    ..default()
+                                },
+                                Button,
+                                BackgroundColor(style.button_color),
+                                ActionButton(action),
+                            ))
+                            .with_children(|builder| {
+                                builder.spawn((
+                                    Text::new(control.to_string()),
+                                    style.font(28.0),
+                                    TextColor(style.text_color),
+                                    Pickable::IGNORE,
+                                ));
+                                style.display_keybind(
+                                    builder,
+                                    &Keybind(control, keybinds.get_control(control)),
+                                );
+                            })
+                            .observe(action_click);
+                    }
+                });
+
             builder
                 .spawn((
-                    ImageNode {
-                        image: asset_server.load(SPECIAL_MOVE_IMAGE_PATH),
-                        ..default()
-                    },
                     Node {
                         align_items: AlignItems::Center,
                         justify_content: JustifyContent::Center,
-                        flex_basis: Val::Px(50.0),
+                        padding: UiRect::px(20.0, 20.0, 5.0, 5.0),
                         ..default()
                     },
                     Button,
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Items"),
+                        style.font(33.0),
+                        TextColor(style.text_color),
+                        Pickable::IGNORE,
+                    )],
                 ))
-                .observe(special_move);
+                .observe(toggle_inventory_click);
         });
 }
 
-pub fn despawn_attack_menu(mut commands: Commands, menu_entity: Single<Entity, With<AttackMenu>>) {
-    commands.entity(*menu_entity).despawn();
+/// The lines of stats shown when a run ends.
+fn run_summary(stats: &RunStats, save: &SaveGame) -> Vec<String> {
+    let mut lines = vec![format!("Turns: {}", save.turns)];
+    lines.extend(stats_lines(stats));
+    lines
 }
 
 pub fn spawn_gameover_screen(
@@ -108,7 +223,10 @@ pub fn spawn_gameover_screen(
     asset_server: Res<AssetServer>,
     style: Res<Style>,
     keybinds: Res<Controls>,
+    stats: Res<RunStats>,
+    save: Res<SaveGame>,
 ) {
+    let summary = run_summary(&stats, &save);
     commands
         .spawn((
             Node {
@@ -135,6 +253,8 @@ pub fn spawn_gameover_screen(
                 },
             ));
 
+            spawn_stats(builder, &style, summary);
+
             builder
                 .spawn((Node::default(),))
                 .with_children(|builder| {
@@ -150,7 +270,10 @@ pub fn spawn_victory_screen(
 
     style: Res<Style>,
     keybinds: Res<Controls>,
+    stats: Res<RunStats>,
+    save: Res<SaveGame>,
 ) {
+    let summary = run_summary(&stats, &save);
     commands
         .spawn((
             Node {
@@ -178,6 +301,8 @@ pub fn spawn_victory_screen(
                 },
             ));
 
+            spawn_stats(builder, &style, summary);
+
             builder
                 .spawn((Node {
                     align_content: AlignContent::Center,
@@ -193,117 +318,89 @@ pub fn spawn_victory_screen(
 fn basic_attack(
     mut click: Trigger<Pointer<Click>>,
     mut commands: Commands,
-    rng: ResMut<EventRng>,
-    queue: ResMut<TurnOrder>,
-    active_actor: Single<(Entity, &Team), With<ActingActor>>,
-    actor_q: Query<(&Health, &Team)>,
+    queue: Res<TurnOrder>,
+    active_actor: Single<&Team, With<ActingActor>>,
+    actor_q: Query<(&Health, &Team, &Transform)>,
     mut next_state: ResMut<NextState<CombatState>>,
 ) {
     click.propagate(false);
 
     if click.button == PointerButton::Primary {
-        commands.insert_resource(ActingActorAction(Action::Attack {
-            target: choose_target(rng, queue, active_actor, actor_q),
-        }));
-        next_state.set(CombatState::PerformAction);
+        commands.insert_resource(TargetSelection::attack(
+            TargetKind::Attack,
+            &queue,
+            **active_actor,
+            &actor_q,
+        ));
+        next_state.set(CombatState::SelectTarget);
     }
 }
 
-fn special_move(
+/// Picks a target for the ability if it needs one, otherwise it is used straight away.
+/// Abilities that are cooling down can't be used.
+fn ability_click(
     mut click: Trigger<Pointer<Click>>,
     mut commands: Commands,
     mut next_state: ResMut<NextState<CombatState>>,
-    rng: ResMut<EventRng>,
-    queue: ResMut<TurnOrder>,
-    active_actor: Single<(Entity, &Team, &ActorName), With<ActingActor>>,
-    actor_q: Query<(&Health, &Team)>,
+    queue: Res<TurnOrder>,
+    buttons: Query<&AbilityButton>,
+    active_actor: Single<(Entity, &Team, &Cooldowns), With<ActingActor>>,
+    actor_q: Query<(&Health, &Team, &Transform)>,
 ) {
     click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
 
-    if click.button == PointerButton::Primary {
-        commands.insert_resource(ActingActorAction(Action::SpecialAction {
-            target: choose_special_target(rng, queue, active_actor, actor_q),
+    let Ok(AbilityButton(ability)) = buttons.get(click.target()) else {
+        return;
+    };
+    let (entity, team, cooldowns) = *active_actor;
+    if !cooldowns.ready(*ability) {
+        return;
+    }
+
+    if ability.ability().target.picks_target() {
+        commands.insert_resource(TargetSelection::ability(*ability, &queue, *team, &actor_q));
+        next_state.set(CombatState::SelectTarget);
+    } else {
+        commands.insert_resource(ActingActorAction(Action::Ability {
+            ability: *ability,
+            target: entity,
         }));
         next_state.set(CombatState::PerformAction);
     }
 }
 
-pub fn choose_target(
-    mut rng: ResMut<EventRng>,
-    queue: ResMut<TurnOrder>,
-    active_actor: Single<(Entity, &Team), With<ActingActor>>,
-    actor_q: Query<(&Health, &Team)>,
-) -> Entity {
-    //remove any current action
-    let (_, team) = *active_actor;
-    let targets: Vec<Entity> = queue
-        .queue()
-        .iter()
-        .filter_map(|&entity| {
-            if let Ok((health, target_team)) = actor_q.get(entity) {
-                if health.is_alive() && *target_team != *team {
-                    Some(entity)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        })
-        .collect();
+fn action_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<CombatState>>,
+    buttons: Query<&ActionButton>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
 
-    targets[rng.random_range(0..targets.len())]
+    if let Ok(ActionButton(action)) = buttons.get(click.target()) {
+        commands.insert_resource(ActingActorAction(*action));
+        next_state.set(CombatState::PerformAction);
+    }
 }
 
-pub fn choose_special_target(
-    mut rng: ResMut<EventRng>,
-    queue: ResMut<TurnOrder>,
-    active_actor: Single<(Entity, &Team, &ActorName), With<ActingActor>>,
-    actor_q: Query<(&Health, &Team)>,
-) -> Entity {
-    let (_, team, name) = *active_actor;
-    match name {
-        ActorName::Priestess => {
-            let mut players: Vec<(Entity, u32)> = queue
-                .queue()
-                .iter()
-                .filter_map(|&entity| {
-                    if let Ok((health, target_team)) = actor_q.get(entity) {
-                        if *target_team == *team {
-                            let current_health = health.current().map(|h| h.get()).unwrap_or(0);
-                            Some((entity, current_health))
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-
-            players.sort_by(|a, b| a.1.cmp(&b.1));
-
-            players[0].0
-        }
-        _ => {
-            let targets: Vec<Entity> = queue
-                .queue()
-                .iter()
-                .filter_map(|&entity| {
-                    if let Ok((health, target_team)) = actor_q.get(entity) {
-                        if health.is_alive() && *target_team != *team {
-                            Some(entity)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-
-            targets[rng.random_range(0..targets.len())]
-        }
+/// Takes the action for whichever of the [`ACTION_CONTROLS`] was pressed.
+fn action_key(
+    mut commands: Commands,
+    key: Res<ControlState>,
+    mut next_state: ResMut<NextState<CombatState>>,
+    previous_room: Query<(), With<PreviousRoom>>,
+) {
+    if let Some((action, _)) =
+        available_actions(&previous_room).find(|(_, control)| key.just_pressed(*control))
+    {
+        commands.insert_resource(ActingActorAction(action));
+        next_state.set(CombatState::PerformAction);
     }
 }
 
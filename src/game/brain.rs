@@ -0,0 +1,288 @@
+//! How enemies decide what to do on their turn.
+use super::*;
+use crate::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// How an enemy picks who to attack.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetStrategy {
+    /// Anyone still standing.
+    #[default]
+    Random,
+    /// Whoever has the least health left, to finish them off.
+    LowestHealth,
+    /// The Priestess while she is alive, so the party can't heal.
+    HealerFirst,
+}
+
+/// What an enemy knows about an actor when choosing their action.
+#[derive(Clone, Copy)]
+pub struct BrainTarget {
+    pub entity: Entity,
+    pub name: ActorName,
+    pub health: Health,
+}
+
+/// Chooses the actions of an enemy, see [`choose_action`].
+/// These are set for each actor in their [`ActorDefinition`].
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnemyBrain {
+    pub targeting: TargetStrategy,
+    /// The chance of using their ability when it would help.
+    /// Should be between 0.0 and 1.0
+    pub special_chance: f64,
+}
+
+/// Marks an enemy that used a slow ability, so they skip their next turn.
+/// See [`AbilityEffect::Recover`].
+#[derive(Component, Debug)]
+pub struct Recovering;
+
+impl EnemyBrain {
+    /// Chooses what `actor` does to the living `targets`, which asserts isn't empty.
+    /// `ability` is the one they could use this turn, if any is ready.
+    pub fn choose_action(
+        &self,
+        actor: BrainTarget,
+        targets: &[BrainTarget],
+        ability: Option<AbilityName>,
+        rng: &mut impl Rng,
+    ) -> Action {
+        let target = self.choose_target(targets, rng);
+
+        let Some(ability) = ability.filter(|ability| wants_ability(*ability, actor)) else {
+            return Action::Attack { target };
+        };
+        if !rng.random_bool(self.special_chance.clamp(0.0, 1.0)) {
+            return Action::Attack { target };
+        }
+
+        match ability.ability().target {
+            TargetRule::Enemy => Action::Ability { ability, target },
+            // The rest are used on themself or everyone at once.
+            _ => Action::Ability {
+                ability,
+                target: actor.entity,
+            },
+        }
+    }
+
+    fn choose_target(&self, targets: &[BrainTarget], rng: &mut impl Rng) -> Entity {
+        assert!(!targets.is_empty());
+
+        match self.targeting {
+            TargetStrategy::Random => targets[rng.random_range(0..targets.len())].entity,
+            TargetStrategy::LowestHealth => {
+                targets
+                    .iter()
+                    .min_by_key(|target| target.health.current().map_or(0, |h| h.get()))
+                    .unwrap()
+                    .entity
+            }
+            TargetStrategy::HealerFirst => {
+                match targets
+                    .iter()
+                    .find(|target| target.name == ActorName::Priestess)
+                {
+                    Some(healer) => healer.entity,
+                    None => targets[rng.random_range(0..targets.len())].entity,
+                }
+            }
+        }
+    }
+}
+
+/// Healing abilities are saved for when they are below half health.
+fn wants_ability(ability: AbilityName, actor: BrainTarget) -> bool {
+    let heals = ability
+        .ability()
+        .effects
+        .iter()
+        .any(|effect| matches!(effect, AbilityEffect::Heal { .. }));
+    let current = actor.health.current().map_or(0, |h| h.get());
+    !heals || current < actor.health.max().get() / 2
+}
+
+#[cfg(test)]
+mod brain_tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use rand::SeedableRng;
+    use std::num::NonZero;
+
+    /// Runs [`choose_action`] for a single enemy against the party,
+    /// with the [`EventRng`] seeded so the choices are always the same.
+    struct Harness {
+        world: World,
+        enemy: Entity,
+        party: Vec<Entity>,
+    }
+
+    impl Harness {
+        fn new(seed: u64, enemy: ActorName, brain: EnemyBrain, party: &[(ActorName, u32)]) -> Self {
+            let mut world = World::new();
+            let mut spawn = |name: ActorName, team: Team, current: u32| {
+                let health =
+                    Health::with_current(NonZero::new(current), NonZero::new(100).unwrap());
+                world.spawn((name, team, health)).id()
+            };
+
+            let party = party
+                .iter()
+                .map(|(name, current)| spawn(*name, Team::Player, *current))
+                .collect::<Vec<_>>();
+            let abilities = Abilities::from_definition(ActorRegistry::default().get(enemy));
+            let enemy = spawn(enemy, Team::Enemy, 100);
+            world
+                .entity_mut(enemy)
+                .insert((brain, abilities, ActingActor));
+
+            let queue = TurnOrder::from_speeds(
+                party
+                    .iter()
+                    .chain([&enemy])
+                    .map(|entity| (*entity, AttackSpeed(1))),
+            );
+            world.insert_resource(queue);
+            world.insert_resource(EventRng(RandomSource::seed_from_u64(seed)));
+            world.insert_resource(NextState::<CombatState>::default());
+
+            Self {
+                world,
+                enemy,
+                party,
+            }
+        }
+
+        fn set_health(&mut self, entity: Entity, current: u32) {
+            *self.world.get_mut::<Health>(entity).unwrap() =
+                Health::with_current(NonZero::new(current), NonZero::new(100).unwrap());
+        }
+
+        fn choose(&mut self) -> Action {
+            self.world.run_system_once(choose_action).unwrap();
+            self.world.resource::<ActingActorAction>().0
+        }
+    }
+
+    const PARTY: [(ActorName, u32); 3] = [
+        (ActorName::Warrior, 80),
+        (ActorName::Priestess, 60),
+        (ActorName::Theif, 40),
+    ];
+
+    #[test]
+    fn test_same_seed_same_actions() {
+        let brain = EnemyBrain {
+            targeting: TargetStrategy::Random,
+            special_chance: 0.5,
+        };
+        let choices = |seed| {
+            let mut harness = Harness::new(seed, ActorName::Goblin, brain, &PARTY);
+            (0..20).map(|_| harness.choose()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(choices(7), choices(7));
+        assert_ne!(choices(7), choices(8));
+    }
+
+    #[test]
+    fn test_lowest_health() {
+        let brain = EnemyBrain {
+            targeting: TargetStrategy::LowestHealth,
+            special_chance: 0.0,
+        };
+        let mut harness = Harness::new(1, ActorName::Goblin, brain, &PARTY);
+        let theif = harness.party[2];
+
+        for _ in 0..10 {
+            assert_eq!(harness.choose(), Action::Attack { target: theif });
+        }
+
+        // dead actors can't be targeted
+        harness.set_health(theif, 0);
+        let priestess = harness.party[1];
+        assert_eq!(harness.choose(), Action::Attack { target: priestess });
+    }
+
+    #[test]
+    fn test_healer_first() {
+        let brain = EnemyBrain {
+            targeting: TargetStrategy::HealerFirst,
+            special_chance: 0.0,
+        };
+        let mut harness = Harness::new(2, ActorName::Skeleton, brain, &PARTY);
+        let priestess = harness.party[1];
+
+        for _ in 0..10 {
+            assert_eq!(harness.choose(), Action::Attack { target: priestess });
+        }
+
+        harness.set_health(priestess, 0);
+        for _ in 0..10 {
+            assert_ne!(harness.choose(), Action::Attack { target: priestess });
+        }
+    }
+
+    #[test]
+    fn test_ogre_recovers_after_smash() {
+        let brain = EnemyBrain {
+            targeting: TargetStrategy::LowestHealth,
+            special_chance: 1.0,
+        };
+        let mut harness = Harness::new(3, ActorName::Ogre, brain, &PARTY);
+        let theif = harness.party[2];
+        let smash = Action::Ability {
+            ability: AbilityName::Smash,
+            target: theif,
+        };
+
+        assert_eq!(harness.choose(), smash);
+
+        harness.world.entity_mut(harness.enemy).insert(Recovering);
+        assert_eq!(harness.choose(), Action::SkipTurn);
+        assert!(!harness.world.entity(harness.enemy).contains::<Recovering>());
+        assert_eq!(harness.choose(), smash);
+    }
+
+    #[test]
+    fn test_skeleton_reassembles_when_hurt() {
+        let brain = EnemyBrain {
+            targeting: TargetStrategy::LowestHealth,
+            special_chance: 1.0,
+        };
+        let mut harness = Harness::new(4, ActorName::Skeleton, brain, &PARTY);
+        let theif = harness.party[2];
+
+        assert_eq!(harness.choose(), Action::Attack { target: theif });
+
+        harness.set_health(harness.enemy, 30);
+        assert_eq!(
+            harness.choose(),
+            Action::Ability {
+                ability: AbilityName::Reassemble,
+                target: harness.enemy
+            }
+        );
+    }
+
+    #[test]
+    fn test_definition_brains() {
+        let registry = ActorRegistry::default();
+        assert_eq!(
+            registry.get(ActorName::Goblin).brain.targeting,
+            TargetStrategy::LowestHealth
+        );
+        assert_eq!(
+            registry.get(ActorName::Skeleton).brain.targeting,
+            TargetStrategy::HealerFirst
+        );
+        assert!(registry.get(ActorName::Ogre).brain.special_chance > 0.0);
+        assert_eq!(
+            registry.get(ActorName::Warrior).brain,
+            EnemyBrain::default()
+        );
+    }
+}
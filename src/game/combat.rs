@@ -1,26 +1,31 @@
 use super::*;
 use crate::prelude::*;
-use crate::update_player_hp_bar;
+use crate::stats::RunStats;
+use crate::update_player_hp_bar_pit;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
-use rand::Rng;
 use std::fmt;
 
 pub struct CombatPlugin;
 const ACTOR_SPEED: f32 = 300.0;
-const DAMAGE_MULTIPLIER: f32 = 1.2;
+/// The chance of the party getting away when fleeing.
+const FLEE_CHANCE: f64 = 0.5;
 
 impl Plugin for CombatPlugin {
     fn build(&self, app: &mut App) {
-        app.add_sub_state::<CombatState>();
+        app.add_sub_state::<CombatState>()
+            .add_event::<CombatEvent>();
 
         #[cfg(feature = "debug")]
         app.add_systems(Update, log_transitions::<CombatState>);
         app.add_systems(
             OnEnter(GameState::Combat),
-            (setup_turn_order, store_actor_positions),
+            (setup_turn_order, store_actor_positions, clear_cooldowns),
+        )
+        .add_systems(
+            OnEnter(CombatState::TurnSetup),
+            (prep_turn_order, tick_status_effects, tick_cooldowns).chain(),
         )
-        .add_systems(OnEnter(CombatState::TurnSetup), prep_turn_order)
         .add_systems(OnEnter(CombatState::MoveToCenter), move_to_center)
         .add_systems(OnEnter(CombatState::MoveBack), move_back)
         .add_systems(
@@ -33,15 +38,15 @@ impl Plugin for CombatPlugin {
             OnEnter(CombatState::SpawnMenu),
             attack_options::create_attack_menu,
         )
-        .add_systems(
-            OnEnter(CombatState::PerformAction),
-            (despawn_attack_menu, perform_action).chain(),
-        )
+        .add_systems(OnEnter(CombatState::PerformAction), perform_action)
         .add_systems(
             Update,
             (move_to_target, move_back_check).run_if(in_state(CombatState::MoveBack)),
         )
-        .add_systems(OnEnter(CombatState::EndOfTurn), end_turn)
+        .add_systems(
+            OnEnter(CombatState::EndOfTurn),
+            (update_timeline_speeds, end_turn).chain(),
+        )
         .add_systems(OnExit(GameState::Combat), cleanup_positions);
     }
 }
@@ -58,8 +63,7 @@ impl Plugin for CombatPlugin {
 pub enum CombatState {
     /// Everything to set up the turn that is about to come
     ///
-    /// [`ActingActor`] is front of queue
-    /// Asserts it is not empty
+    /// [`ActingActor`] is the active actor of the [`TurnOrder`]
     ///
     /// Set [`ActingActor`]
     #[default]
@@ -70,6 +74,9 @@ pub enum CombatState {
     MoveToCenter,
     /// Spawns Menu
     SpawnMenu,
+    /// The player picks who their action is used on.
+    /// See [`TargetSelection`]
+    SelectTarget,
     /// Checks which Team is Attacking
     CheckTeam,
     /// Monster Attack
@@ -91,7 +98,7 @@ pub enum CombatState {
     /// Update: Move [`AttackingActor`]
     MoveBack,
     /// If both teams are alive, move to [`TurnSetup`]
-    /// Rotate Left [`TurnOrder`]
+    /// Move the [`TurnOrder`] on to the next actor
     EndOfTurn,
 }
 
@@ -124,15 +131,23 @@ pub enum Action {
     Attack {
         target: Entity,
     },
-    // TBD
-    SpecialAction {
+    /// The actor uses one of their [`Abilities`]. The `target` is
+    /// ignored by abilities that are used on everyone they can be.
+    Ability {
+        ability: AbilityName,
         target: Entity,
     },
-    /// The actor does damage to the `target`
+    /// The actor uses up an item on the `target`
     UseItem {
-        item: (),
+        item: Item,
         target: Entity,
     },
+    /// The actor guards, making them more likely to block until their next turn.
+    Defend,
+    /// The actor puts off their turn, see [`TurnOrder::wait`].
+    Wait,
+    /// The actor tries to get the party away to the room they came from.
+    Flee,
     SkipTurn,
 }
 
@@ -150,99 +165,383 @@ pub struct ActorOriginalPosition(pub Vec2);
 #[derive(Component, Deref, DerefMut)]
 pub struct ActorTargetPosition(pub Vec2);
 
+/// The acting actor chose to wait, so they move along
+/// the [`TurnOrder`] less than usual at the end of their turn.
+#[derive(Component)]
+pub struct Waiting;
+
+/// The acting actor got the party away, so the fight
+/// ends at the end of their turn.
+#[derive(Component)]
+pub struct Fled;
+
 ////////////RESOURCES//////////////////
 /// The action being taken by the acting actor
 #[derive(Resource, Deref, DerefMut)]
 pub struct ActingActorAction(pub Action);
 
-/// The combat queue of actors
-#[derive(Resource, Debug)]
+/// The time an actor with a speed of 1 waits between turns on the [`TurnOrder`].
+/// Faster actors wait this divided by their speed, so they act more often.
+const TURN_DELAY: u64 = 1200;
+
+/// An actor's place on the [`TurnOrder`] timeline.
+#[derive(Debug, Clone, Copy)]
+struct TimelineEntry {
+    entity: Entity,
+    speed: u32,
+    /// The time of their next turn.
+    next: u64,
+    /// Whether their next turn resumes one they waited on.
+    waited: bool,
+}
+
+/// The time an actor with `speed` waits between turns.
+fn turn_delay(speed: u32) -> u64 {
+    TURN_DELAY / speed.max(1) as u64
+}
+
+/// The combat timeline. Between turns each actor waits a time based on
+/// their speed, so faster actors act more often. When two actors would act
+/// at the same time, the faster one goes first, then whoever was given first.
+#[derive(Resource, Debug, Clone)]
 pub struct TurnOrder {
-    queue: VecDeque<Entity>,
+    /// Every actor in the fight, in the order ties are broken in.
+    timeline: Vec<TimelineEntry>,
+    /// The index in `timeline` of the active actor.
+    active: usize,
+    /// The time of the active actor's turn.
+    now: u64,
+    /// Whether the active actor's turn resumes one they waited on.
+    resumed: bool,
 }
 
 impl TurnOrder {
-    pub fn new(actor_q: Query<Entity, With<Actor>>, speed_q: Query<&AttackSpeed>) -> Self {
-        let mut queue = actor_q.iter().collect::<VecDeque<_>>();
+    /// Makes the timeline for every actor, with their status effects on their speed.
+    pub fn new(actor_q: Query<(Entity, &AttackSpeed, &StatusEffects), With<Actor>>) -> Self {
+        Self::from_speeds(
+            actor_q
+                .iter()
+                .map(|(entity, speed, effects)| (entity, effects.speed(*speed))),
+        )
+    }
+
+    /// Makes the timeline from the given actors and their speeds.
+    /// This doesn't need a [`World`], so it can be used outside of the game.
+    ///
+    /// Asserts there is at least one actor.
+    pub fn from_speeds(actors: impl IntoIterator<Item = (Entity, AttackSpeed)>) -> Self {
+        let mut timeline = actors
+            .into_iter()
+            .map(|(entity, speed)| TimelineEntry {
+                entity,
+                speed: speed.0,
+                next: turn_delay(speed.0),
+                waited: false,
+            })
+            .collect::<Vec<_>>();
+        // This is stable, so equal speeds keep the order they were given in.
+        timeline.sort_by_key(|entry| std::cmp::Reverse(entry.speed));
 
-        queue.shrink_to_fit();
-        queue
-            .make_contiguous()
-            .sort_by_cached_key(|entity| speed_q.get(*entity).unwrap().0);
+        let active = (0..timeline.len())
+            .min_by_key(|idx| timeline[*idx].next)
+            .unwrap();
+        let now = timeline[active].next;
 
-        Self { queue }
+        Self {
+            timeline,
+            active,
+            now,
+            resumed: false,
+        }
     }
 
     /// Gets the active actor.
-    /// asserts that the queue isn't empty
     pub fn active(&self) -> Entity {
-        *self.queue.back().unwrap()
+        self.timeline[self.active].entity
+    }
+
+    /// Whether the active actor's turn resumes one they waited on,
+    /// in which case their status effects and cooldowns have already ticked.
+    pub fn resumed(&self) -> bool {
+        self.resumed
     }
 
-    /// Should be called at end of turn to set the first actor in the
-    /// queue as the first elegible actor to take a turn (i.e. skipping over dead actors)
+    /// Should be called at end of turn to move the active actor
+    /// along the timeline by their speed, and make whoever is living
+    /// that comes next the active actor.
     ///
     /// Asserts at least 1 actor is left alive.
     pub fn skip_to_next(&mut self, health_q: Query<&Health>) {
-        let idx = self
-            .queue
+        self.skip_to_next_by(|entity| health_q.get(entity).ok().copied());
+    }
+
+    /// The same as [`TurnOrder::skip_to_next`], but gets the health
+    /// of each actor from `get_health` instead of a query.
+    pub fn skip_to_next_by(&mut self, get_health: impl Fn(Entity) -> Option<Health>) {
+        assert!(self.advance(self.now + self.active_delay(), get_health));
+    }
+
+    /// Used instead of [`TurnOrder::skip_to_next`] when the active actor waits.
+    /// Their turn resumes straight after the next living actor's turn,
+    /// so waiting lets someone else go first without losing them a turn.
+    /// If nobody else is alive, their turn ends as usual.
+    ///
+    /// Asserts at least 1 actor is left alive.
+    pub fn wait(&mut self, health_q: Query<&Health>) {
+        self.wait_by(|entity| health_q.get(entity).ok().copied());
+    }
+
+    /// The same as [`TurnOrder::wait`], but gets the health
+    /// of each actor from `get_health` instead of a query.
+    pub fn wait_by(&mut self, get_health: impl Fn(Entity) -> Option<Health>) {
+        let now = self.now;
+        let next_other = self
+            .timeline
             .iter()
-            .rev()
             .enumerate()
-            .skip(1)
-            .filter_map(|(idx, entity)| health_q.get(*entity).ok().map(|a| (idx, a)))
-            .find_map(|(idx, health)| health.is_alive().then_some(idx))
-            .unwrap();
+            .filter(|(idx, entry)| {
+                *idx != self.active && get_health(entry.entity).is_some_and(|h| h.is_alive())
+            })
+            .map(|(_, entry)| entry.next.max(now))
+            .min();
+
+        let next_turn = match next_other {
+            Some(next_other) => {
+                self.timeline[self.active].waited = true;
+                next_other + 1
+            }
+            None => now + self.active_delay(),
+        };
+        assert!(self.advance(next_turn, get_health));
+    }
+
+    /// The time the active actor usually waits between turns.
+    fn active_delay(&self) -> u64 {
+        turn_delay(self.timeline[self.active].speed)
+    }
+
+    /// Moves the active actor's next turn to `next_turn`, then moves
+    /// to the next living actor, returning false if there isn't one.
+    fn advance(&mut self, next_turn: u64, get_health: impl Fn(Entity) -> Option<Health>) -> bool {
+        let now = self.now;
+        self.timeline[self.active].next = next_turn;
+
+        // Anyone revived since their turn came up goes straight away.
+        let Some(next) = (0..self.timeline.len())
+            .filter(|idx| get_health(self.timeline[*idx].entity).is_some_and(|h| h.is_alive()))
+            .min_by_key(|idx| (self.timeline[*idx].next.max(now), *idx))
+        else {
+            return false;
+        };
+
+        self.active = next;
+        self.now = self.timeline[next].next.max(now);
+        self.resumed = std::mem::take(&mut self.timeline[next].waited);
+        true
+    }
+
+    /// Changes an actor's speed, moving their next turn sooner or
+    /// later by how much of their wait is left.
+    pub fn set_speed(&mut self, entity: Entity, speed: AttackSpeed) {
+        let now = self.now;
+        let Some(entry) = self
+            .timeline
+            .iter_mut()
+            .find(|entry| entry.entity == entity)
+        else {
+            return;
+        };
+        if entry.speed == speed.0 {
+            return;
+        }
 
-        // + 1 because we skipped one
-        self.queue.rotate_right(idx);
+        let waiting = entry.next.saturating_sub(now);
+        entry.next = now + waiting * turn_delay(speed.0) / turn_delay(entry.speed);
+        entry.speed = speed.0;
+    }
 
-        assert!(health_q.get(self.active()).unwrap().is_alive());
+    /// Who takes the next `count` turns, starting with the active actor,
+    /// if nobody's speed changes or anyone else dies.
+    pub fn upcoming(
+        &self,
+        count: usize,
+        get_health: impl Fn(Entity) -> Option<Health>,
+    ) -> Vec<Entity> {
+        let mut timeline = self.clone();
+        let mut turns = vec![timeline.active()];
+        while turns.len() < count
+            && timeline.advance(timeline.now + timeline.active_delay(), &get_health)
+        {
+            turns.push(timeline.active());
+        }
+        turns
     }
 
     pub fn teams_alive(&mut self, actor_q: Query<(&Health, &Team)>) -> TeamAlive {
-        self.queue
-            .iter()
-            .map(|e| actor_q.get(*e).unwrap())
-            .filter_map(|(health, team)| health.is_alive().then_some(team))
-            .fold(TeamAlive::Neither, |acc, elm| acc.found(elm))
+        self.teams_alive_by(|entity| {
+            let (health, team) = actor_q.get(entity).unwrap();
+            (*health, *team)
+        })
     }
 
-    pub fn queue(&self) -> &VecDeque<Entity> {
-        &self.queue
+    /// The same as [`TurnOrder::teams_alive`], but gets the health
+    /// and team of each actor from `get_actor` instead of a query.
+    pub fn teams_alive_by(&self, get_actor: impl Fn(Entity) -> (Health, Team)) -> TeamAlive {
+        self.actors()
+            .map(get_actor)
+            .filter_map(|(health, team)| health.is_alive().then_some(team))
+            .fold(TeamAlive::Neither, |acc, elm| acc.found(&elm))
     }
 
-    pub fn display_with_names(&self, name_q: &Query<&ActorName>) -> String {
-        self.queue
-            .iter()
-            .map(|entity| {
-                name_q
-                    .get(*entity)
-                    .map(|name| name.to_string())
-                    .unwrap_or("Unknown".to_string())
-            })
-            .collect::<Vec<_>>()
-            .join(", ")
+    /// Every actor in the fight, living or dead.
+    pub fn actors(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.timeline.iter().map(|entry| entry.entity)
     }
 }
 
 ////////////////EVENTS///////////////////
 
-//An event for when an action is done
-#[derive(Event)]
-pub struct ActionEvent {
-    pub actor: Entity,
-    pub action: Action,
-    pub target: Entity,
+/// An actor taking part in a fight, as it is shown in [`CombatEvent`]s.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Combatant {
+    pub entity: Entity,
+    pub name: ActorName,
+    pub team: Team,
+}
+
+impl fmt::Display for Combatant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Something that happened in a fight.
+/// These are sent as they happen, for the combat log and run stats.
+#[derive(Event, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CombatEvent {
+    /// `damage` is what the target actually lost,
+    /// which can be less than the attack did.
+    Attack {
+        attacker: Combatant,
+        target: Combatant,
+        damage: u32,
+    },
+    Miss {
+        attacker: Combatant,
+        target: Combatant,
+    },
+    Block {
+        attacker: Combatant,
+        target: Combatant,
+    },
+    Heal {
+        healer: Combatant,
+        target: Combatant,
+        amount: u32,
+    },
+    /// Sent before the events of what the item did.
+    UseItem {
+        actor: Combatant,
+        item: Item,
+    },
+    /// Sent before the events of what the ability did.
+    Ability {
+        actor: Combatant,
+        ability: AbilityName,
+    },
+    Death {
+        actor: Combatant,
+    },
+    /// The start of an actor's turn.
+    TurnChange {
+        actor: Combatant,
+    },
+    /// A status effect was put on the target.
+    Afflicted {
+        source: Combatant,
+        target: Combatant,
+        kind: StatusKind,
+    },
+    /// A status effect hurt the actor at the start of their turn.
+    StatusDamage {
+        actor: Combatant,
+        kind: StatusKind,
+        damage: u32,
+    },
+    /// A status effect healed the actor at the start of their turn.
+    StatusHeal {
+        actor: Combatant,
+        kind: StatusKind,
+        amount: u32,
+    },
+    /// The actor's turn was skipped because they are stunned.
+    Stunned {
+        actor: Combatant,
+    },
+    Wait {
+        actor: Combatant,
+    },
+    /// The actor tried to flee, getting the party away if `escaped`.
+    Flee {
+        actor: Combatant,
+        escaped: bool,
+    },
+}
+
+impl fmt::Display for CombatEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CombatEvent::Attack {
+                attacker,
+                target,
+                damage,
+            } => write!(f, "{attacker} hit {target} for {damage} damage"),
+            CombatEvent::Miss { attacker, target } => write!(f, "{attacker} missed {target}"),
+            CombatEvent::Block { attacker, target } => {
+                write!(f, "{target} blocked {attacker}")
+            }
+            CombatEvent::Heal {
+                healer,
+                target,
+                amount,
+            } => write!(f, "{healer} healed {target} for {amount} health"),
+            CombatEvent::UseItem { actor, item } => write!(f, "{actor} used a {item}"),
+            CombatEvent::Ability { actor, ability } => write!(f, "{actor} used {ability}"),
+            CombatEvent::Death { actor } => write!(f, "{actor} died"),
+            CombatEvent::TurnChange { actor } => write!(f, "{actor}'s turn"),
+            CombatEvent::Afflicted { target, kind, .. } => {
+                write!(f, "{target} is {}", kind.describe())
+            }
+            CombatEvent::StatusDamage {
+                actor,
+                kind,
+                damage,
+            } => write!(f, "{actor} took {damage} damage from {kind}"),
+            CombatEvent::StatusHeal {
+                actor,
+                kind,
+                amount,
+            } => write!(f, "{actor} regained {amount} health from {kind}"),
+            CombatEvent::Stunned { actor } => write!(f, "{actor} is stunned and can't act"),
+            CombatEvent::Wait { actor } => write!(f, "{actor} waits"),
+            CombatEvent::Flee {
+                actor,
+                escaped: true,
+            } => write!(f, "{actor} fled with the party"),
+            CombatEvent::Flee {
+                actor,
+                escaped: false,
+            } => write!(f, "{actor} tried to flee but couldn't get away"),
+        }
+    }
 }
 
 //sets up the turn queue
 fn setup_turn_order(
     mut commands: Commands,
-    actor_q: Query<Entity, With<Actor>>,
-    speed_q: Query<&AttackSpeed>,
+    actor_q: Query<(Entity, &AttackSpeed, &StatusEffects), With<Actor>>,
 ) {
-    commands.insert_resource(TurnOrder::new(actor_q, speed_q));
+    commands.insert_resource(TurnOrder::new(actor_q));
 }
 
 //stores the actors original positions
@@ -270,14 +569,27 @@ fn prep_turn_order(
     mut commands: Commands,
     mut queue: ResMut<TurnOrder>,
     mut next_state: ResMut<NextState<CombatState>>,
+    mut events: EventWriter<CombatEvent>,
     actor_q: Query<(&Health, &Team)>,
     name_q: Query<&ActorName>,
 ) {
-    println!("Turn order: {}", queue.display_with_names(&name_q));
+    let active = queue.active();
+    let active_actor = match (name_q.get(active), actor_q.get(active)) {
+        (Ok(name), Ok((_, team))) => Some(Combatant {
+            entity: active,
+            name: *name,
+            team: *team,
+        }),
+        _ => None,
+    };
+
     match queue.teams_alive(actor_q) {
         TeamAlive::Both => {
             //commands.entity(queue.active()).remove::<ActingActor>();
-            commands.entity(queue.active()).insert(ActingActor);
+            commands.entity(active).insert(ActingActor);
+            if let Some(actor) = active_actor {
+                events.write(CombatEvent::TurnChange { actor });
+            }
             next_state.set(CombatState::MoveToCenter);
         }
         // End the turn in this case (likely another function)
@@ -285,7 +597,93 @@ fn prep_turn_order(
             commands.entity(queue.active()).remove::<ActingActor>();
         }
     }
-    println!("Turn order: {}", queue.display_with_names(&name_q));
+}
+
+/// Ticks the [`StatusEffects`] of the actor whose turn is starting.
+/// Their turn is skipped if they are stunned or their effects kill them.
+/// A turn resumed after waiting has already ticked them.
+fn tick_status_effects(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<CombatState>>,
+    mut events: EventWriter<CombatEvent>,
+    queue: Res<TurnOrder>,
+    mut actor_q: Query<(&mut Health, &mut StatusEffects, &ActorName, &Team)>,
+) {
+    if queue.resumed() {
+        return;
+    }
+    let active = queue.active();
+    let Ok((mut health, mut effects, name, team)) = actor_q.get_mut(active) else {
+        return;
+    };
+    if effects.is_empty() {
+        return;
+    }
+    let actor = Combatant {
+        entity: active,
+        name: *name,
+        team: *team,
+    };
+
+    let mut skip = false;
+    for kind in effects.tick() {
+        if !health.is_alive() {
+            break;
+        }
+
+        if kind == StatusKind::Stun {
+            events.write(CombatEvent::Stunned { actor });
+            skip = true;
+        }
+        if kind.damage() > 0 {
+            let health_before = health.current().map_or(0, |h| h.get());
+            health.damage(kind.damage());
+            let health_after = health.current().map_or(0, |h| h.get());
+            events.write(CombatEvent::StatusDamage {
+                actor,
+                kind,
+                damage: health_before - health_after,
+            });
+            if !health.is_alive() {
+                events.write(CombatEvent::Death { actor });
+                skip = true;
+            }
+        }
+        if kind.healing() > 0 {
+            let health_before = health.current().map_or(0, |h| h.get());
+            health.heal(kind.healing());
+            let health_after = health.current().map_or(0, |h| h.get());
+            events.write(CombatEvent::StatusHeal {
+                actor,
+                kind,
+                amount: health_after - health_before,
+            });
+        }
+    }
+
+    if skip {
+        commands.insert_resource(ActingActorAction(Action::SkipTurn));
+        next_state.set(CombatState::EndOfTurn);
+    }
+    commands.run_system_cached(update_player_hp_bar_pit);
+}
+
+/// Counts down the [`Cooldowns`] of the actor whose turn is starting,
+/// unless it resumes a turn they waited on.
+fn tick_cooldowns(queue: Res<TurnOrder>, mut cooldown_q: Query<&mut Cooldowns>) {
+    if queue.resumed() {
+        return;
+    }
+    if let Ok(mut cooldowns) = cooldown_q.get_mut(queue.active()) {
+        cooldowns.tick();
+    }
+}
+
+/// Every fight starts with all abilities ready.
+fn clear_cooldowns(mut cooldown_q: Query<&mut Cooldowns>) {
+    for mut cooldowns in &mut cooldown_q {
+        cooldowns.clear();
+    }
 }
 
 //////////FROM HERE ARE MOVEMENT SYSTEMS//////////////////
@@ -388,39 +786,66 @@ fn move_to_target(
 }
 
 ////////////////Choose action/////////////////////
+
+/// The acting enemy, with everything they choose their action by.
+type ChoosingActor = (
+    Entity,
+    &'static Team,
+    &'static ActorName,
+    &'static Health,
+    Option<&'static EnemyBrain>,
+    Option<&'static Abilities>,
+    Option<&'static Cooldowns>,
+    Has<Recovering>,
+);
+
+/// The acting enemy chooses their action with their [`EnemyBrain`]
+/// and first ready ability,
+/// or skips their turn if they are [`Recovering`].
 pub fn choose_action(
     mut commands: Commands,
     mut next_state: ResMut<NextState<CombatState>>,
     mut rng: ResMut<EventRng>,
-    queue: ResMut<TurnOrder>,
-    active_actor: Single<(Entity, &Team), With<ActingActor>>,
-    actor_q: Query<(&Health, &Team)>,
+    queue: Res<TurnOrder>,
+    active_actor: Single<ChoosingActor, With<ActingActor>>,
+    actor_q: Query<(&Health, &Team, &ActorName)>,
 ) {
-    //remove any current action
-    let (_, team) = *active_actor;
-    let targets: Vec<Entity> = queue
-        .queue()
-        .iter()
-        .filter_map(|&entity| {
-            if let Ok((health, target_team)) = actor_q.get(entity) {
-                if health.is_alive() && *target_team != *team {
-                    Some(entity)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        })
-        .collect();
+    let (entity, team, name, health, brain, abilities, cooldowns, recovering) = *active_actor;
 
-    let chosen_target = targets[rng.random_range(0..targets.len())];
-    let combat_action = Action::Attack {
-        target: chosen_target,
-    };
-    debug!("CHOSEN TARGET {:?}", chosen_target);
+    let combat_action = if recovering {
+        commands.entity(entity).remove::<Recovering>();
+        Action::SkipTurn
+    } else {
+        let targets = queue
+            .actors()
+            .filter_map(|target| {
+                let (health, target_team, name) = actor_q.get(target).ok()?;
+                (health.is_alive() && target_team != team).then_some(BrainTarget {
+                    entity: target,
+                    name: *name,
+                    health: *health,
+                })
+            })
+            .collect::<Vec<_>>();
+        let actor = BrainTarget {
+            entity,
+            name: *name,
+            health: *health,
+        };
 
-    // Get the Attack and do .conduct on that
+        let ability = abilities.and_then(|abilities| {
+            abilities
+                .iter()
+                .copied()
+                .find(|ability| cooldowns.is_none_or(|cooldowns| cooldowns.ready(*ability)))
+        });
+
+        brain
+            .copied()
+            .unwrap_or_default()
+            .choose_action(actor, &targets, ability, &mut *rng)
+    };
+    debug!("{name} CHOSE {:?}", combat_action);
 
     commands.insert_resource(ActingActorAction(combat_action));
     next_state.set(CombatState::PerformAction);
@@ -428,107 +853,200 @@ pub fn choose_action(
 
 ///////////////Perform Action///////////////////
 
+type ActionTarget = (
+    Entity,
+    &'static mut Health,
+    &'static BlockChance,
+    &'static ActorName,
+    &'static Team,
+    &'static mut StatusEffects,
+);
+
 fn perform_action(
     mut commands: Commands,
     mut next_state: ResMut<NextState<CombatState>>,
     mut rng: ResMut<EventRng>,
-    active_actor: Single<(Entity, &Attack), With<ActingActor>>,
+    mut events: EventWriter<CombatEvent>,
+    active_actor: Single<(Entity, &Attack, &ActorName, &Team, &mut Cooldowns), With<ActingActor>>,
     actor_action: Res<ActingActorAction>,
-    mut actor_q: Query<(&mut Health, &BlockChance), With<Actor>>,
-    actor_name: Single<&ActorName, With<ActingActor>>,
+    mut actor_q: Query<ActionTarget, With<Actor>>,
 ) {
-    let (_, a_attack) = *active_actor;
+    let (entity, a_attack, a_name, a_team, mut cooldowns) = active_actor.into_inner();
+    let attacker = Combatant {
+        entity,
+        name: *a_name,
+        team: *a_team,
+    };
+    let a_attack = match actor_q.get(entity) {
+        Ok((.., effects)) => effects.attack(a_attack),
+        Err(_) => a_attack.clone(),
+    };
+    let mut log = Vec::new();
     match **actor_action {
         Action::Attack { target } => {
-            let attack = a_attack.clone();
-
-            let attack_result = attack.conduct(&mut *rng);
-            debug!("ATTACK RESULT {:?}", attack_result);
-
-            match attack_result {
-                AttackDamage::Hit(damage) => {
-                    if let Ok((mut target_health, block_chance)) = actor_q.get_mut(target) {
-                        debug!("TARGETS BLOCK CHANCE: {}\n", block_chance.0);
-                        let blocked = rng.random_bool(block_chance.0.into());
-                        debug!("Block chance: {:?}, Blocked: {}\n", block_chance.0, blocked);
-                        if !blocked {
-                            target_health.damage(damage.get());
-                            let current_health =
-                                target_health.current().map(|h| h.get()).unwrap_or(0);
-                            debug!(
-                                "DAMAGE DEALT: {}, TARGET HEALTH: {}\n",
-                                damage.get(),
-                                current_health
-                            );
-
-                            if !target_health.is_alive() {
-                                debug!("{:?} IS DEAD!!!!!!!!!!!!!!\n", target);
-                            }
-                        }
+            if let Ok((_, mut target_health, block_chance, t_name, t_team, t_effects)) =
+                actor_q.get_mut(target)
+            {
+                let target = Combatant {
+                    entity: target,
+                    name: *t_name,
+                    team: *t_team,
+                };
+                match a_attack.conduct_against(&mut *rng, t_effects.block_chance(*block_chance)) {
+                    AttackOutcome::Hit(damage) => {
+                        deal_damage(&mut log, attacker, target, &mut target_health, damage.get());
+                    }
+                    AttackOutcome::Blocked => {
+                        log.push(CombatEvent::Block { attacker, target });
+                    }
+                    AttackOutcome::Miss => {
+                        log.push(CombatEvent::Miss { attacker, target });
                     }
-                }
-                AttackDamage::Miss => {
-                    debug!("MISSED!!!!!!!!!!!!!!\n");
                 }
             }
         }
-        Action::SpecialAction { target } => match **actor_name {
-            ActorName::Warrior => {
-                if let Ok((mut target_health, _)) = actor_q.get_mut(target) {
-                    let attack_result = a_attack.conduct(&mut *rng);
-                    match attack_result {
-                        AttackDamage::Hit(damage) => {
-                            let extra_damage = (damage.get() as f32 * DAMAGE_MULTIPLIER) as u32;
-                            target_health.damage(extra_damage);
-                        }
-                        AttackDamage::Miss => {}
-                    }
-                }
+        Action::Ability {
+            ability: name,
+            target,
+        } => {
+            let ability = name.ability();
+            let targets = match ability.target {
+                TargetRule::AllEnemies | TargetRule::AllAllies => actor_q
+                    .iter()
+                    .filter(|(_, health, _, _, team, _)| {
+                        ability.target.allows(attacker.team, **team, health)
+                    })
+                    .map(|(e, ..)| e)
+                    .collect(),
+                TargetRule::User => vec![entity],
+                TargetRule::Enemy | TargetRule::Ally => vec![target],
+            };
+
+            log.push(CombatEvent::Ability {
+                actor: attacker,
+                ability: name,
+            });
+            let mut landed = false;
+            for target in targets {
+                let Ok((_, mut health, block_chance, t_name, t_team, mut effects)) =
+                    actor_q.get_mut(target)
+                else {
+                    continue;
+                };
+                let block_chance = effects.block_chance(*block_chance);
+                landed |= ability.use_on(
+                    &mut log,
+                    attacker,
+                    &a_attack,
+                    AbilityTarget {
+                        combatant: Combatant {
+                            entity: target,
+                            name: *t_name,
+                            team: *t_team,
+                        },
+                        health: &mut health,
+                        block_chance,
+                        effects: &mut effects,
+                    },
+                    &mut *rng,
+                );
             }
-            ActorName::Priestess => {
-                if let Ok((mut target_health, _)) = actor_q.get_mut(target) {
-                    let health_before = target_health.current().map(|h| h.get()).unwrap_or(0);
-                    debug!("target {} health is {}", target, health_before);
-                    let heal_num = rng.random_range(15..30);
-                    target_health.heal_or_revive(heal_num);
-                    let health_after = target_health.current().map(|h| h.get()).unwrap_or(0);
-                    debug!(
-                        "{} has healed {} points, health is now {}",
-                        target, heal_num, health_after
-                    );
-                }
+
+            cooldowns.start(name);
+            ability.apply_to_user(&mut commands.entity(entity), landed);
+        }
+
+        Action::UseItem { item, target } => {
+            commands.run_system_cached_with(crate::items::use_item, (item, target));
+        }
+        Action::Defend => {
+            if let Ok((.., mut effects)) = actor_q.get_mut(entity) {
+                afflict(
+                    &mut log,
+                    attacker,
+                    attacker,
+                    &mut effects,
+                    StatusKind::Guard,
+                    1,
+                );
             }
-            ActorName::Theif => {
-                let theif_attack = a_attack.clone();
-
-                let attack_result = theif_attack.conduct(&mut *rng);
-
-                match attack_result {
-                    AttackDamage::Hit(damage) => {
-                        if let Ok((mut target_health, block_chance)) = actor_q.get_mut(target) {
-                            let blocked = rng.random_bool(block_chance.0.into());
-                            if !blocked {
-                                target_health.damage(damage.get());
-                            }
-                        }
-                    }
-                    AttackDamage::Miss => {
-                        debug!("MISSED!!!!!!!!!!!!!!\n");
-                    }
-                }
+        }
+        Action::Wait => {
+            log.push(CombatEvent::Wait { actor: attacker });
+            commands.entity(entity).insert(Waiting);
+        }
+        Action::Flee => {
+            let escaped = rng.random_bool(FLEE_CHANCE);
+            log.push(CombatEvent::Flee {
+                actor: attacker,
+                escaped,
+            });
+            if escaped {
+                commands.entity(entity).insert(Fled);
             }
-            _ => {}
-        },
-
-        Action::UseItem { target, item } => {}
+        }
         Action::SkipTurn => {}
     }
+    events.write_batch(log);
 
-    commands.run_system_cached(update_player_hp_bar);
+    commands.run_system_cached(update_player_hp_bar_pit);
 
     next_state.set(CombatState::MoveBack);
 }
 
+/// Puts a status effect on the target, adding the [`CombatEvent`] for it.
+pub(super) fn afflict(
+    events: &mut Vec<CombatEvent>,
+    source: Combatant,
+    target: Combatant,
+    effects: &mut StatusEffects,
+    kind: StatusKind,
+    turns: u32,
+) {
+    effects.apply(kind, turns);
+    events.push(CombatEvent::Afflicted {
+        source,
+        target,
+        kind,
+    });
+}
+
+/// Damages the target, adding the [`CombatEvent`]s for the
+/// damage actually taken and for their death if it kills them.
+pub(super) fn deal_damage(
+    events: &mut Vec<CombatEvent>,
+    attacker: Combatant,
+    target: Combatant,
+    health: &mut Health,
+    damage: u32,
+) {
+    let health_before = health.current().map_or(0, |h| h.get());
+    health.damage(damage);
+    let health_after = health.current().map_or(0, |h| h.get());
+
+    events.push(CombatEvent::Attack {
+        attacker,
+        target,
+        damage: health_before - health_after,
+    });
+    if health_before > 0 && !health.is_alive() {
+        events.push(CombatEvent::Death { actor: target });
+    }
+}
+
+/// Moves actors along the [`TurnOrder`] when their speed
+/// changed from status effects, before the next turn is picked.
+fn update_timeline_speeds(
+    mut queue: ResMut<TurnOrder>,
+    actor_q: Query<(Entity, &AttackSpeed, &StatusEffects)>,
+) {
+    for (entity, speed, effects) in &actor_q {
+        queue.set_speed(entity, effects.speed(*speed));
+    }
+}
+
+type EndingActor = (Entity, Has<ExtraTurn>, Has<Waiting>, Has<Fled>);
+
 pub fn end_turn(
     mut commands: Commands,
     mut queue: ResMut<TurnOrder>,
@@ -536,17 +1054,35 @@ pub fn end_turn(
     mut update_gamestate: ResMut<NextState<GameState>>,
     actor_q: Query<(&Health, &Team)>,
     health_q: Query<&Health>,
-    actor_name: Single<&ActorName, With<ActingActor>>,
-    actor_action: Res<ActingActorAction>,
+    active_actor: Single<EndingActor, With<ActingActor>>,
+    mut save_game: ResMut<SaveGame>,
+    mut stats: ResMut<RunStats>,
 ) {
-    if matches!(**actor_name, ActorName::Theif)
-        && matches!(actor_action.0, Action::SpecialAction { .. })
-    {
+    save_game.turns += 1;
+    commands.remove_resource::<ActingActorAction>();
+
+    let (entity, extra_turn, waiting, fled) = *active_actor;
+    if fled {
+        commands
+            .entity(entity)
+            .remove::<(ActingActor, ExtraTurn, Fled)>();
+        commands.run_system_cached(flee_to_previous_room);
+        return;
+    }
+
+    // Waiting uses up an extra turn.
+    if extra_turn && !waiting && health_q.get(entity).is_ok_and(Health::is_alive) {
+        commands.entity(entity).remove::<ExtraTurn>();
     } else {
-        commands.entity(queue.active()).remove::<ActingActor>();
-        queue.skip_to_next(health_q);
+        commands
+            .entity(entity)
+            .remove::<(ActingActor, ExtraTurn, Waiting)>();
+        if waiting {
+            queue.wait(health_q);
+        } else {
+            queue.skip_to_next(health_q);
+        }
     }
-    commands.remove_resource::<ActingActorAction>();
 
     match queue.teams_alive(actor_q) {
         TeamAlive::Both => {
@@ -556,6 +1092,8 @@ pub fn end_turn(
         //TODO: If you have time, despawn enemies
         TeamAlive::Player => {
             debug!("Players won");
+            stats.fights_won += 1;
+            commands.run_system_cached(award_experience);
             update_gamestate.set(GameState::Navigation);
         }
         TeamAlive::Enemy => {
@@ -568,3 +1106,122 @@ pub fn end_turn(
         }
     }
 }
+
+#[cfg(test)]
+mod combat_tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::num::NonZero;
+
+    fn alive(_: Entity) -> Option<Health> {
+        Some(Health::new(NonZero::new(10).unwrap()))
+    }
+
+    #[test]
+    fn test_faster_acts_more_often() {
+        let [fast, slow] = [0, 1].map(Entity::from_raw);
+        let queue = TurnOrder::from_speeds([(slow, AttackSpeed(2)), (fast, AttackSpeed(6))]);
+
+        // they tie every 600, where the faster one goes first
+        assert_eq!(
+            queue.upcoming(8, alive),
+            [fast, fast, fast, slow, fast, fast, fast, slow]
+        );
+    }
+
+    #[test]
+    fn test_ties_keep_given_order() {
+        let [a, b, c] = [0, 1, 2].map(Entity::from_raw);
+        let mut queue = TurnOrder::from_speeds([
+            (a, AttackSpeed(3)),
+            (b, AttackSpeed(3)),
+            (c, AttackSpeed(3)),
+        ]);
+        assert_eq!(queue.upcoming(6, alive), [a, b, c, a, b, c]);
+
+        // dead actors are skipped
+        let dead = |entity| {
+            if entity == b {
+                Some(Health::with_current(None, NonZero::new(10).unwrap()))
+            } else {
+                alive(entity)
+            }
+        };
+        queue.skip_to_next_by(dead);
+        assert_eq!(queue.active(), c);
+    }
+
+    #[test]
+    fn test_speed_change_reorders() {
+        let [a, b] = [0, 1].map(Entity::from_raw);
+        let mut queue = TurnOrder::from_speeds([(a, AttackSpeed(4)), (b, AttackSpeed(3))]);
+        assert_eq!(queue.upcoming(4, alive), [a, b, a, b]);
+
+        // b has 100 of their 400 left to wait, which now takes 3 times as long
+        queue.set_speed(b, AttackSpeed(1));
+        assert_eq!(queue.upcoming(4, alive), [a, a, b, a]);
+    }
+
+    #[test]
+    fn test_wait_goes_later() {
+        let [a, b] = [0, 1].map(Entity::from_raw);
+        let mut queue = TurnOrder::from_speeds([(a, AttackSpeed(2)), (b, AttackSpeed(1))]);
+        assert_eq!(queue.active(), a);
+
+        // a would go again at 1200 before b, but waiting puts them just after b
+        queue.wait_by(alive);
+        assert_eq!(queue.active(), b);
+        assert!(!queue.resumed());
+        assert_eq!(queue.upcoming(5, alive), [b, a, a, b, a]);
+
+        // a's resumed turn is only that one turn
+        queue.skip_to_next_by(alive);
+        assert_eq!(queue.active(), a);
+        assert!(queue.resumed());
+        queue.skip_to_next_by(alive);
+        assert_eq!(queue.active(), a);
+        assert!(!queue.resumed());
+
+        // with nobody else alive, waiting just ends the turn
+        let only_a = |entity| (entity == a).then(|| alive(entity)).flatten();
+        queue.wait_by(only_a);
+        assert_eq!(queue.active(), a);
+        assert!(!queue.resumed());
+    }
+
+    #[test]
+    fn test_wait_keeps_cooldowns() {
+        let mut world = World::new();
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.start(AbilityName::BattleCry);
+        let a = world.spawn(cooldowns).id();
+        let b = world.spawn(Cooldowns::default()).id();
+        world.insert_resource(TurnOrder::from_speeds([
+            (a, AttackSpeed(2)),
+            (b, AttackSpeed(1)),
+        ]));
+        let remaining = |world: &World| {
+            world
+                .get::<Cooldowns>(a)
+                .unwrap()
+                .remaining(AbilityName::BattleCry)
+        };
+        let start = remaining(&world);
+
+        world.run_system_once(tick_cooldowns).unwrap();
+        assert_eq!(remaining(&world), start - 1);
+
+        // a waits for b, then their turn resumes without ticking again
+        world.resource_mut::<TurnOrder>().wait_by(alive);
+        world.run_system_once(tick_cooldowns).unwrap();
+        world.resource_mut::<TurnOrder>().skip_to_next_by(alive);
+        assert_eq!(world.resource::<TurnOrder>().active(), a);
+        world.run_system_once(tick_cooldowns).unwrap();
+        assert_eq!(remaining(&world), start - 1);
+
+        // their next fresh turn ticks as usual
+        world.resource_mut::<TurnOrder>().skip_to_next_by(alive);
+        world.run_system_once(tick_cooldowns).unwrap();
+        assert_eq!(remaining(&world), start - 2);
+    }
+}
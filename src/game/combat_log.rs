@@ -0,0 +1,261 @@
+//! The combat log, which shows every [`CombatEvent`] of the current fight.
+use super::*;
+use crate::menu::update_scroll_position_event;
+use crate::prelude::*;
+use bevy::prelude::*;
+
+pub struct CombatLogPlugin;
+
+impl Plugin for CombatLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Combat),
+            (init_resource::<CombatLog>, spawn_combat_log),
+        )
+        .add_systems(OnExit(GameState::Combat), remove_resource::<CombatLog>)
+        .add_systems(
+            Update,
+            (
+                record_combat_events,
+                update_combat_log.run_if(resource_changed::<CombatLog>),
+            )
+                .chain()
+                .run_if(in_state(GameState::Combat)),
+        );
+    }
+}
+
+/// Every [`CombatEvent`] of the current fight, oldest first.
+#[derive(Resource, Default, Debug, Deref)]
+pub struct CombatLog(Vec<CombatEvent>);
+
+impl CombatLog {
+    /// The log as text, with a line for each event.
+    pub fn to_text(&self) -> String {
+        self.0.iter().map(|event| format!("{event}\n")).collect()
+    }
+}
+
+/// The node the lines of the log are spawned in.
+#[derive(Component)]
+struct CombatLogLines;
+
+/// Shows where the log was saved.
+#[derive(Component)]
+struct CombatLogStatus;
+
+fn spawn_combat_log(mut commands: Commands, style: Res<Style>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                width: Val::Percent(30.0),
+                height: Val::Percent(40.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(style.background_color),
+            StateScoped(GameState::Combat),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("Combat Log"),
+                style.font(24.0),
+                TextColor(style.title_color),
+            ));
+
+            builder
+                .spawn((
+                    Node {
+                        flex_grow: 1.0,
+                        flex_direction: FlexDirection::Column,
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                    ScrollPosition::default(),
+                    CombatLogLines,
+                ))
+                .observe(update_scroll_position_event);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            builder
+                .spawn(Node {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(10.0),
+                    ..default()
+                })
+                .with_children(|builder| {
+                    builder
+                        .spawn((
+                            Node {
+                                padding: UiRect::px(10.0, 10.0, 5.0, 5.0),
+                                ..default()
+                            },
+                            Button,
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new("Save Log"),
+                                style.font(20.0),
+                                TextColor(style.text_color),
+                                Pickable::IGNORE,
+                            )],
+                        ))
+                        .observe(save_combat_log_click);
+
+                    builder.spawn((
+                        Text::default(),
+                        CombatLogStatus,
+                        style.font(16.0),
+                        TextColor(style.text_color),
+                    ));
+                });
+        });
+}
+
+fn record_combat_events(mut events: EventReader<CombatEvent>, mut log: ResMut<CombatLog>) {
+    log.0.extend(events.read().copied());
+}
+
+/// Shows the new lines of the log, scrolled to the newest.
+fn update_combat_log(
+    mut commands: Commands,
+    log: Res<CombatLog>,
+    style: Res<Style>,
+    lines: Single<(Entity, &mut ScrollPosition, Option<&Children>), With<CombatLogLines>>,
+) {
+    let (entity, mut scroll, children) = lines.into_inner();
+    let shown = children.map_or(0, |children| children.len());
+
+    commands.entity(entity).with_children(|builder| {
+        for event in log.iter().skip(shown) {
+            let color = match event {
+                CombatEvent::TurnChange { .. } => style.title_color,
+                _ => style.text_color,
+            };
+            builder.spawn((
+                Text::new(event.to_string()),
+                style.font(16.0),
+                TextColor(color),
+            ));
+        }
+    });
+
+    // This is clamped to the bottom when the layout is updated.
+    scroll.offset_y = f32::MAX;
+}
+
+/// The directory combat logs are saved to, to attach to bug reports.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn combat_log_directory() -> std::path::PathBuf {
+    crate::database::get_default_db_directory().join("combat_logs")
+}
+
+/// Saves the log to a file in the [`combat_log_directory`],
+/// returning the path of the file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_combat_log(log: &CombatLog, save: &SaveGame) -> std::io::Result<std::path::PathBuf> {
+    let directory = combat_log_directory();
+    std::fs::create_dir_all(&directory)?;
+
+    let now = chrono::Local::now();
+    let path = directory.join(format!(
+        "combat-{}-{}.txt",
+        save.game_id.0,
+        now.format("%Y%m%d-%H%M%S")
+    ));
+    let header = format!(
+        "Game {}, seed {}, turn {}, saved {}\n",
+        save.game_id.0,
+        crate::generate_map::seed_label(save.seed, &save.seed_text),
+        save.turns,
+        now.to_rfc3339(),
+    );
+    std::fs::write(&path, header + &log.to_text())?;
+
+    Ok(path)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_combat_log_click(
+    mut click: Trigger<Pointer<Click>>,
+    log: Res<CombatLog>,
+    save: Res<SaveGame>,
+    mut status: Single<&mut Text, With<CombatLogStatus>>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    status.0 = match save_combat_log(&log, &save) {
+        Ok(path) => format!("Saved to {}", path.display()),
+        Err(err) => {
+            warn!("Failed to save the combat log: {err}");
+            format!("Failed to save: {err}")
+        }
+    };
+}
+
+#[cfg(test)]
+mod combat_log_tests {
+    use super::*;
+
+    #[test]
+    fn test_combat_log_text() {
+        let warrior = Combatant {
+            entity: Entity::from_raw(0),
+            name: ActorName::Warrior,
+            team: Team::Player,
+        };
+        let ogre = Combatant {
+            entity: Entity::from_raw(1),
+            name: ActorName::Ogre,
+            team: Team::Enemy,
+        };
+        let log = CombatLog(vec![
+            CombatEvent::TurnChange { actor: warrior },
+            CombatEvent::Attack {
+                attacker: warrior,
+                target: ogre,
+                damage: 12,
+            },
+            CombatEvent::Death { actor: ogre },
+        ]);
+
+        assert_eq!(
+            log.to_text(),
+            "Warrior's turn\nWarrior hit Ogre for 12 damage\nOgre died\n"
+        );
+    }
+
+    #[test]
+    fn test_combat_events_recorded() {
+        let mut app = App::new();
+        app.add_event::<CombatEvent>()
+            .init_resource::<CombatLog>()
+            .add_systems(Update, record_combat_events);
+
+        let warrior = Combatant {
+            entity: Entity::from_raw(0),
+            name: ActorName::Warrior,
+            team: Team::Player,
+        };
+        app.world_mut()
+            .send_event(CombatEvent::TurnChange { actor: warrior });
+        app.update();
+        app.world_mut()
+            .send_event(CombatEvent::Death { actor: warrior });
+        app.update();
+
+        assert_eq!(
+            **app.world().resource::<CombatLog>(),
+            vec![
+                CombatEvent::TurnChange { actor: warrior },
+                CombatEvent::Death { actor: warrior },
+            ]
+        );
+    }
+}
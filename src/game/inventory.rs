@@ -0,0 +1,229 @@
+//! The inventory panel for using [`Items`].
+//!
+//! The same panel is used in combat and while navigating.
+//! In combat using an item takes the acting actor's turn,
+//! otherwise it is used straight away.
+use super::*;
+use crate::items::use_item;
+use crate::prelude::*;
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+/// The panel listing the party's items.
+#[derive(Component)]
+pub struct InventoryPanel;
+
+/// Uses the item, or asks for a target if it needs one.
+#[derive(Component)]
+struct ItemButton(Item);
+
+/// The combat turn an item is chosen in, if there is a fight.
+#[derive(SystemParam)]
+struct ItemTurn<'w> {
+    acting_actor: Option<Single<'w, Entity, With<ActingActor>>>,
+    combat_state: Option<Res<'w, State<CombatState>>>,
+    next_state: ResMut<'w, NextState<CombatState>>,
+}
+
+impl ItemTurn<'_> {
+    /// The state to change to when in combat, where using an item takes the turn.
+    fn next_state(&mut self) -> Option<&mut NextState<CombatState>> {
+        self.combat_state.is_some().then_some(&mut *self.next_state)
+    }
+}
+
+/// Uses the item on the party member.
+#[derive(Component)]
+struct ItemTargetButton(Item, Entity);
+
+/// Opens or closes the [`InventoryPanel`].
+pub fn toggle_inventory_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    style: Res<Style>,
+    items: Res<Items>,
+    panel: Query<Entity, With<InventoryPanel>>,
+    combat_state: Option<Res<State<CombatState>>>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    if panel.is_empty() {
+        let panel = spawn_panel(&mut commands, &style, combat_state.is_some());
+        commands
+            .entity(panel)
+            .with_children(|builder| item_list(builder, &style, &items));
+    } else {
+        panel
+            .iter()
+            .for_each(|entity| commands.entity(entity).despawn());
+    }
+}
+
+fn spawn_panel(commands: &mut Commands, style: &Style, in_combat: bool) -> Entity {
+    let mut panel = commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            right: Val::Px(10.0),
+            min_width: Val::Px(250.0),
+            padding: UiRect::all(Val::Px(10.0)),
+            row_gap: Val::Px(5.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Stretch,
+            ..default()
+        },
+        InventoryPanel,
+        BackgroundColor(style.background_color),
+        ZIndex(1),
+    ));
+
+    if in_combat {
+        panel.insert(StateScoped(CombatState::SpawnMenu));
+    } else {
+        panel.insert(StateScoped(GameState::Navigation));
+    }
+
+    panel.id()
+}
+
+fn panel_button<'a>(
+    builder: &'a mut ChildSpawnerCommands<'_>,
+    style: &Style,
+    text: String,
+) -> EntityCommands<'a> {
+    builder.spawn((
+        Node {
+            padding: UiRect::px(10.0, 10.0, 5.0, 5.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Button,
+        BackgroundColor(style.button_color),
+        children![(
+            Text::new(text),
+            style.font(24.0),
+            TextColor(style.text_color),
+            Pickable::IGNORE
+        )],
+    ))
+}
+
+fn item_list(builder: &mut ChildSpawnerCommands<'_>, style: &Style, items: &Items) {
+    builder.spawn((
+        Text::new("Items"),
+        style.font(33.0),
+        TextColor(style.title_color),
+    ));
+
+    let counts = items.counts();
+    if counts.is_empty() {
+        builder.spawn((
+            Text::new("Nothing yet"),
+            style.font(24.0),
+            TextColor(style.text_color),
+        ));
+    }
+
+    for (item, count) in counts {
+        panel_button(builder, style, format!("{item} x{count}"))
+            .insert(ItemButton(item))
+            .observe(item_click);
+    }
+}
+
+fn item_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    style: Res<Style>,
+    buttons: Query<&ItemButton>,
+    panel: Single<Entity, With<InventoryPanel>>,
+    party: Query<(Entity, &ActorName, &Health, &Team)>,
+    mut turn: ItemTurn,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    let Ok(ItemButton(item)) = buttons.get(click.target()) else {
+        return;
+    };
+    let item = *item;
+
+    if !item.needs_target() {
+        // The target doesn't matter, so use whoever is acting if anyone.
+        let target = turn
+            .acting_actor
+            .as_ref()
+            .map_or(Entity::PLACEHOLDER, |actor| **actor);
+        choose_item(&mut commands, item, target, turn.next_state());
+        return;
+    }
+
+    commands
+        .entity(*panel)
+        .despawn_related::<Children>()
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new(format!("Use {item} on")),
+                style.font(33.0),
+                TextColor(style.title_color),
+            ));
+
+            for (entity, name, health, _) in party
+                .iter()
+                .filter(|(_, _, health, team)| **team == Team::Player && health.is_alive())
+            {
+                let current = health.current().map_or(0, |h| h.get());
+                panel_button(
+                    builder,
+                    &style,
+                    format!("{name} {current}/{}", health.max()),
+                )
+                .insert(ItemTargetButton(item, entity))
+                .observe(item_target_click);
+            }
+        });
+}
+
+fn item_target_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    buttons: Query<&ItemTargetButton>,
+    mut turn: ItemTurn,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    let Ok(ItemTargetButton(item, target)) = buttons.get(click.target()) else {
+        return;
+    };
+
+    choose_item(&mut commands, *item, *target, turn.next_state());
+}
+
+/// In combat (when given the `next_state`) this is the acting actor's
+/// action for the turn, otherwise the item is used now and the panel closed.
+fn choose_item(
+    commands: &mut Commands,
+    item: Item,
+    target: Entity,
+    next_state: Option<&mut NextState<CombatState>>,
+) {
+    match next_state {
+        Some(next_state) => {
+            commands.insert_resource(ActingActorAction(Action::UseItem { item, target }));
+            next_state.set(CombatState::PerformAction);
+        }
+        None => {
+            commands.run_system_cached_with(use_item, (item, target));
+            commands.run_system_cached(despawn_filtered::<With<InventoryPanel>>);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Experience for winning fights, and the screen showing who levelled up.
+use super::*;
+use crate::prelude::*;
+use crate::update_player_hp_bar_pit;
+use bevy::prelude::*;
+
+pub struct LevelUpPlugin;
+
+impl Plugin for LevelUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Navigation),
+            spawn_level_up_screen.run_if(resource_exists::<LevelUps>),
+        )
+        .add_systems(
+            Update,
+            close_level_up_key
+                .run_if(in_state(GameState::Navigation).and(resource_exists::<LevelUps>)),
+        )
+        .add_systems(OnExit(GameState::Navigation), remove_resource::<LevelUps>);
+    }
+}
+
+/// A party member that levelled up after a fight.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LevelUp {
+    pub name: ActorName,
+    /// The level they reached.
+    pub level: u32,
+    pub gains: StatGrowth,
+}
+
+/// Everyone that levelled up in the last fight,
+/// which is shown when the party goes back to navigating.
+#[derive(Resource, Debug, Default, Deref)]
+pub struct LevelUps(Vec<LevelUp>);
+
+#[derive(Component)]
+struct LevelUpScreen;
+
+/// Gives each living party member the experience for every enemy in the fight,
+/// raising their stats for each level they gain.
+pub fn award_experience(
+    mut commands: Commands,
+    registry: Res<ActorRegistry>,
+    queue: Res<TurnOrder>,
+    mut actor_q: Query<(
+        &ActorName,
+        &Team,
+        &mut Health,
+        &mut Attack,
+        &mut AttackSpeed,
+        &mut Level,
+    )>,
+) {
+    let experience = queue
+        .actors()
+        .filter_map(|entity| actor_q.get(entity).ok())
+        .filter(|(_, team, ..)| **team == Team::Enemy)
+        .map(|(name, ..)| registry.get(*name).experience)
+        .sum::<u32>();
+
+    let mut level_ups = Vec::new();
+    for entity in queue.actors() {
+        let Ok((name, team, mut health, mut attack, mut speed, mut level)) =
+            actor_q.get_mut(entity)
+        else {
+            continue;
+        };
+        if *team != Team::Player || !health.is_alive() {
+            continue;
+        }
+
+        let levels = level.gain(experience);
+        if levels > 0 {
+            let gains = registry.get(*name).growth.times(levels);
+            gains.apply(&mut health, &mut attack, &mut speed);
+            level_ups.push(LevelUp {
+                name: *name,
+                level: level.level,
+                gains,
+            });
+        }
+    }
+
+    if !level_ups.is_empty() {
+        commands.insert_resource(LevelUps(level_ups));
+        commands.run_system_cached(update_player_hp_bar_pit);
+    }
+}
+
+fn spawn_level_up_screen(mut commands: Commands, style: Res<Style>, level_ups: Res<LevelUps>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            LevelUpScreen,
+            Pickable::IGNORE,
+            StateScoped(GameState::Navigation),
+        ))
+        .with_children(|builder| {
+            builder
+                .spawn((
+                    Node {
+                        padding: UiRect::all(Val::Px(20.0)),
+                        row_gap: Val::Px(10.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.background_color),
+                ))
+                .with_children(|builder| {
+                    builder.spawn((
+                        Text::new("Level Up!"),
+                        style.font(33.0),
+                        TextColor(style.title_color),
+                    ));
+
+                    for level_up in level_ups.iter() {
+                        builder.spawn((
+                            Text::new(format!(
+                                "{} reached level {}: {}",
+                                level_up.name,
+                                level_up.level,
+                                level_up.gains.describe()
+                            )),
+                            style.font(20.0),
+                            TextColor(style.text_color),
+                        ));
+                    }
+
+                    builder
+                        .spawn((
+                            Node {
+                                padding: UiRect::px(20.0, 20.0, 5.0, 5.0),
+                                ..default()
+                            },
+                            Button,
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new("Continue"),
+                                style.font(24.0),
+                                TextColor(style.text_color),
+                                Pickable::IGNORE,
+                            )],
+                        ))
+                        .observe(close_level_up_click);
+                });
+        });
+}
+
+fn close_level_up(commands: &mut Commands, screen: Entity) {
+    commands.entity(screen).despawn();
+    commands.remove_resource::<LevelUps>();
+}
+
+fn close_level_up_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    screen: Single<Entity, With<LevelUpScreen>>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    close_level_up(&mut commands, *screen);
+}
+
+/// Mouse clicks are left to [`close_level_up_click`],
+/// so clicking a door doesn't also close the screen.
+fn close_level_up_key(
+    mut commands: Commands,
+    key: Res<ControlState>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    screen: Single<Entity, With<LevelUpScreen>>,
+) {
+    if key.just_pressed(Control::Select) && !mouse.just_pressed(MouseButton::Left) {
+        close_level_up(&mut commands, *screen);
+    }
+}
+
+#[cfg(test)]
+mod level_up_tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::num::NonZero;
+
+    #[test]
+    fn test_award_experience() {
+        let registry = ActorRegistry::default();
+        let mut world = World::new();
+
+        let mut spawn = |name: ActorName, team: Team, alive: bool| {
+            let definition = registry.get(name);
+            let mut health = Health::new(definition.health);
+            if !alive {
+                health.kill();
+            }
+            world
+                .spawn((
+                    name,
+                    team,
+                    health,
+                    Attack::from_definition(definition),
+                    AttackSpeed::from_definition(definition),
+                    Level {
+                        level: 1,
+                        experience: 90,
+                    },
+                ))
+                .id()
+        };
+        let warrior = spawn(ActorName::Warrior, Team::Player, true);
+        let theif = spawn(ActorName::Theif, Team::Player, false);
+        let ogre = spawn(ActorName::Ogre, Team::Enemy, false);
+        let goblin = spawn(ActorName::Goblin, Team::Enemy, false);
+
+        let queue = TurnOrder::from_speeds(
+            [warrior, theif, ogre, goblin]
+                .into_iter()
+                .map(|entity| (entity, AttackSpeed(1))),
+        );
+        world.insert_resource(queue);
+        world.insert_resource(registry.clone());
+        world.run_system_once(award_experience).unwrap();
+
+        // 90 + 140 experience is one level, with 130 left over
+        let level = world.get::<Level>(warrior).unwrap();
+        assert_eq!(
+            *level,
+            Level {
+                level: 2,
+                experience: 130
+            }
+        );
+        let growth = registry.get(ActorName::Warrior).growth;
+        let health = world.get::<Health>(warrior).unwrap();
+        assert_eq!(
+            health.max(),
+            NonZero::new(registry.get(ActorName::Warrior).health.get() + growth.health).unwrap()
+        );
+        assert_eq!(
+            **world.resource::<LevelUps>(),
+            vec![LevelUp {
+                name: ActorName::Warrior,
+                level: 2,
+                gains: growth,
+            }]
+        );
+
+        // dead party members don't get any
+        assert_eq!(world.get::<Level>(theif).unwrap().level, 1);
+    }
+}
@@ -1,23 +1,40 @@
+mod ability;
 mod attack_options;
+mod brain;
 pub mod combat;
+mod combat_log;
+mod inventory;
+mod level_up;
 mod pouch;
+#[cfg(test)]
+pub mod simulation;
+mod targeting;
+mod turn_order_bar;
 
+pub use ability::*;
 pub use attack_options::*;
+pub use brain::*;
 pub use combat::*;
+pub use combat_log::*;
+pub use inventory::*;
+pub use level_up::*;
 pub use pouch::*;
+pub use targeting::*;
+pub use turn_order_bar::*;
 
+use crate::generate_map::seed_label;
 use crate::prelude::*;
 use crate::room::{
-    CurrentRoom, EntranceDirection, InRoom, ROOM_CENTER, ROOM_RADIUS, mark_room_cleared,
-    spawn_room, spawn_room_entities,
+    CurrentRoom, EntranceDirection, InRoom, PreviousRoom, ROOM_CENTER, ROOM_RADIUS,
+    mark_room_cleared, mark_room_visited, spawn_room, spawn_room_entities,
 };
-#[cfg(feature = "sqlite")]
-use crate::saving::save_game;
+use crate::saving::{manual_save, save_game};
+use crate::stats::RunStats;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::helpers::hex_grid::neighbors::HexNeighbors;
 use bevy_ecs_tilemap::prelude::*;
+use bevy_ui_text_input::clipboard::Clipboard;
 use rand::{Rng, SeedableRng};
-use std::collections::VecDeque;
 
 pub struct GamePlugin;
 
@@ -40,6 +57,7 @@ impl Plugin for GamePlugin {
                     spawn_room_entities,
                 )
                     .chain(),
+                mark_room_visited,
                 change_state(GameState::TriggerEvent),
             ),
         )
@@ -58,22 +76,26 @@ impl Plugin for GamePlugin {
         .add_systems(
             OnEnter(GameState::Navigation),
             (
-                (
-                    mark_room_cleared,
-                    #[cfg(feature = "sqlite")]
-                    save_game,
-                )
-                    .chain(),
+                (mark_room_cleared, save_game).chain(),
                 navigation_enter,
+                spawn_navigation_buttons,
             ),
         )
         .add_systems(
             OnExit(GameState::Navigation),
             despawn_filtered::<With<EntranceDirection>>,
         )
+        .add_systems(
+            Update,
+            crate::spawn_map::update_fog_of_war.run_if(in_state(AppState::Game)),
+        )
         .add_systems(OnEnter(GameState::GameOver), spawn_gameover_screen)
         .add_systems(OnEnter(GameState::Victory), spawn_victory_screen)
         .add_plugins(CombatPlugin)
+        .add_plugins(CombatLogPlugin)
+        .add_plugins(LevelUpPlugin)
+        .add_plugins(TargetingPlugin)
+        .add_plugins(TurnOrderBarPlugin)
         .add_plugins(AttackOptionsPlugin);
     }
 }
@@ -256,6 +278,8 @@ fn trigger_event(
     info: Single<&RoomInfo, With<CurrentRoom>>,
     mut actor_q: Query<&mut Health>,
     mut event_rng: ResMut<EventRng>,
+    mut items: ResMut<Items>,
+    mut stats: ResMut<RunStats>,
 ) {
     let RoomInfo {
         cleared, r_type, ..
@@ -275,17 +299,21 @@ fn trigger_event(
 
             let actor_damaged = event_rng.random_range(0..actor_count);
 
-            actor_q
+            let mut health = actor_q
                 .iter_mut()
                 .filter(|h| h.is_alive())
-                .skip(actor_damaged)
-                .next()
-                .unwrap()
-                .damage_no_one_shot(*damage);
+                .nth(actor_damaged)
+                .unwrap();
+            let health_before = health.current().map_or(0, |h| h.get());
+            health.damage_no_one_shot(*damage);
+            let health_after = health.current().map_or(0, |h| h.get());
+            stats.pit_damage += (health_before - health_after) as u64;
 
             commands.run_system_cached(update_player_hp_bar_pit);
         }
-        R::Item(item) => {}
+        R::Item(item) => {
+            items.push(*item);
+        }
         R::Pillar => {
             commands.run_system_cached(pouch::add_pillar);
         }
@@ -341,10 +369,129 @@ fn navigation_enter(
     });
 }
 
+/// The button for saving into the manual save slot.
+#[derive(Component)]
+struct ManualSaveButton;
+
+/// The button that copies the world seed, so the run can be shared.
+#[derive(Component)]
+struct CopySeedButton;
+
+/// The seed, save and inventory buttons in the corner of the screen.
+fn spawn_navigation_buttons(mut commands: Commands, style: Res<Style>, save: Res<SaveGame>) {
+    let button_node = Node {
+        padding: UiRect::px(20.0, 20.0, 5.0, 5.0),
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                column_gap: Val::Px(10.0),
+                ..default()
+            },
+            StateScoped(GameState::Navigation),
+        ))
+        .with_children(|builder| {
+            builder
+                .spawn((
+                    button_node.clone(),
+                    Button,
+                    CopySeedButton,
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new(format!("Seed: {}", seed_label(save.seed, &save.seed_text))),
+                        style.font(33.0),
+                        TextColor(style.text_color),
+                        Pickable::IGNORE,
+                    )],
+                ))
+                .observe(copy_seed_click);
+
+            builder
+                .spawn((
+                    button_node.clone(),
+                    Button,
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Items"),
+                        style.font(33.0),
+                        TextColor(style.text_color),
+                        Pickable::IGNORE,
+                    )],
+                ))
+                .observe(toggle_inventory_click);
+
+            builder
+                .spawn((
+                    button_node,
+                    Button,
+                    ManualSaveButton,
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Save"),
+                        style.font(33.0),
+                        TextColor(style.text_color),
+                        Pickable::IGNORE,
+                    )],
+                ))
+                .observe(manual_save_click);
+        });
+}
+
+fn manual_save_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    button: Single<&Children, With<ManualSaveButton>>,
+    mut text: Query<&mut Text>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    commands.queue(manual_save);
+
+    if let Some(mut text) = text.iter_many_mut(button.iter()).fetch_next() {
+        text.0 = "Saved!".into();
+    }
+}
+
+fn copy_seed_click(
+    mut click: Trigger<Pointer<Click>>,
+    save: Res<SaveGame>,
+    mut clipboard: ResMut<Clipboard>,
+    button: Single<&Children, With<CopySeedButton>>,
+    mut text: Query<&mut Text>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    let copied = match clipboard.set_text(seed_label(save.seed, &save.seed_text)) {
+        Ok(()) => "Seed Copied!",
+        Err(err) => {
+            warn!("Failed to copy the seed with {err:?}");
+            "Copy Failed"
+        }
+    };
+
+    if let Some(mut text) = text.iter_many_mut(button.iter()).fetch_next() {
+        text.0 = copied.into();
+    }
+}
+
 fn click_door(
     event: Trigger<Pointer<Click>>,
     mut commands: Commands,
     current_room: Single<(Entity, &TilePos), With<CurrentRoom>>,
+    previous_room: Option<Single<Entity, With<PreviousRoom>>>,
     map_map: Single<&TileStorage, (With<MapTilemap>, Without<RoomTilemap>)>,
     direction_q: Query<&EntranceDirection>,
     mut next_state: ResMut<NextState<GameState>>,
@@ -361,7 +508,34 @@ fn click_door(
     let new_room_entity = map_storage.get(&new_room_pos).unwrap();
     commands.entity(new_room_entity).insert(CurrentRoom);
 
-    commands.entity(current_room_entity).remove::<CurrentRoom>();
+    if let Some(previous_room) = previous_room {
+        commands.entity(*previous_room).remove::<PreviousRoom>();
+    }
+    commands
+        .entity(current_room_entity)
+        .remove::<CurrentRoom>()
+        .insert(PreviousRoom);
+
+    next_state.set(GameState::EnterRoom);
+}
+
+/// Takes the party back to the room they came from after fleeing a fight.
+/// The room they fled is left uncleared, so its enemies are there
+/// again when they come back.
+fn flee_to_previous_room(
+    mut commands: Commands,
+    current_room: Single<Entity, With<CurrentRoom>>,
+    previous_room: Single<Entity, With<PreviousRoom>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    commands
+        .entity(*current_room)
+        .remove::<CurrentRoom>()
+        .insert(PreviousRoom);
+    commands
+        .entity(*previous_room)
+        .remove::<PreviousRoom>()
+        .insert(CurrentRoom);
 
     next_state.set(GameState::EnterRoom);
 }
@@ -0,0 +1,391 @@
+//! A headless combat simulator for balance testing.
+//!
+//! This plays out fights using the same [`TurnOrder`] and attack rules
+//! as [`CombatPlugin`], but without any of the Bevy states, animations,
+//! or UI, so thousands of seeded fights can be run in a few seconds.
+//!
+//! To print a balance report, run `just balance`.
+use super::*;
+use crate::generate_map::MapSettings;
+use crate::prelude::*;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The most turns a single fight can take before it is called a draw.
+/// This keeps fights between actors that can't hurt each other from running forever.
+pub const MAX_TURNS: u32 = 1000;
+
+/// An actor in a simulated fight.
+#[derive(Clone)]
+pub struct SimActor {
+    pub name: ActorName,
+    pub team: Team,
+    pub health: Health,
+    pub attack: Attack,
+    pub speed: AttackSpeed,
+    pub block_chance: BlockChance,
+}
+
+impl SimActor {
+    /// Makes an actor with the same stats as [`ActorBundle::from_name`].
+    pub fn from_name(registry: &ActorRegistry, name: ActorName, team: Team) -> Self {
+        let definition = registry.get(name);
+        Self {
+            name,
+            team,
+            health: HealthBundle::from_definition(definition).health,
+            attack: Attack::from_definition(definition),
+            speed: AttackSpeed::from_definition(definition),
+            block_chance: BlockChance::from_definition(definition),
+        }
+    }
+}
+
+/// The result of a single simulated fight.
+#[derive(Clone, Debug)]
+pub struct FightResult {
+    /// The team that was left standing.
+    /// [`TeamAlive::Both`] if the fight hit [`MAX_TURNS`].
+    pub winner: TeamAlive,
+    /// The number of turns taken in total by all actors.
+    pub turns: u32,
+    /// The actors after the fight, in the order they were given.
+    pub actors: Box<[SimActorResult]>,
+}
+
+/// How a single actor fared in a simulated fight.
+#[derive(Clone, Copy, Debug)]
+pub struct SimActorResult {
+    pub name: ActorName,
+    pub team: Team,
+    pub damage_taken: u32,
+    pub alive: bool,
+}
+
+/// Plays out a fight between the `party` and `enemies` until
+/// one team is dead, using `rng` for all of the rolls.
+///
+/// Both teams attack a random living member of the other team,
+/// like monsters with the [`TargetStrategy::Random`] brain do in [`choose_action`].
+pub fn simulate_fight(
+    registry: &ActorRegistry,
+    party: &[ActorName],
+    enemies: &[ActorName],
+    rng: &mut RandomSource,
+) -> FightResult {
+    let mut actors = party
+        .iter()
+        .map(|name| SimActor::from_name(registry, *name, Team::Player))
+        .chain(
+            enemies
+                .iter()
+                .map(|name| SimActor::from_name(registry, *name, Team::Enemy)),
+        )
+        .collect::<Vec<_>>();
+    let mut damage_taken = vec![0u32; actors.len()];
+
+    // Stand in entities so the real turn order can be used.
+    let index = |entity: Entity| entity.index() as usize;
+    let mut queue = TurnOrder::from_speeds(
+        actors
+            .iter()
+            .enumerate()
+            .map(|(idx, actor)| (Entity::from_raw(idx as u32), actor.speed)),
+    );
+
+    let mut turns = 0;
+    let winner = loop {
+        match queue.teams_alive_by(|e| (actors[index(e)].health, actors[index(e)].team)) {
+            TeamAlive::Both if turns >= MAX_TURNS => break TeamAlive::Both,
+            TeamAlive::Both => {}
+            other => break other,
+        }
+
+        if !actors[index(queue.active())].health.is_alive() {
+            queue.skip_to_next_by(|e| Some(actors[index(e)].health));
+        }
+
+        let active = index(queue.active());
+        let team = actors[active].team;
+        let targets = queue
+            .actors()
+            .map(index)
+            .filter(|idx| actors[*idx].health.is_alive() && actors[*idx].team != team)
+            .collect::<Vec<_>>();
+        let target = targets[rng.random_range(0..targets.len())];
+
+        let outcome = actors[active]
+            .attack
+            .conduct_against(rng, actors[target].block_chance);
+        if let AttackOutcome::Hit(damage) = outcome {
+            let before = actors[target].health.current().map_or(0, |h| h.get());
+            actors[target].health.damage(damage.get());
+            let after = actors[target].health.current().map_or(0, |h| h.get());
+            damage_taken[target] += before - after;
+        }
+
+        turns += 1;
+
+        if queue.teams_alive_by(|e| (actors[index(e)].health, actors[index(e)].team))
+            == TeamAlive::Both
+        {
+            queue.skip_to_next_by(|e| Some(actors[index(e)].health));
+        }
+    };
+
+    FightResult {
+        winner,
+        turns,
+        actors: actors
+            .iter()
+            .zip(damage_taken)
+            .map(|(actor, damage_taken)| SimActorResult {
+                name: actor.name,
+                team: actor.team,
+                damage_taken,
+                alive: actor.health.is_alive(),
+            })
+            .collect(),
+    }
+}
+
+/// Simulates the fight in a room.
+/// Returns `None` if the room isn't a [`RoomType::Combat`] room.
+pub fn simulate_room(
+    registry: &ActorRegistry,
+    party: &[ActorName],
+    room: &RoomType,
+    rng: &mut RandomSource,
+) -> Option<FightResult> {
+    match room {
+        RoomType::Combat(enemies) => Some(simulate_fight(registry, party, enemies, rng)),
+        _ => None,
+    }
+}
+
+/// Simulates `fights` fights between the `party` and randomly generated
+/// combat rooms. The same `seed` always gives the same report.
+pub fn simulate_many(
+    registry: &ActorRegistry,
+    party: &[ActorName],
+    fights: u32,
+    seed: u64,
+) -> SimulationReport {
+    let mut rng = RandomSource::seed_from_u64(seed);
+    let mut report = SimulationReport::default();
+
+    for _ in 0..fights {
+        let room = RoomType::Combat(ActorName::get_enemies(
+            &mut rng,
+            MapSettings::default().enemy_density,
+        ));
+        let mut fight_rng = RandomSource::seed_from_u64(rng.random());
+        let result = simulate_room(registry, party, &room, &mut fight_rng).unwrap();
+        report.add(&result);
+    }
+
+    report
+}
+
+/// The statistics collected over many simulated fights.
+#[derive(Clone, Debug, Default)]
+pub struct SimulationReport {
+    pub fights: u32,
+    pub player_wins: u32,
+    pub enemy_wins: u32,
+    /// Fights where everyone died, or that hit [`MAX_TURNS`].
+    pub draws: u32,
+    pub total_turns: u64,
+    pub actors: HashMap<ActorName, ActorReport>,
+}
+
+/// The statistics for every actor with a given name.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ActorReport {
+    /// The number of times an actor with this name was in a fight.
+    pub appearances: u32,
+    pub deaths: u32,
+    pub damage_taken: u64,
+}
+
+impl ActorReport {
+    pub fn average_damage_taken(&self) -> f64 {
+        self.damage_taken as f64 / self.appearances.max(1) as f64
+    }
+
+    pub fn death_rate(&self) -> f64 {
+        self.deaths as f64 / self.appearances.max(1) as f64
+    }
+}
+
+impl SimulationReport {
+    pub fn add(&mut self, result: &FightResult) {
+        self.fights += 1;
+        self.total_turns += result.turns as u64;
+
+        match result.winner {
+            TeamAlive::Player => self.player_wins += 1,
+            TeamAlive::Enemy => self.enemy_wins += 1,
+            TeamAlive::Both | TeamAlive::Neither => self.draws += 1,
+        }
+
+        for actor in result.actors.iter() {
+            let entry = self.actors.entry(actor.name).or_default();
+            entry.appearances += 1;
+            entry.damage_taken += actor.damage_taken as u64;
+            if !actor.alive {
+                entry.deaths += 1;
+            }
+        }
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.player_wins as f64 / self.fights.max(1) as f64
+    }
+
+    pub fn average_turns(&self) -> f64 {
+        self.total_turns as f64 / self.fights.max(1) as f64
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "fights:        {}", self.fights)?;
+        writeln!(f, "player wins:   {:.1}%", self.win_rate() * 100.0)?;
+        writeln!(f, "enemy wins:    {}", self.enemy_wins)?;
+        writeln!(f, "draws:         {}", self.draws)?;
+        writeln!(f, "average turns: {:.2}", self.average_turns())?;
+        writeln!(
+            f,
+            "{:<12} {:>11} {:>10} {:>15}",
+            "actor", "appearances", "death rate", "avg damage taken"
+        )?;
+
+        let mut actors = self.actors.iter().collect::<Vec<_>>();
+        actors.sort_by_key(|(name, _)| name.to_string());
+
+        for (name, actor) in actors {
+            writeln!(
+                f,
+                "{:<12} {:>11} {:>9.1}% {:>15.2}",
+                name.to_string(),
+                actor.appearances,
+                actor.death_rate() * 100.0,
+                actor.average_damage_taken()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod simulation_tests {
+    use super::*;
+
+    const PARTY: [ActorName; 3] = [ActorName::Warrior, ActorName::Priestess, ActorName::Theif];
+
+    #[test]
+    fn test_fight_is_deterministic() {
+        let registry = ActorRegistry::default();
+        for seed in 0..100 {
+            let a = simulate_fight(
+                &registry,
+                &PARTY,
+                &[ActorName::Ogre, ActorName::Goblin],
+                &mut RandomSource::seed_from_u64(seed),
+            );
+            let b = simulate_fight(
+                &registry,
+                &PARTY,
+                &[ActorName::Ogre, ActorName::Goblin],
+                &mut RandomSource::seed_from_u64(seed),
+            );
+
+            assert_eq!(a.winner, b.winner);
+            assert_eq!(a.turns, b.turns);
+            for (a, b) in a.actors.iter().zip(b.actors.iter()) {
+                assert_eq!(a.damage_taken, b.damage_taken);
+                assert_eq!(a.alive, b.alive);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fight_ends_with_a_winner() {
+        let registry = ActorRegistry::default();
+        for seed in 0..100 {
+            let result = simulate_fight(
+                &registry,
+                &PARTY,
+                &[ActorName::Skeleton],
+                &mut RandomSource::seed_from_u64(seed),
+            );
+
+            let alive = |team| {
+                result
+                    .actors
+                    .iter()
+                    .any(|actor| actor.team == team && actor.alive)
+            };
+
+            match result.winner {
+                TeamAlive::Player => assert!(alive(Team::Player) && !alive(Team::Enemy)),
+                TeamAlive::Enemy => assert!(!alive(Team::Player) && alive(Team::Enemy)),
+                TeamAlive::Both | TeamAlive::Neither => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_harmless_fight_is_a_draw() {
+        let result = simulate_fight(
+            &ActorRegistry::default(),
+            &[ActorName::UnknownJim],
+            &[ActorName::UnknownJim],
+            &mut RandomSource::seed_from_u64(0),
+        );
+
+        assert_eq!(result.winner, TeamAlive::Both);
+        assert_eq!(result.turns, MAX_TURNS);
+    }
+
+    #[test]
+    fn test_non_combat_room() {
+        let registry = ActorRegistry::default();
+        let mut rng = RandomSource::seed_from_u64(0);
+        assert!(simulate_room(&registry, &PARTY, &RoomType::EmptyRoom, &mut rng).is_none());
+        assert!(simulate_room(&registry, &PARTY, &RoomType::Pit(10), &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_report() {
+        let registry = ActorRegistry::default();
+        let report = simulate_many(&registry, &PARTY, 1000, 0x5eed);
+        assert_eq!(report.fights, 1000);
+        assert_eq!(
+            report.player_wins + report.enemy_wins + report.draws,
+            report.fights
+        );
+        assert_eq!(report.actors[&ActorName::Warrior].appearances, 1000);
+
+        let again = simulate_many(&registry, &PARTY, 1000, 0x5eed);
+        assert_eq!(report.player_wins, again.player_wins);
+        assert_eq!(report.total_turns, again.total_turns);
+    }
+
+    /// Prints a balance report for the default party.
+    /// Run with `just balance`.
+    #[test]
+    #[ignore]
+    fn balance_report() {
+        let seed = std::env::var("BALANCE_SEED")
+            .ok()
+            .and_then(|s| u64::from_str_radix(&s, 16).ok())
+            .unwrap_or(0x5eed);
+
+        let registry = ActorRegistry::default();
+        println!("{}", simulate_many(&registry, &PARTY, 10_000, seed));
+    }
+}
@@ -0,0 +1,407 @@
+//! Choosing who the player's action is used on, by pointing at them
+//! or cycling through them with the movement controls.
+use super::*;
+use crate::prelude::*;
+use crate::room::RoomTile;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+/// The tint of the target that is selected.
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.8, 0.3);
+/// The tint of the actors that can't be targeted.
+const UNTARGETABLE_COLOR: Color = Color::srgb(0.45, 0.45, 0.45);
+
+pub struct TargetingPlugin;
+
+impl Plugin for TargetingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(CombatState::SelectTarget), spawn_target_prompt)
+            .add_systems(
+                OnExit(CombatState::SelectTarget),
+                (remove_resource::<TargetSelection>, clear_highlights),
+            )
+            .add_systems(
+                Update,
+                (
+                    (cycle_target, confirm_target, cancel_target),
+                    highlight_targets.run_if(resource_exists_and_changed::<TargetSelection>),
+                )
+                    .chain()
+                    .run_if(in_state(CombatState::SelectTarget)),
+            )
+            .add_observer(hover_target)
+            .add_observer(click_target);
+    }
+}
+
+/// Which action is waiting on a target.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum TargetKind {
+    Attack,
+    Ability(AbilityName),
+}
+
+/// The action waiting on a target, and who it can be used on.
+#[derive(Resource, Debug)]
+pub struct TargetSelection {
+    pub kind: TargetKind,
+    /// Ordered from left to right on the screen, so cycling follows the sprites.
+    targets: Vec<Entity>,
+    selected: usize,
+}
+
+impl TargetSelection {
+    /// Asserts there is at least one target.
+    pub fn new(kind: TargetKind, targets: Vec<Entity>, selected: Entity) -> Self {
+        assert!(!targets.is_empty());
+        let selected = targets.iter().position(|e| *e == selected).unwrap_or(0);
+        Self {
+            kind,
+            targets,
+            selected,
+        }
+    }
+
+    /// Every living actor on the other team can be attacked.
+    pub fn attack(
+        kind: TargetKind,
+        queue: &TurnOrder,
+        team: Team,
+        actor_q: &Query<(&Health, &Team, &Transform)>,
+    ) -> Self {
+        let targets = targets_by(queue, actor_q, |health, target_team| {
+            health.is_alive() && target_team != team
+        });
+        let first = targets[0];
+        Self::new(kind, targets, first)
+    }
+
+    /// Whoever the ability's [`TargetRule`] allows. Abilities used on allies
+    /// start on whoever has the least health, as they need it the most.
+    pub fn ability(
+        ability: AbilityName,
+        queue: &TurnOrder,
+        team: Team,
+        actor_q: &Query<(&Health, &Team, &Transform)>,
+    ) -> Self {
+        let rule = ability.ability().target;
+        let targets = targets_by(queue, actor_q, |health, target_team| {
+            rule.allows(team, target_team, health)
+        });
+        let selected = match rule {
+            TargetRule::Ally => *targets
+                .iter()
+                .min_by_key(|e| {
+                    actor_q
+                        .get(**e)
+                        .map_or(0, |(health, ..)| health.current().map_or(0, |h| h.get()))
+                })
+                .unwrap(),
+            _ => targets[0],
+        };
+        Self::new(TargetKind::Ability(ability), targets, selected)
+    }
+
+    pub fn targets(&self) -> &[Entity] {
+        &self.targets
+    }
+
+    pub fn selected(&self) -> Entity {
+        self.targets[self.selected]
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.targets.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = (self.selected + self.targets.len() - 1) % self.targets.len();
+    }
+
+    /// Selects the entity if it can be targeted, returning whether it could.
+    pub fn select(&mut self, entity: Entity) -> bool {
+        match self.targets.iter().position(|e| *e == entity) {
+            Some(idx) => {
+                self.selected = idx;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The action used on the selected target.
+    pub fn action(&self) -> Action {
+        let target = self.selected();
+        match self.kind {
+            TargetKind::Attack => Action::Attack { target },
+            TargetKind::Ability(ability) => Action::Ability { ability, target },
+        }
+    }
+}
+
+/// The actors in the fight that `filter` allows, from left to right.
+fn targets_by(
+    queue: &TurnOrder,
+    actor_q: &Query<(&Health, &Team, &Transform)>,
+    filter: impl Fn(&Health, Team) -> bool,
+) -> Vec<Entity> {
+    let mut targets = queue
+        .actors()
+        .filter_map(|entity| {
+            let (health, team, transform) = actor_q.get(entity).ok()?;
+            filter(health, *team).then_some((entity, transform.translation.x))
+        })
+        .collect::<Vec<_>>();
+    targets.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    targets.into_iter().map(|(entity, _)| entity).collect()
+}
+
+fn spawn_target_prompt(mut commands: Commands, style: Res<Style>, keybinds: Res<Controls>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(20.0),
+                ..default()
+            },
+            StateScoped(CombatState::SelectTarget),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("Choose a target"),
+                style.font(33.0),
+                TextColor(style.text_color),
+            ));
+            style.display_keybind(builder, &Keybind(Control::Select, keybinds.select));
+            style.display_keybind(builder, &Keybind(Control::Pause, keybinds.pause));
+        });
+}
+
+fn cycle_target(key: Res<ControlState>, mut selection: ResMut<TargetSelection>) {
+    if key.just_pressed(Control::MoveLeft) || key.just_pressed(Control::MoveUp) {
+        selection.previous();
+    }
+    if key.just_pressed(Control::MoveRight) || key.just_pressed(Control::MoveDown) {
+        selection.next();
+    }
+}
+
+/// Mouse clicks are left to [`click_target`],
+/// so clicking anything else doesn't choose the selected target.
+fn confirm_target(
+    mut commands: Commands,
+    key: Res<ControlState>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    selection: Res<TargetSelection>,
+    mut next_state: ResMut<NextState<CombatState>>,
+) {
+    if key.just_pressed(Control::Select) && !mouse.just_pressed(MouseButton::Left) {
+        commands.insert_resource(ActingActorAction(selection.action()));
+        next_state.set(CombatState::PerformAction);
+    }
+}
+
+/// Goes back to choosing an action.
+fn cancel_target(key: Res<ControlState>, mut next_state: ResMut<NextState<CombatState>>) {
+    if key.just_pressed(Control::Pause) {
+        next_state.set(CombatState::SpawnMenu);
+    }
+}
+
+fn highlight_targets(
+    selection: Res<TargetSelection>,
+    mut sprite_q: Query<(Entity, &mut Sprite), With<Actor>>,
+) {
+    for (entity, mut sprite) in sprite_q.iter_mut() {
+        sprite.color = if entity == selection.selected() {
+            SELECTED_COLOR
+        } else if selection.targets().contains(&entity) {
+            Color::WHITE
+        } else {
+            UNTARGETABLE_COLOR
+        };
+    }
+}
+
+fn clear_highlights(mut sprite_q: Query<&mut Sprite, With<Actor>>) {
+    for mut sprite in sprite_q.iter_mut() {
+        sprite.color = Color::WHITE;
+    }
+}
+
+/// The target the pointer is on, either their sprite or the tile they stand on.
+fn pointed_target(
+    entity: Entity,
+    selection: &TargetSelection,
+    tile_q: &Query<&TilePos, With<RoomTile>>,
+    position_q: &Query<&ActorOriginalPosition>,
+    tilemap_q: &Query<
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+        ),
+        With<RoomTilemap>,
+    >,
+) -> Option<Entity> {
+    if selection.targets().contains(&entity) {
+        return Some(entity);
+    }
+
+    let tile_pos = tile_q.get(entity).ok()?;
+    let (map_size, grid_size, tile_size, map_type, anchor) = tilemap_q.single().ok()?;
+    selection.targets().iter().copied().find(|target| {
+        position_q.get(*target).is_ok_and(|position| {
+            TilePos::from_world_pos(
+                &position.0,
+                map_size,
+                grid_size,
+                tile_size,
+                map_type,
+                anchor,
+            )
+            .is_some_and(|pos| pos == *tile_pos)
+        })
+    })
+}
+
+fn hover_target(
+    over: Trigger<Pointer<Over>>,
+    selection: Option<ResMut<TargetSelection>>,
+    tile_q: Query<&TilePos, With<RoomTile>>,
+    position_q: Query<&ActorOriginalPosition>,
+    tilemap_q: Query<
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+        ),
+        With<RoomTilemap>,
+    >,
+) {
+    let Some(mut selection) = selection else {
+        return;
+    };
+
+    if let Some(target) =
+        pointed_target(over.target(), &selection, &tile_q, &position_q, &tilemap_q)
+        && target != selection.selected()
+    {
+        selection.select(target);
+    }
+}
+
+fn click_target(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    selection: Option<ResMut<TargetSelection>>,
+    tile_q: Query<&TilePos, With<RoomTile>>,
+    position_q: Query<&ActorOriginalPosition>,
+    tilemap_q: Query<
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+        ),
+        With<RoomTilemap>,
+    >,
+    mut next_state: ResMut<NextState<CombatState>>,
+) {
+    let Some(mut selection) = selection else {
+        return;
+    };
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    if let Some(target) =
+        pointed_target(click.target(), &selection, &tile_q, &position_q, &tilemap_q)
+    {
+        click.propagate(false);
+        selection.select(target);
+        commands.insert_resource(ActingActorAction(selection.action()));
+        next_state.set(CombatState::PerformAction);
+    }
+}
+
+#[cfg(test)]
+mod targeting_tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::num::NonZero;
+
+    #[test]
+    fn test_cycle_targets() {
+        let targets = (0..3).map(Entity::from_raw).collect::<Vec<_>>();
+        let mut selection = TargetSelection::new(TargetKind::Attack, targets.clone(), targets[1]);
+        assert_eq!(selection.selected(), targets[1]);
+
+        selection.next();
+        selection.next();
+        assert_eq!(selection.selected(), targets[0]);
+        selection.previous();
+        assert_eq!(selection.selected(), targets[2]);
+
+        assert!(!selection.select(Entity::from_raw(7)));
+        assert!(selection.select(targets[1]));
+        assert_eq!(selection.action(), Action::Attack { target: targets[1] });
+    }
+
+    #[test]
+    fn test_target_candidates() {
+        let mut world = World::new();
+        let mut actor = |team: Team, hp: u32, x: f32| {
+            let health = Health::with_current(NonZero::new(hp), NonZero::new(30).unwrap());
+            world
+                .spawn((health, team, Transform::from_xyz(x, 0.0, 0.0)))
+                .id()
+        };
+        let priestess = actor(Team::Player, 20, 0.0);
+        let warrior = actor(Team::Player, 0, -5.0);
+        let right_ogre = actor(Team::Enemy, 30, 10.0);
+        let left_ogre = actor(Team::Enemy, 30, 5.0);
+        let dead_ogre = actor(Team::Enemy, 0, 0.0);
+
+        let queue = TurnOrder::from_speeds(
+            [priestess, warrior, right_ogre, left_ogre, dead_ogre]
+                .map(|entity| (entity, AttackSpeed(1))),
+        );
+
+        let (attack, heal) = world
+            .run_system_once(move |actor_q: Query<(&Health, &Team, &Transform)>| {
+                (
+                    TargetSelection::attack(TargetKind::Attack, &queue, Team::Player, &actor_q),
+                    TargetSelection::ability(
+                        AbilityName::HealTarget,
+                        &queue,
+                        Team::Player,
+                        &actor_q,
+                    ),
+                )
+            })
+            .unwrap();
+
+        assert_eq!(attack.targets(), [left_ogre, right_ogre]);
+        assert_eq!(attack.selected(), left_ogre);
+        assert_eq!(heal.targets(), [warrior, priestess]);
+        assert_eq!(heal.selected(), warrior, "starts on the least health");
+        assert_eq!(
+            heal.action(),
+            Action::Ability {
+                ability: AbilityName::HealTarget,
+                target: warrior
+            }
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! The bar showing who takes the next turns in combat, from the [`TurnOrder`].
+use super::*;
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// How many turns ahead the bar shows, including the current one.
+const TURNS_SHOWN: usize = 8;
+/// The color of enemy names in the bar.
+const ENEMY_COLOR: Color = Color::srgb(0.9, 0.4, 0.4);
+
+pub struct TurnOrderBarPlugin;
+
+impl Plugin for TurnOrderBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Combat), spawn_turn_order_bar)
+            .add_systems(
+                Update,
+                update_turn_order_bar.run_if(
+                    in_state(GameState::Combat).and(resource_exists_and_changed::<TurnOrder>),
+                ),
+            );
+    }
+}
+
+/// The node the upcoming turns are spawned in.
+#[derive(Component)]
+struct TurnOrderSlots;
+
+fn spawn_turn_order_bar(mut commands: Commands, style: Res<Style>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(40.0),
+                right: Val::Px(10.0),
+                width: Val::Percent(30.0),
+                margin: UiRect::top(Val::Px(20.0)),
+                padding: UiRect::all(Val::Px(10.0)),
+                row_gap: Val::Px(6.0),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(style.background_color),
+            StateScoped(GameState::Combat),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("Turn Order"),
+                style.font(24.0),
+                TextColor(style.title_color),
+            ));
+            builder.spawn((
+                Node {
+                    flex_wrap: FlexWrap::Wrap,
+                    column_gap: Val::Px(6.0),
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                TurnOrderSlots,
+            ));
+        });
+}
+
+/// Shows the upcoming turns, with the current one highlighted.
+fn update_turn_order_bar(
+    mut commands: Commands,
+    style: Res<Style>,
+    queue: Res<TurnOrder>,
+    actor_q: Query<(&ActorName, &Team, &Health)>,
+    slots: Single<Entity, With<TurnOrderSlots>>,
+) {
+    let upcoming = queue.upcoming(TURNS_SHOWN, |entity| {
+        actor_q.get(entity).ok().map(|(.., health)| *health)
+    });
+
+    commands
+        .entity(*slots)
+        .despawn_related::<Children>()
+        .with_children(|builder| {
+            for (turn, entity) in upcoming.into_iter().enumerate() {
+                let Ok((name, team, _)) = actor_q.get(entity) else {
+                    continue;
+                };
+                let color = match (turn, team) {
+                    (0, _) => style.title_color,
+                    (_, Team::Player) => style.text_color,
+                    (_, Team::Enemy) => ENEMY_COLOR,
+                };
+
+                builder.spawn((
+                    Node {
+                        padding: UiRect::px(6.0, 6.0, 2.0, 2.0),
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new(name.to_string()),
+                        style.font(16.0),
+                        TextColor(color)
+                    )],
+                ));
+            }
+        });
+}
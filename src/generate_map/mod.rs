@@ -0,0 +1,534 @@
+//! Generating the map of rooms for a new game.
+//!
+//! Each algorithm is a [`MapGenerator`], picked by the
+//! [`MapGeneratorKind`] in the [`GenerationSettings`].
+use crate::embed_asset;
+use crate::menu::new_game::{GenerationProgress, NewGameState, abort_generation};
+use crate::prelude::*;
+use crate::room::RoomWeights;
+use bevy::prelude::*;
+use bevy::tasks::futures::check_ready;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy_ecs_tilemap::helpers::hex_grid::axial::AxialPos;
+use bevy_ecs_tilemap::prelude::*;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use strum::{Display, EnumIter};
+
+mod paths;
+mod seed;
+mod validate;
+mod wfc;
+
+pub use paths::PathGenerator;
+pub use seed::{MAX_SEED_LEN, parse_seed, seed_label};
+pub use validate::{MapError, generate_valid_map};
+pub use wfc::WaveFunctionCollapse;
+
+pub struct GenerateMapPlugin;
+
+pub const WORLD_MAP_ORIGIN: Vec3 = Vec3::new(10000.0, 0.0, MAP_TILE_LAYER);
+pub const MAP_TILE_LAYER: f32 = 0.0;
+pub const MAP_COORD_SYSTEM: HexCoordSystem = HexCoordSystem::Column;
+
+const GENERATING_STATE: NewGameState = NewGameState::GeneratingWorld;
+
+pub const MAP_TILE_SIZE: TilemapTileSize = TilemapTileSize { x: 52.0, y: 48.0 };
+pub const MAP_TILE_ASSET_LOAD_PATH: &'static str = "embedded://assets/sprites/map_tiles.png";
+
+/// Plugin to setup map generation
+impl Plugin for GenerateMapPlugin {
+    fn build(&self, app: &mut App) {
+        embed_asset!(app, "assets/sprites/map_tiles.png");
+
+        app.add_systems(
+            OnEnter(GENERATING_STATE),
+            (
+                start_generation,
+                spawn_map,
+                #[cfg(feature = "debug")]
+                spawn_tile_labels::<With<MapTilemap>, With<MapTile>>,
+            )
+                .chain(),
+        )
+        .add_systems(Update, poll_generation.run_if(in_state(GENERATING_STATE)))
+        .add_systems(OnExit(GENERATING_STATE), despawn_outline_tiles);
+    }
+}
+
+/// Settings set by the UI before world generation to
+/// give generation parameters.
+#[derive(Resource, Default, Clone)]
+pub struct GenerationSettings {
+    pub seed: u64,
+    /// The seed as the player typed it, so it can be shared.
+    pub seed_text: String,
+    /// Set when this is the daily run for the day, see [`crate::daily_run`].
+    pub daily_run: Option<chrono::NaiveDate>,
+    pub generator: MapGeneratorKind,
+    pub map: MapSettings,
+    /// The classes the party starts with.
+    pub party: Party,
+}
+
+/// The shape and difficulty of a map, chosen for each game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MapSettings {
+    /// How many rooms there are from the entrance to the edge of the map.
+    pub radius: u32,
+    /// How many pillars there are, collecting them all wins the game.
+    pub pillars: u32,
+    pub room_weights: RoomWeights,
+    /// Scales the chance of a combat room having more than one enemy.
+    pub enemy_density: f64,
+}
+
+impl Default for MapSettings {
+    fn default() -> Self {
+        Self {
+            radius: 5,
+            pillars: 4,
+            room_weights: RoomWeights::default(),
+            enemy_density: 1.0,
+        }
+    }
+}
+
+impl MapSettings {
+    pub fn size(&self) -> TilemapSize {
+        TilemapSize {
+            x: self.radius * 2 + 1,
+            y: self.radius * 2 + 1,
+        }
+    }
+
+    /// Where the entrance is, in the middle of the map.
+    pub fn origin(&self) -> TilePos {
+        TilePos {
+            x: self.radius,
+            y: self.radius,
+        }
+    }
+
+    /// Every position on the map.
+    pub fn positions(&self) -> impl Iterator<Item = TilePos> {
+        generate_hexagon(self.axial_origin(), self.radius)
+            .into_iter()
+            .map(|axial_pos| axial_pos.as_tile_pos_given_coord_system(MAP_COORD_SYSTEM))
+    }
+
+    /// The positions `distance` rooms away from the entrance, in order around it.
+    pub fn ring(&self, distance: u32) -> Vec<TilePos> {
+        generate_hex_ring(self.axial_origin(), distance)
+            .into_iter()
+            .map(|axial_pos| axial_pos.as_tile_pos_given_coord_system(MAP_COORD_SYSTEM))
+            .collect()
+    }
+
+    fn axial_origin(&self) -> AxialPos {
+        AxialPos::from_tile_pos_given_coord_system(&self.origin(), MAP_COORD_SYSTEM)
+    }
+}
+
+/// An algorithm that lays out the rooms of a map.
+///
+/// The same settings and rng state must always give the same map,
+/// so a seed can be shared.
+pub trait MapGenerator: Sync {
+    /// Lays out the rooms, which must include an [`RoomType::Entrance`] at
+    /// [`MapSettings::origin`] and [`MapSettings::pillars`] pillars that can all be reached from it.
+    ///
+    /// This runs off the main thread, so it should report its progress to
+    /// the `tracker` and stop with [`MapError::Cancelled`] when asked to.
+    fn generate(
+        &self,
+        settings: &GenerationSettings,
+        rng: &mut RandomSource,
+        tracker: &GenerationTracker,
+    ) -> Result<RoomGraph, MapError>;
+}
+
+/// Shared between a generation task and the game, so the game can
+/// show how far along it is and stop it early.
+#[derive(Clone, Default)]
+pub struct GenerationTracker {
+    /// The bits of an `f32` from 0 to 1, as there is no atomic float.
+    progress: Arc<AtomicU32>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl GenerationTracker {
+    pub fn progress(&self) -> f32 {
+        f32::from_bits(self.progress.load(Ordering::Relaxed))
+    }
+
+    pub fn set_progress(&self, progress: f32) {
+        self.progress
+            .store(progress.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Fails with [`MapError::Cancelled`] once [`Self::cancel`] has been called.
+    pub fn check_cancelled(&self) -> Result<(), MapError> {
+        match self.cancelled.load(Ordering::Relaxed) {
+            true => Err(MapError::Cancelled),
+            false => Ok(()),
+        }
+    }
+}
+
+/// The map being generated in the background.
+#[derive(Resource)]
+pub struct GenerationTask {
+    task: Task<Result<RoomGraph, MapError>>,
+    pub tracker: GenerationTracker,
+}
+
+/// Which [`MapGenerator`] makes the map, kept with the save game.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Display, EnumIter)]
+pub enum MapGeneratorKind {
+    /// Rooms along paths from the entrance to each pillar.
+    #[default]
+    Paths,
+    /// Caves grown with wave function collapse.
+    #[strum(to_string = "Caves (WFC)")]
+    WaveFunctionCollapse,
+}
+
+impl MapGeneratorKind {
+    pub fn generator(&self) -> &'static dyn MapGenerator {
+        match self {
+            MapGeneratorKind::Paths => &PathGenerator,
+            MapGeneratorKind::WaveFunctionCollapse => &WaveFunctionCollapse,
+        }
+    }
+}
+
+/// The rooms made by a [`MapGenerator`].
+///
+/// Rooms are connected to every room next to them on the map,
+/// so only the positions need to be kept.
+#[derive(Default, Debug, Clone)]
+pub struct RoomGraph {
+    rooms: HashMap<TilePos, RoomInfo>,
+}
+
+impl RoomGraph {
+    pub fn insert(&mut self, pos: TilePos, info: RoomInfo) {
+        self.rooms.insert(pos, info);
+    }
+
+    pub fn contains(&self, pos: &TilePos) -> bool {
+        self.rooms.contains_key(pos)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&TilePos, &RoomInfo)> {
+        self.rooms.iter()
+    }
+}
+
+#[derive(Component)]
+pub struct MapTile;
+
+#[derive(Component)]
+pub struct MapTilemap;
+
+/// The number of rooms between two positions.
+pub fn map_distance(a: &TilePos, b: &TilePos) -> u32 {
+    let a = AxialPos::from_tile_pos_given_coord_system(a, MAP_COORD_SYSTEM);
+    let b = AxialPos::from_tile_pos_given_coord_system(b, MAP_COORD_SYSTEM);
+    a.distance_from(&b) as u32
+}
+
+/// Starts generating the map on the [`AsyncComputeTaskPool`].
+fn start_generation(mut commands: Commands, settings: Res<GenerationSettings>) {
+    let settings = settings.clone();
+    let tracker = GenerationTracker::default();
+    let task_tracker = tracker.clone();
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let mut rng = RandomSource::seed_from_u64(settings.seed);
+        generate_valid_map(&settings, &mut rng, &task_tracker)
+    });
+
+    commands.insert_resource(GenerationTask { task, tracker });
+}
+
+/// Spawns tilemap
+fn spawn_map(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<GenerationSettings>,
+) {
+    let tile_sprite = asset_server.load(MAP_TILE_ASSET_LOAD_PATH);
+    let tilemap_entity = commands.spawn_empty().id();
+
+    let mut tile_storage = TileStorage::empty(settings.map.size());
+    let tile_positions = settings.map.positions();
+
+    commands.entity(tilemap_entity).with_children(|parent| {
+        for tile_pos in tile_positions {
+            let id = parent
+                .spawn((
+                    MapTile,
+                    TileBundle {
+                        position: tile_pos,
+                        tilemap_id: TilemapId(tilemap_entity),
+                        texture_index: TileTextureIndex(OUTLINE_TILE),
+                        ..Default::default()
+                    },
+                ))
+                .id();
+            tile_storage.checked_set(&tile_pos, id);
+        }
+    });
+
+    commands.entity(tilemap_entity).insert((
+        MapTilemap,
+        TilemapBundle {
+            grid_size: MAP_TILE_SIZE.into(),
+            map_type: TilemapType::Hexagon(MAP_COORD_SYSTEM),
+            size: settings.map.size(),
+            storage: tile_storage,
+            texture: TilemapTexture::Single(tile_sprite),
+            tile_size: MAP_TILE_SIZE,
+            anchor: TilemapAnchor::Center,
+            transform: Transform::from_translation(WORLD_MAP_ORIGIN),
+            ..Default::default()
+        },
+        StateScoped(AppState::Game),
+    ));
+}
+
+/// Why the last world failed to generate, shown in the new game menu.
+#[derive(Resource)]
+pub struct GenerationError(pub MapError);
+
+/// Reports the progress of the [`GenerationTask`], and once it is done
+/// fills in the map with its rooms.
+/// If it couldn't make a valid map the new game is abandoned.
+fn poll_generation(
+    mut commands: Commands,
+    settings: Res<GenerationSettings>,
+    mut generation_task: ResMut<GenerationTask>,
+    tile_storage: Single<&TileStorage, With<MapTilemap>>,
+    mut tile_text_q: Query<&mut TileTextureIndex>,
+    mut generation_progress: ResMut<GenerationProgress>,
+) {
+    generation_progress.world = generation_task.tracker.progress();
+
+    let Some(result) = check_ready(&mut generation_task.task) else {
+        return;
+    };
+    commands.remove_resource::<GenerationTask>();
+
+    let graph = match result {
+        Ok(graph) => graph,
+        Err(MapError::Cancelled) => return,
+        Err(err) => {
+            error!(
+                "Failed to generate a map for seed {:X}: {err}",
+                settings.seed
+            );
+            commands.insert_resource(GenerationError(err));
+            commands.run_system_cached(abort_generation);
+            return;
+        }
+    };
+
+    for (pos, info) in graph.iter() {
+        let Some(tile) = tile_storage.get(pos) else {
+            warn!("Generated a room at {pos:?}, which is outside of the map");
+            continue;
+        };
+
+        if let Ok(mut texture) = tile_text_q.get_mut(tile) {
+            *texture = TileTextureIndex(0);
+        }
+        commands.entity(tile).insert(info.clone());
+    }
+
+    generation_progress.world_done = true;
+}
+
+fn despawn_outline_tiles(
+    mut commands: Commands,
+    tile_storage: Single<&mut TileStorage, With<MapTilemap>>,
+    tile_q: Query<&TileTextureIndex>,
+) {
+    tile_storage
+        .iter()
+        .filter_map(|opt| *opt)
+        .filter(|tile_entity| {
+            tile_q
+                .get(*tile_entity)
+                .ok()
+                .is_some_and(|texture| *texture == TileTextureIndex(OUTLINE_TILE))
+        })
+        .for_each(|entity| commands.entity(entity).despawn());
+}
+
+#[cfg(test)]
+mod generate_map_tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    fn generate(kind: MapGeneratorKind, seed: u64, map: &MapSettings) -> RoomGraph {
+        let settings = GenerationSettings {
+            seed,
+            generator: kind,
+            map: map.clone(),
+            ..default()
+        };
+        let mut rng = RandomSource::seed_from_u64(seed);
+        kind.generator()
+            .generate(&settings, &mut rng, &GenerationTracker::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_generators_make_valid_maps() {
+        let cases = [(5, 4, 2000), (3, 2, 100), (3, 6, 100), (7, 6, 100)];
+
+        for (radius, pillars, seeds) in cases {
+            let map = MapSettings {
+                radius,
+                pillars,
+                ..default()
+            };
+
+            for kind in MapGeneratorKind::iter() {
+                for seed in 0..seeds {
+                    assert_eq!(
+                        generate(kind, seed, &map).validate(&map),
+                        Ok(()),
+                        "{kind} radius {radius} pillars {pillars} seed {seed}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Something that changes whenever a map does.
+    fn fingerprint(graph: &RoomGraph) -> u64 {
+        let mut rooms = graph
+            .iter()
+            .map(|(pos, info)| format!("{} {} {:?} {}", pos.x, pos.y, info.r_type, info.rng_seed))
+            .collect::<Vec<_>>();
+        rooms.sort();
+
+        // FNV-1a, so it doesn't change between Rust versions.
+        rooms
+            .join("\n")
+            .bytes()
+            .fold(0xcbf29ce484222325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            })
+    }
+
+    /// Seeds with the maps they made when this was written.
+    ///
+    /// If a change to generation is meant to change existing maps,
+    /// update these with the values from the failure message.
+    const REGRESSION_SEEDS: &[(MapGeneratorKind, u64, usize, u64)] = &[
+        (MapGeneratorKind::Paths, 0, 16, 0x86518059e189b234),
+        (MapGeneratorKind::Paths, 1, 18, 0xdcdc5d5a2c6fd00a),
+        (MapGeneratorKind::Paths, 0x5eed, 16, 0x7f38f1d63fdbe84f),
+        (MapGeneratorKind::Paths, 0xdead_beef, 13, 0xbe0e6a5ff4044508),
+        (MapGeneratorKind::Paths, u64::MAX, 19, 0x873fd1388c09dc34),
+        (
+            MapGeneratorKind::WaveFunctionCollapse,
+            0,
+            46,
+            0x0a09eaf290d467d9,
+        ),
+        (
+            MapGeneratorKind::WaveFunctionCollapse,
+            1,
+            39,
+            0x9e7798eea33269ea,
+        ),
+        (
+            MapGeneratorKind::WaveFunctionCollapse,
+            0x5eed,
+            52,
+            0xca6f69c54dd991db,
+        ),
+        (
+            MapGeneratorKind::WaveFunctionCollapse,
+            0xdead_beef,
+            52,
+            0xdb490843a3939b92,
+        ),
+        (
+            MapGeneratorKind::WaveFunctionCollapse,
+            u64::MAX,
+            47,
+            0x7132fc9500256556,
+        ),
+    ];
+
+    #[test]
+    fn test_seed_regressions() {
+        let map = MapSettings::default();
+
+        let found = REGRESSION_SEEDS
+            .iter()
+            .map(|(kind, seed, _, _)| {
+                let graph = generate(*kind, *seed, &map);
+                (*kind, *seed, graph.iter().count(), fingerprint(&graph))
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(found, REGRESSION_SEEDS);
+    }
+
+    #[test]
+    fn test_room_weights() {
+        let map = MapSettings {
+            room_weights: RoomWeights {
+                combat: 0,
+                pit: 0,
+                ..default()
+            },
+            ..default()
+        };
+
+        for kind in MapGeneratorKind::iter() {
+            let graph = generate(kind, 0x5eed, &map);
+            assert!(
+                graph.iter().all(|(_, info)| !matches!(
+                    info.r_type,
+                    RoomType::Combat(_) | RoomType::Pit(_)
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_generators_are_deterministic() {
+        let map = MapSettings::default();
+
+        for kind in MapGeneratorKind::iter() {
+            let rooms = |graph: RoomGraph| {
+                let mut rooms = graph
+                    .iter()
+                    .map(|(pos, info)| (pos.x, pos.y, info.rng_seed))
+                    .collect::<Vec<_>>();
+                rooms.sort();
+                rooms
+            };
+
+            assert_eq!(
+                rooms(generate(kind, 0x5eed, &map)),
+                rooms(generate(kind, 0x5eed, &map))
+            );
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! The original generator, which walks a path from the entrance to each pillar.
+use super::*;
+use bevy_ecs_tilemap::helpers::hex_grid::neighbors::HexNeighbors;
+use rand::Rng;
+
+/// Spreads the pillars around the entrance, then greedily walks from the
+/// entrance to each of them, putting a random room on every step.
+pub struct PathGenerator;
+
+impl MapGenerator for PathGenerator {
+    fn generate(
+        &self,
+        settings: &GenerationSettings,
+        rng: &mut RandomSource,
+        tracker: &GenerationTracker,
+    ) -> Result<RoomGraph, MapError> {
+        let map = &settings.map;
+        let origin = map.origin();
+        let pillars = pillar_positions(map, rng);
+
+        let mut graph = RoomGraph::default();
+        graph.insert(
+            origin,
+            RoomInfo::from_type(RoomType::Entrance, rng.random_range(..u64::MAX)),
+        );
+        for pillar in &pillars {
+            graph.insert(
+                *pillar,
+                RoomInfo::from_type(RoomType::Pillar, rng.random_range(..u64::MAX)),
+            );
+        }
+
+        for (i, pillar) in pillars.iter().enumerate() {
+            tracker.check_cancelled()?;
+            tracker.set_progress(i as f32 / pillars.len() as f32);
+            let mut current_pos = origin;
+
+            while current_pos != *pillar {
+                current_pos = HexNeighbors::<TilePos>::get_neighboring_positions_standard(
+                    &current_pos,
+                    &map.size(),
+                )
+                .iter()
+                .min_by_key(|neighbor| {
+                    neighbor.x.abs_diff(pillar.x) + neighbor.y.abs_diff(pillar.y)
+                })
+                .copied()
+                .unwrap_or(*pillar);
+
+                if !graph.contains(&current_pos) {
+                    graph.insert(
+                        current_pos,
+                        RoomInfo::from_type(
+                            RoomType::from_rng(rng, map),
+                            rng.random_range(..u64::MAX),
+                        ),
+                    );
+                }
+            }
+        }
+
+        tracker.set_progress(1.0);
+        Ok(graph)
+    }
+}
+
+/// Gives each pillar its own slice of the map around the entrance,
+/// somewhere in the outer half of it.
+fn pillar_positions(map: &MapSettings, rng: &mut RandomSource) -> Vec<TilePos> {
+    let pillars = map.pillars as usize;
+
+    (0..pillars)
+        .map(|i| {
+            let distance = rng.random_range(map.radius.div_ceil(2).max(1)..=map.radius);
+            let ring = map.ring(distance);
+
+            let slice = (ring.len() / pillars).max(1);
+            let start = i * ring.len() / pillars;
+            ring[(start + rng.random_range(0..slice)) % ring.len()]
+        })
+        .collect()
+}
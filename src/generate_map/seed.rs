@@ -0,0 +1,86 @@
+//! Turning what the player types into a world seed.
+use thiserror::Error;
+
+/// The longest seed the player can type.
+pub const MAX_SEED_LEN: usize = 32;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SeedError {
+    #[error("Seeds can be at most {MAX_SEED_LEN} characters")]
+    TooLong,
+    #[error("Seeds can't contain control characters")]
+    ControlCharacter,
+}
+
+/// Reads a seed typed by the player, ignoring any whitespace around it.
+///
+/// Up to 16 hex digits are read as the seed itself, so the seeds shown
+/// for random worlds can be typed back in. Anything else is hashed.
+/// Gives None when nothing was typed, for a random seed.
+pub fn parse_seed(text: &str) -> Result<Option<u64>, SeedError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    if text.chars().count() > MAX_SEED_LEN {
+        return Err(SeedError::TooLong);
+    }
+    if text.chars().any(char::is_control) {
+        return Err(SeedError::ControlCharacter);
+    }
+
+    if text.len() <= 16 && text.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Ok(u64::from_str_radix(text, 16).ok());
+    }
+
+    let hash = blake3::hash(text.as_bytes());
+    let (bytes, _) = hash.as_bytes().split_first_chunk::<8>().unwrap();
+    Ok(Some(u64::from_le_bytes(*bytes)))
+}
+
+/// How a seed is shown to the player, which can be typed back in for the same world.
+///
+/// Games from before seeds could be words have no text, so they show the hex.
+pub fn seed_label(seed: u64, text: &str) -> String {
+    match text.is_empty() {
+        true => format!("{seed:X}"),
+        false => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod seed_tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_seeds() {
+        assert_eq!(parse_seed("5EED"), Ok(Some(0x5eed)));
+        assert_eq!(parse_seed(" 5eed\t"), Ok(Some(0x5eed)));
+        assert_eq!(
+            parse_seed(&seed_label(u64::MAX, "")),
+            Ok(Some(u64::MAX)),
+            "shown seeds must give the same world"
+        );
+    }
+
+    #[test]
+    fn test_word_seeds() {
+        let seed = parse_seed("hexes befall").unwrap();
+        assert!(seed.is_some());
+        assert_eq!(parse_seed("  hexes befall "), Ok(seed));
+        assert_ne!(parse_seed("hexes befell"), Ok(seed));
+        // too long to be hex, so it is hashed
+        assert_ne!(parse_seed("10000000000000000"), Ok(Some(0)));
+    }
+
+    #[test]
+    fn test_invalid_seeds() {
+        assert_eq!(parse_seed(""), Ok(None));
+        assert_eq!(parse_seed("   "), Ok(None));
+        assert_eq!(
+            parse_seed(&"a".repeat(MAX_SEED_LEN + 1)),
+            Err(SeedError::TooLong)
+        );
+        assert_eq!(parse_seed("a\u{7}b"), Err(SeedError::ControlCharacter));
+    }
+}
@@ -0,0 +1,244 @@
+//! Checks that a generated [`RoomGraph`] can be played.
+use super::*;
+use bevy_ecs_tilemap::helpers::hex_grid::neighbors::HexNeighbors;
+use std::collections::{HashSet, VecDeque};
+use thiserror::Error;
+
+/// How many maps are generated for a seed before giving up on it.
+pub const GENERATION_ATTEMPTS: usize = 10;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum MapError {
+    #[error("The map has {0} entrances instead of one")]
+    EntranceCount(usize),
+    #[error("The entrance is at {0:?} instead of the middle of the map")]
+    EntranceNotAtOrigin(TilePos),
+    #[error("The map has {found} pillars instead of {expected}")]
+    PillarCount { expected: u32, found: usize },
+    #[error("The room at {0:?} is outside of the map")]
+    OutOfBounds(TilePos),
+    #[error("The pillar at {0:?} can't be reached from the entrance")]
+    UnreachablePillar(TilePos),
+    #[error("The room at {0:?} can't be reached from the entrance")]
+    UnreachableRoom(TilePos),
+    #[error("Generation was cancelled")]
+    Cancelled,
+}
+
+impl RoomGraph {
+    /// The rooms next to `pos`, which are the ones it connects to.
+    pub fn neighbors(&self, pos: &TilePos, map: &MapSettings) -> Vec<TilePos> {
+        HexNeighbors::<TilePos>::get_neighboring_positions_standard(pos, &map.size())
+            .iter()
+            .filter(|neighbor| self.contains(neighbor))
+            .copied()
+            .collect()
+    }
+
+    /// Makes sure there is one entrance in the middle of the map, every
+    /// room is inside of it, and every room including the pillars can be
+    /// reached from the entrance.
+    pub fn validate(&self, map: &MapSettings) -> Result<(), MapError> {
+        let entrances = self
+            .iter()
+            .filter(|(_, info)| info.r_type == RoomType::Entrance)
+            .map(|(pos, _)| *pos)
+            .collect::<Vec<_>>();
+        match entrances[..] {
+            [entrance] if entrance == map.origin() => {}
+            [entrance] => return Err(MapError::EntranceNotAtOrigin(entrance)),
+            _ => return Err(MapError::EntranceCount(entrances.len())),
+        }
+
+        let pillars = self
+            .iter()
+            .filter(|(_, info)| info.r_type == RoomType::Pillar)
+            .count();
+        if pillars != map.pillars as usize {
+            return Err(MapError::PillarCount {
+                expected: map.pillars,
+                found: pillars,
+            });
+        }
+
+        if let Some((pos, _)) = self
+            .iter()
+            .find(|(pos, _)| map_distance(pos, &map.origin()) > map.radius)
+        {
+            return Err(MapError::OutOfBounds(*pos));
+        }
+
+        let mut reachable = HashSet::from([map.origin()]);
+        let mut queue = VecDeque::from([map.origin()]);
+        while let Some(pos) = queue.pop_front() {
+            for neighbor in self.neighbors(&pos, map) {
+                if reachable.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        // Pillars are checked first, as they are the ones that stop a game being won.
+        let mut unreachable = self
+            .iter()
+            .filter(|(pos, _)| !reachable.contains(pos))
+            .collect::<Vec<_>>();
+        unreachable.sort_by_key(|(_, info)| info.r_type != RoomType::Pillar);
+
+        match unreachable.first() {
+            Some((pos, info)) if info.r_type == RoomType::Pillar => {
+                Err(MapError::UnreachablePillar(**pos))
+            }
+            Some((pos, _)) => Err(MapError::UnreachableRoom(**pos)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Generates maps with the chosen [`MapGenerator`] until one is valid.
+///
+/// Each attempt keeps using the same rng, so a seed always gives the same map.
+/// Fails with the last problem found if none of the [`GENERATION_ATTEMPTS`] are valid,
+/// or straight away if the `tracker` is cancelled.
+pub fn generate_valid_map(
+    settings: &GenerationSettings,
+    rng: &mut RandomSource,
+    tracker: &GenerationTracker,
+) -> Result<RoomGraph, MapError> {
+    let generator = settings.generator.generator();
+
+    let mut attempt = 1;
+    loop {
+        tracker.set_progress(0.0);
+        let graph = generator.generate(settings, rng, tracker)?;
+        match graph.validate(&settings.map) {
+            Ok(()) => return Ok(graph),
+            Err(err) if attempt >= GENERATION_ATTEMPTS => return Err(err),
+            Err(err) => {
+                warn!("Generated an invalid map on attempt {attempt}, retrying: {err}");
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod validate_tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    fn room(r_type: RoomType) -> RoomInfo {
+        RoomInfo::from_type(r_type, 0)
+    }
+
+    /// A line of rooms from the entrance to a single pillar.
+    fn line_map() -> (RoomGraph, MapSettings) {
+        let map = MapSettings {
+            radius: 3,
+            pillars: 1,
+            ..default()
+        };
+
+        let mut graph = RoomGraph::default();
+        graph.insert(map.origin(), room(RoomType::Entrance));
+        graph.insert(TilePos { x: 3, y: 4 }, room(RoomType::EmptyRoom));
+        graph.insert(TilePos { x: 3, y: 5 }, room(RoomType::Pillar));
+
+        (graph, map)
+    }
+
+    #[test]
+    fn test_valid_map() {
+        let (graph, map) = line_map();
+        assert_eq!(graph.validate(&map), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_maps() {
+        let (mut graph, map) = line_map();
+        graph.insert(TilePos { x: 0, y: 0 }, room(RoomType::EmptyRoom));
+        assert_eq!(
+            graph.validate(&map),
+            Err(MapError::OutOfBounds(TilePos { x: 0, y: 0 }))
+        );
+
+        let (mut graph, map) = line_map();
+        graph.insert(TilePos { x: 3, y: 4 }, room(RoomType::Pit(1)));
+        graph.insert(TilePos { x: 3, y: 1 }, room(RoomType::EmptyRoom));
+        assert_eq!(
+            graph.validate(&map),
+            Err(MapError::UnreachableRoom(TilePos { x: 3, y: 1 }))
+        );
+
+        let (mut graph, map) = line_map();
+        graph.insert(TilePos { x: 3, y: 1 }, room(RoomType::Pillar));
+        let map = MapSettings { pillars: 2, ..map };
+        assert_eq!(
+            graph.validate(&map),
+            Err(MapError::UnreachablePillar(TilePos { x: 3, y: 1 }))
+        );
+        assert_eq!(
+            graph.validate(&MapSettings { pillars: 3, ..map }),
+            Err(MapError::PillarCount {
+                expected: 3,
+                found: 2
+            })
+        );
+
+        let (mut graph, map) = line_map();
+        graph.insert(TilePos { x: 3, y: 4 }, room(RoomType::Entrance));
+        assert_eq!(graph.validate(&map), Err(MapError::EntranceCount(2)));
+    }
+
+    #[test]
+    fn test_generation_fails_cleanly() {
+        for kind in MapGeneratorKind::iter() {
+            // More pillars than fit around the entrance.
+            let settings = GenerationSettings {
+                generator: kind,
+                map: MapSettings {
+                    radius: 1,
+                    pillars: 7,
+                    ..default()
+                },
+                ..default()
+            };
+            let mut rng = RandomSource::seed_from_u64(0);
+            assert!(matches!(
+                generate_valid_map(&settings, &mut rng, &GenerationTracker::default()),
+                Err(MapError::PillarCount { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_generation_progress() {
+        for kind in MapGeneratorKind::iter() {
+            let settings = GenerationSettings {
+                generator: kind,
+                ..default()
+            };
+            let tracker = GenerationTracker::default();
+            let mut rng = RandomSource::seed_from_u64(0);
+            assert!(generate_valid_map(&settings, &mut rng, &tracker).is_ok());
+            assert_eq!(tracker.progress(), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_generation_cancelled() {
+        for kind in MapGeneratorKind::iter() {
+            let settings = GenerationSettings {
+                generator: kind,
+                ..default()
+            };
+            let tracker = GenerationTracker::default();
+            tracker.cancel();
+            let mut rng = RandomSource::seed_from_u64(0);
+            assert!(matches!(
+                generate_valid_map(&settings, &mut rng, &tracker),
+                Err(MapError::Cancelled)
+            ));
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! A generator using wave function collapse.
+//!
+//! Every cell of the map starts as either a room or empty. Cells are
+//! collapsed one at a time, and after each one the rules below are
+//! propagated to the cells around it:
+//! - A room must have another room next to it.
+//! - A room that has only one neighbor left that could be a room
+//!   forces that neighbor to be a room.
+//!
+//! Together these never contradict each other, so there is no backtracking.
+//! Afterwards paths are carved to any pillar the caves didn't reach.
+use super::*;
+use bevy_ecs_tilemap::helpers::hex_grid::neighbors::HexNeighbors;
+use rand::Rng;
+use std::collections::VecDeque;
+
+/// The chance an undecided cell collapses to a room.
+const ROOM_CHANCE: f64 = 0.45;
+
+/// Grows cave-like maps with wave function collapse.
+pub struct WaveFunctionCollapse;
+
+impl MapGenerator for WaveFunctionCollapse {
+    fn generate(
+        &self,
+        settings: &GenerationSettings,
+        rng: &mut RandomSource,
+        tracker: &GenerationTracker,
+    ) -> Result<RoomGraph, MapError> {
+        let map = &settings.map;
+        let mut wave = Wave::new(map);
+
+        let pillars = pillar_positions(map, &wave.cells, rng);
+        for pos in std::iter::once(&map.origin()).chain(&pillars) {
+            wave.collapse(wave.index[pos], Tile::Room);
+        }
+
+        while let Some(cell) = wave.lowest_entropy(rng) {
+            tracker.check_cancelled()?;
+            tracker.set_progress(wave.collapsed() as f32 / wave.cells.len() as f32);
+
+            let tile = match rng.random_bool(ROOM_CHANCE) {
+                true => Tile::Room,
+                false => Tile::Empty,
+            };
+            wave.collapse(cell, tile);
+        }
+
+        for pillar in &pillars {
+            wave.connect(wave.index[pillar]);
+        }
+
+        let reachable = wave.reachable();
+        let mut graph = RoomGraph::default();
+        for (cell, pos) in wave.cells.iter().enumerate() {
+            if !reachable[cell] {
+                continue;
+            }
+
+            let r_type = if *pos == map.origin() {
+                RoomType::Entrance
+            } else if pillars.contains(pos) {
+                RoomType::Pillar
+            } else {
+                RoomType::from_rng(rng, map)
+            };
+            graph.insert(
+                *pos,
+                RoomInfo::from_type(r_type, rng.random_range(..u64::MAX)),
+            );
+        }
+
+        tracker.set_progress(1.0);
+        Ok(graph)
+    }
+}
+
+/// What a cell can collapse to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tile {
+    Empty,
+    Room,
+}
+
+/// The tiles a cell could still be.
+#[derive(Clone, Copy, Debug)]
+struct Superposition {
+    empty: bool,
+    room: bool,
+}
+
+impl Superposition {
+    const ANY: Superposition = Superposition {
+        empty: true,
+        room: true,
+    };
+
+    fn entropy(&self) -> usize {
+        self.empty as usize + self.room as usize
+    }
+
+    fn is(&self, tile: Tile) -> bool {
+        match tile {
+            Tile::Empty => self.empty && !self.room,
+            Tile::Room => self.room && !self.empty,
+        }
+    }
+}
+
+impl From<Tile> for Superposition {
+    fn from(tile: Tile) -> Self {
+        Superposition {
+            empty: tile == Tile::Empty,
+            room: tile == Tile::Room,
+        }
+    }
+}
+
+/// Every cell of the map and what it could still be.
+struct Wave {
+    origin: TilePos,
+    cells: Vec<TilePos>,
+    index: HashMap<TilePos, usize>,
+    neighbors: Vec<Vec<usize>>,
+    states: Vec<Superposition>,
+}
+
+impl Wave {
+    fn new(map: &MapSettings) -> Self {
+        let cells = map.positions().collect::<Vec<_>>();
+        let index = cells
+            .iter()
+            .enumerate()
+            .map(|(i, pos)| (*pos, i))
+            .collect::<HashMap<_, _>>();
+        let neighbors = cells
+            .iter()
+            .map(|pos| {
+                HexNeighbors::<TilePos>::get_neighboring_positions_standard(pos, &map.size())
+                    .iter()
+                    .filter_map(|neighbor| index.get(neighbor).copied())
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            origin: map.origin(),
+            states: vec![Superposition::ANY; cells.len()],
+            cells,
+            index,
+            neighbors,
+        }
+    }
+
+    /// How many cells have been decided.
+    fn collapsed(&self) -> usize {
+        self.states
+            .iter()
+            .filter(|state| state.entropy() == 1)
+            .count()
+    }
+
+    /// A random cell out of those with the fewest options left,
+    /// or None when every cell has collapsed.
+    fn lowest_entropy(&self, rng: &mut RandomSource) -> Option<usize> {
+        let lowest = self
+            .states
+            .iter()
+            .map(Superposition::entropy)
+            .filter(|entropy| *entropy > 1)
+            .min()?;
+
+        let candidates = (0..self.cells.len())
+            .filter(|cell| self.states[*cell].entropy() == lowest)
+            .collect::<Vec<_>>();
+
+        Some(candidates[rng.random_range(0..candidates.len())])
+    }
+
+    fn collapse(&mut self, cell: usize, tile: Tile) {
+        self.states[cell] = tile.into();
+        self.propagate(cell);
+    }
+
+    /// Applies the rules around a changed cell until nothing else changes.
+    fn propagate(&mut self, cell: usize) {
+        let mut changed = vec![cell];
+
+        while let Some(cell) = changed.pop() {
+            for &other in std::iter::once(&cell).chain(&self.neighbors[cell]) {
+                let room_neighbors = self.neighbors[other]
+                    .iter()
+                    .copied()
+                    .filter(|neighbor| self.states[*neighbor].room)
+                    .collect::<Vec<_>>();
+
+                let state = self.states[other];
+                if state.room && state.empty && room_neighbors.is_empty() {
+                    self.states[other] = Tile::Empty.into();
+                    changed.push(other);
+                } else if state.is(Tile::Room)
+                    && let [only] = room_neighbors[..]
+                    && !self.states[only].is(Tile::Room)
+                {
+                    self.states[only] = Tile::Room.into();
+                    changed.push(only);
+                }
+            }
+        }
+    }
+
+    /// Which cells are rooms that can be reached from the entrance.
+    fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.cells.len()];
+        let origin = self.index[&self.origin];
+        reachable[origin] = true;
+
+        let mut queue = VecDeque::from([origin]);
+        while let Some(cell) = queue.pop_front() {
+            for &neighbor in &self.neighbors[cell] {
+                if !reachable[neighbor] && self.states[neighbor].is(Tile::Room) {
+                    reachable[neighbor] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        reachable
+    }
+
+    /// Turns the cells between the room and the entrance into rooms,
+    /// if it can't be reached already.
+    fn connect(&mut self, mut cell: usize) {
+        let reachable = self.reachable();
+
+        while !reachable[cell] {
+            self.states[cell] = Tile::Room.into();
+            cell = self.neighbors[cell]
+                .iter()
+                .copied()
+                .min_by_key(|neighbor| map_distance(&self.cells[*neighbor], &self.origin))
+                .expect("Every cell has neighbors");
+        }
+    }
+}
+
+/// Pillars far from the entrance and spread apart from each other.
+fn pillar_positions(map: &MapSettings, cells: &[TilePos], rng: &mut RandomSource) -> Vec<TilePos> {
+    let min_distance = map.radius.saturating_sub(2).max(1);
+    let mut candidates = cells
+        .iter()
+        .copied()
+        .filter(|pos| map_distance(pos, &map.origin()) >= min_distance)
+        .collect::<Vec<_>>();
+
+    let pillars_wanted = map.pillars as usize;
+    let mut pillars = Vec::with_capacity(pillars_wanted);
+    while pillars.len() < pillars_wanted && !candidates.is_empty() {
+        let spread = candidates
+            .iter()
+            .copied()
+            .filter(|pos| {
+                pillars
+                    .iter()
+                    .all(|pillar| map_distance(pos, pillar) >= map.radius)
+            })
+            .collect::<Vec<_>>();
+
+        // Fall back to anywhere far enough, rather than having too few pillars.
+        let pool = match spread.is_empty() {
+            true => &candidates,
+            false => &spread,
+        };
+        let pillar = pool[rng.random_range(0..pool.len())];
+
+        candidates.retain(|pos| *pos != pillar);
+        pillars.push(pillar);
+    }
+
+    pillars
+}
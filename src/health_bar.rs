@@ -1,5 +1,4 @@
 use crate::embed_asset;
-use crate::game::*;
 use crate::prelude::*;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -15,7 +14,6 @@ pub const THIEF_IMAGE_PATH: &str = "embedded://assets/sprites/Thief_name.png";
 pub const WARRIOR_IMAGE_PATH: &str = "embedded://assets/sprites/Warrior_name.png";
 
 pub const FONT_SIZE: f32 = 18.0;
-pub const STANDARD_FLEX_GROW: f32 = 1.75;
 
 impl Plugin for HpPlugin {
     fn build(&self, app: &mut App) {
@@ -24,273 +22,115 @@ impl Plugin for HpPlugin {
         embed_asset!(app, "assets/sprites/Priestess_name.png");
         embed_asset!(app, "assets/sprites/Thief_name.png");
         embed_asset!(app, "assets/sprites/Warrior_name.png");
-        app.add_systems(OnEnter(AppState::Game), (create_hp_bars, spawn_hp).chain());
+        app.add_systems(OnEnter(AppState::Game), spawn_hp_bars)
+            .add_systems(Update, update_status_icons.run_if(in_state(AppState::Game)));
     }
 }
 
 #[derive(Component)]
 pub struct HPBar;
 
-fn create_hp_bars(mut commands: Commands, asset_server: Res<AssetServer>) {
-    // Left HP
-    commands
-        .spawn((Node {
-            align_items: AlignItems::Start,
-            flex_direction: FlexDirection::Column,
-            ..default()
-        },))
-        .with_children(|builder| {
-            builder
-                .spawn(Node {
-                    align_items: AlignItems::Start,
-                    flex_direction: FlexDirection::Row,
-                    ..default()
-                })
-                .with_children(|builder| {
-                    builder.spawn((
-                        ImageNode {
-                            image: asset_server.load(WARRIOR_IMAGE_PATH),
-                            ..default()
-                        },
-                        Node {
-                            top: Val::Px(20.0),
-                            margin: UiRect::all(Val::Px(10.0)),
-                            flex_grow: STANDARD_FLEX_GROW,
-                            flex_basis: Val::Px(100.0),
-                            ..default()
-                        },
-                    ));
+/// Shows the [`StatusEffects`] of a party member under their health.
+#[derive(Component)]
+pub struct StatusIcons;
+
+/// The image with the name of a party member, if they have one.
+fn name_plate_path(name: ActorName) -> Option<&'static str> {
+    match name {
+        ActorName::Warrior => Some(WARRIOR_IMAGE_PATH),
+        ActorName::Priestess => Some(PRIESTESS_IMAGE_PATH),
+        ActorName::Theif => Some(THIEF_IMAGE_PATH),
+        _ => None,
+    }
+}
 
-                    builder.spawn((
-                        ImageNode {
-                            image: asset_server.load(PRIESTESS_IMAGE_PATH),
-                            ..default()
-                        },
-                        Node {
-                            top: Val::Px(20.0),
-                            flex_grow: STANDARD_FLEX_GROW + 1.0,
-                            flex_basis: Val::Px(120.0),
-                            margin: UiRect::all(Val::Px(10.0)),
-                            ..default()
-                        },
-                    ));
-                    builder.spawn((
-                        ImageNode {
-                            image: asset_server.load(THIEF_IMAGE_PATH),
-                            ..default()
-                        },
-                        Node {
-                            top: Val::Px(20.0),
-                            flex_grow: STANDARD_FLEX_GROW,
-                            flex_basis: Val::Px(80.0),
-                            margin: UiRect::all(Val::Px(5.0)),
-                            ..default()
-                        },
-                    ));
-                });
-            builder
-                .spawn((
-                    Node {
-                        flex_direction: FlexDirection::Row,
-                        align_items: AlignItems::Start,
-                        ..default()
-                    },
-                    Transform::from_translation(Vec3::new(0.0, 0.0, -1.0)),
-                ))
-                .with_children(|builder| {
-                    builder.spawn((
-                        ImageNode {
-                            image: asset_server.load(HP_SPRITE_IMAGE_PATH),
-                            ..default()
-                        },
-                        Node {
-                            flex_grow: STANDARD_FLEX_GROW,
-                            flex_basis: Val::Px(100.0),
-                            margin: UiRect::all(Val::Px(10.0)),
-                            ..default()
-                        },
-                    ));
-                    builder.spawn((
-                        ImageNode {
-                            image: asset_server.load(HP_SPRITE_IMAGE_PATH),
-                            ..default()
-                        },
-                        Node {
-                            flex_grow: STANDARD_FLEX_GROW,
-                            flex_basis: Val::Px(100.0),
-                            margin: UiRect::all(Val::Px(10.0)),
-                            ..default()
-                        },
-                    ));
-                    builder.spawn((
-                        ImageNode {
-                            image: asset_server.load(HP_SPRITE_IMAGE_PATH),
-                            ..default()
-                        },
-                        Node {
-                            flex_grow: STANDARD_FLEX_GROW,
-                            flex_basis: Val::Px(100.0),
-                            margin: UiRect::all(Val::Px(10.0)),
-                            ..default()
-                        },
-                    ));
-                });
-        });
+fn health_label(health: &Health) -> String {
+    format!(
+        "{}/{}",
+        health.current().map_or(0, |current| current.get()),
+        health.max()
+    )
 }
 
-fn spawn_hp(
+/// Spawns the name, health and status effects of each party member,
+/// in the order they were added to the party.
+fn spawn_hp_bars(
     mut commands: Commands,
-    mut actors_health_q: Query<&Health, With<Actor>>,
     asset_server: Res<AssetServer>,
+    actor_q: Query<(&ActorName, &Team, &Health), With<Actor>>,
 ) {
-    let mut actors_health: Vec<&Health> = Vec::new();
-
-    for health in actors_health_q {
-        actors_health.push(health);
-    }
-
-    commands.spawn((
-        Node {
-            top: Val::Px(67.5),
-            left: Val::Px(56.5),
-            position_type: PositionType::Absolute,
-            justify_content: JustifyContent::Start,
-            align_items: AlignItems::Center,
-            ..default()
-        },
-        HPBar,
-        ActorName::Warrior,
-        Text::new(format!(
-            "{}/{}",
-            actors_health.get(0).unwrap().current().unwrap(),
-            actors_health.get(0).unwrap().max()
-        )),
-        TextFont {
-            font_size: 11.0,
-            ..default()
-        },
-        TextLayout::new_with_justify(JustifyText::Left),
-    ));
-    commands.spawn((
-        Node {
-            top: Val::Px(67.5),
-            left: Val::Px(177.5),
-            position_type: PositionType::Absolute,
-            justify_content: JustifyContent::Start,
-            align_items: AlignItems::Center,
-            ..default()
-        },
-        HPBar,
-        ActorName::Priestess,
-        Text::new(format!(
-            "{}/{}",
-            actors_health.get(1).unwrap().current().unwrap(),
-            actors_health.get(1).unwrap().max()
-        )),
-        TextFont {
-            font_size: 11.0,
-            ..default()
-        },
-        TextLayout::new_with_justify(JustifyText::Left),
-    ));
-
-    commands.spawn((
-        Node {
-            top: Val::Px(67.5),
-            left: Val::Px(297.5),
-            position_type: PositionType::Absolute,
-            justify_content: JustifyContent::Start,
-            align_items: AlignItems::Center,
-            ..default()
-        },
-        HPBar,
-        ActorName::Theif,
-        Text::new(format!(
-            "{}/{}",
-            actors_health.get(2).unwrap().current().unwrap(),
-            actors_health.get(2).unwrap().max()
-        )),
-        TextFont {
-            font_size: 11.0,
+    commands
+        .spawn(Node {
+            align_items: AlignItems::Start,
+            flex_direction: FlexDirection::Row,
             ..default()
-        },
-        TextLayout::new_with_justify(JustifyText::Left),
-    ));
-}
-
-pub fn update_player_hp_bar(
-    mut commands: Commands,
-    active_actor_team: Single<&Team, With<ActingActor>>,
-    active_actor_name: Single<&ActorName, With<ActingActor>>,
-    mut actor_q: Query<(&ActorName, &Health), With<Actor>>,
-    mut text_q: Query<(Entity, &ActorName), With<HPBar>>,
-    actor_action: Res<ActingActorAction>,
-) {
-    match *active_actor_team {
-        Team::Enemy => match **actor_action {
-            Action::Attack { target } => {
-                if let Ok((actor_name, target_health)) = actor_q.get(target) {
-                    let mut health_str: String = format!("");
-                    if let Some(current_health) = target_health.current() {
-                        health_str = format!("{}/{}", current_health, target_health.max());
-                    } else {
-                        health_str = format!("0/{}", target_health.max());
-                    }
-
-                    for (text_entity, text_actorname) in text_q {
-                        if text_actorname == actor_name {
-                            commands
-                                .entity(text_entity)
-                                .remove::<(Text, TextFont, TextLayout)>()
-                                .insert((
-                                    Text::new(health_str),
-                                    TextFont {
-                                        font_size: 11.0,
-                                        ..default()
-                                    },
-                                    TextLayout::new_with_justify(JustifyText::Left),
-                                ));
-                            break;
-                        }
-                    }
-                }
+        })
+        .with_children(|builder| {
+            for (name, _, health) in actor_q.iter().filter(|(_, team, _)| **team == Team::Player) {
+                builder
+                    .spawn(Node {
+                        margin: UiRect::all(Val::Px(10.0)),
+                        row_gap: Val::Px(4.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Start,
+                        ..default()
+                    })
+                    .with_children(|builder| {
+                        match name_plate_path(*name) {
+                            Some(path) => builder.spawn((
+                                ImageNode {
+                                    image: asset_server.load(path),
+                                    ..default()
+                                },
+                                Node {
+                                    height: Val::Px(16.0),
+                                    ..default()
+                                },
+                            )),
+                            None => builder.spawn((
+                                Text::new(name.to_string()),
+                                TextFont {
+                                    font_size: 11.0,
+                                    ..default()
+                                },
+                            )),
+                        };
+
+                        builder.spawn((
+                            ImageNode {
+                                image: asset_server.load(HP_SPRITE_IMAGE_PATH),
+                                ..default()
+                            },
+                            Node {
+                                width: Val::Px(96.0),
+                                height: Val::Px(32.0),
+                                padding: UiRect::left(Val::Px(46.0)),
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            children![(
+                                HPBar,
+                                *name,
+                                Text::new(health_label(health)),
+                                TextFont {
+                                    font_size: 11.0,
+                                    ..default()
+                                },
+                                TextLayout::new_with_justify(JustifyText::Left),
+                            )],
+                        ));
+
+                        builder.spawn((
+                            Node {
+                                column_gap: Val::Px(4.0),
+                                ..default()
+                            },
+                            StatusIcons,
+                            *name,
+                        ));
+                    });
             }
-            _ => {}
-        },
-        Team::Player => match **actor_action {
-            Action::SpecialAction { target } => match *active_actor_name {
-                ActorName::Priestess => {
-                    if let Ok((actor_name, target_health)) = actor_q.get(target) {
-                        let mut health_str: String = format!("");
-                        if let Some(current_health) = target_health.current() {
-                            health_str = format!("{}/{}", current_health, target_health.max());
-                        } else {
-                            health_str = format!("0/{}", target_health.max());
-                        }
-
-                        for (text_entity, text_actorname) in text_q {
-                            if text_actorname == actor_name {
-                                commands
-                                    .entity(text_entity)
-                                    .remove::<(Text, TextFont, TextLayout)>()
-                                    .insert((
-                                        Text::new(health_str),
-                                        TextFont {
-                                            font_size: 11.0,
-                                            ..default()
-                                        },
-                                        TextLayout::new_with_justify(JustifyText::Left),
-                                    ));
-                                break;
-                            }
-                        }
-                    }
-                }
-                _ => {}
-            },
-            _ => {}
-        },
-    }
+        });
 }
 
 pub fn update_player_hp_bar_pit(
@@ -324,3 +164,31 @@ pub fn update_player_hp_bar_pit(
         }
     }
 }
+
+/// Shows each effect with the turns it has left, for the
+/// party members whose effects changed.
+fn update_status_icons(
+    mut commands: Commands,
+    actor_q: Query<(&ActorName, &Team, &StatusEffects), Changed<StatusEffects>>,
+    icons_q: Query<(Entity, &ActorName), With<StatusIcons>>,
+) {
+    for (name, _, effects) in actor_q.iter().filter(|(_, team, _)| **team == Team::Player) {
+        for (entity, _) in icons_q.iter().filter(|(_, icons_name)| *icons_name == name) {
+            commands
+                .entity(entity)
+                .despawn_related::<Children>()
+                .with_children(|builder| {
+                    for effect in effects.iter() {
+                        builder.spawn((
+                            Text::new(format!("{}{}", effect.kind.icon(), effect.turns)),
+                            TextFont {
+                                font_size: 11.0,
+                                ..default()
+                            },
+                            TextColor(effect.kind.color()),
+                        ));
+                    }
+                });
+        }
+    }
+}
@@ -1,11 +1,46 @@
+use crate::game::{ActingActor, CombatEvent, Combatant};
 use crate::prelude::*;
+use crate::room::CurrentRoom;
+use crate::stats::RunStats;
+use bevy::ecs::system::SystemParam;
+use bevy_ecs_tilemap::helpers::hex_grid::neighbors::HexNeighbors;
+use bevy_ecs_tilemap::prelude::*;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use strum::Display;
 
+/// How much health a healing potion gives back.
+pub const HEALING_POTION_HEAL: u32 = 30;
+
+/// The items the party is carrying.
 #[derive(Resource, Deref, DerefMut, Default)]
 pub struct Items(pub Vec<Item>);
 
+impl Items {
+    /// Removes one of the item, returning false if there were none.
+    pub fn take(&mut self, item: Item) -> bool {
+        match self.0.iter().position(|i| *i == item) {
+            Some(idx) => {
+                self.0.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// How many of each item there are, in the order they were found.
+    pub fn counts(&self) -> Vec<(Item, usize)> {
+        let mut counts: Vec<(Item, usize)> = Vec::new();
+        for item in self.0.iter() {
+            match counts.iter_mut().find(|(i, _)| i == item) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((*item, 1)),
+            }
+        }
+        counts
+    }
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, Display)]
 pub enum Item {
     #[strum(to_string = "Healing Potion")]
@@ -24,6 +59,115 @@ impl Item {
             _ => unreachable!(),
         }
     }
+
+    /// Whether the item is used on a single party member.
+    pub fn needs_target(&self) -> bool {
+        match self {
+            Item::HealingPotion => true,
+            Item::VisionPotion => false,
+        }
+    }
+}
+
+/// The rooms next to the one the party is in.
+#[derive(SystemParam)]
+pub struct NearbyRooms<'w, 's> {
+    current_room: Single<'w, &'static TilePos, With<CurrentRoom>>,
+    map: Single<'w, (&'static TilemapSize, &'static TileStorage), With<MapTilemap>>,
+    info_q: Query<'w, 's, &'static mut RoomInfo>,
+}
+
+impl NearbyRooms<'_, '_> {
+    /// Shows what is in each of them on the map.
+    fn reveal(&mut self) {
+        let (map_size, storage) = *self.map;
+        let neighbors = HexNeighbors::<TilePos>::get_neighboring_positions_standard(
+            &self.current_room,
+            map_size,
+        );
+
+        for entity in neighbors.iter().filter_map(|pos| storage.checked_get(pos)) {
+            if let Ok(mut info) = self.info_q.get_mut(entity) {
+                info.revealed = true;
+            }
+        }
+    }
+}
+
+/// Sends the [`CombatEvent`]s for items used in a fight,
+/// where the acting actor is the one using them.
+#[derive(SystemParam)]
+pub struct ItemEvents<'w, 's> {
+    acting_actor: Option<Single<'w, Entity, With<ActingActor>>>,
+    actor_q: Query<'w, 's, (&'static ActorName, &'static Team)>,
+    events: EventWriter<'w, CombatEvent>,
+}
+
+impl ItemEvents<'_, '_> {
+    fn combatant(&self, entity: Entity) -> Option<Combatant> {
+        let (name, team) = self.actor_q.get(entity).ok()?;
+        Some(Combatant {
+            entity,
+            name: *name,
+            team: *team,
+        })
+    }
+
+    /// Sends that the item was used, with how much it healed the `target`
+    /// if it is a healing item. Nothing is sent outside of a fight.
+    fn send(&mut self, item: Item, target: Entity, healed: Option<u32>) {
+        let Some(actor) = self
+            .acting_actor
+            .as_ref()
+            .and_then(|actor| self.combatant(**actor))
+        else {
+            return;
+        };
+
+        self.events.write(CombatEvent::UseItem { actor, item });
+        if let (Some(amount), Some(target)) = (healed, self.combatant(target)) {
+            self.events.write(CombatEvent::Heal {
+                healer: actor,
+                target,
+                amount,
+            });
+        }
+    }
+}
+
+/// Uses up one of the item from [`Items`].
+/// The `target` is the party member it's used on, and
+/// is ignored by items where [`Item::needs_target`] is false.
+pub fn use_item(
+    In((item, target)): In<(Item, Entity)>,
+    mut commands: Commands,
+    mut items: ResMut<Items>,
+    mut health_q: Query<&mut Health>,
+    mut nearby_rooms: NearbyRooms,
+    mut stats: ResMut<RunStats>,
+    mut events: ItemEvents,
+) {
+    if !items.take(item) {
+        warn!("Tried to use a {item} without having one");
+        return;
+    }
+    stats.potions_used += 1;
+
+    match item {
+        Item::HealingPotion => {
+            let healed = health_q.get_mut(target).ok().map(|mut health| {
+                let health_before = health.current().map_or(0, |h| h.get());
+                health.heal(HEALING_POTION_HEAL);
+                health.current().map_or(0, |h| h.get()) - health_before
+            });
+            events.send(item, target, healed);
+            commands.run_system_cached(update_player_hp_bar_pit);
+        }
+        Item::VisionPotion => {
+            nearby_rooms.reveal();
+            events.send(item, target, None);
+        }
+    }
 }
 
 // #[cfg(feature = "sqlite")]
@@ -52,45 +196,115 @@ impl Item {
 // }
 
 #[cfg(feature = "sqlite")]
-pub fn save_items(
-    items: Res<Items>,
-    save_info: Res<SaveGame>,
-    db: NonSend<Database>,
-) -> Result<(), DatabaseError> {
-    let game_id = save_info.game_id.0;
+pub fn save_items(db: &Database, game_id: GameID, items: &[Item]) -> Result<(), DatabaseError> {
     db.connection
-        .execute("DELETE FROM Item WHERE game_id = :game_id", (game_id,))?;
+        .execute("DELETE FROM Item WHERE game_id = :game_id", (game_id.0,))?;
 
     let query = "INSERT INTO Item(game_id, type) VALUES(:game_id, :type)";
     let mut statement = db.connection.prepare(query)?;
-    for item in items.0.iter() {
+    for item in items {
         let item = ron::to_string(&item).unwrap();
-        statement.execute((game_id, item))?;
+        statement.execute((game_id.0, item))?;
     }
 
     Ok(())
 }
 
 #[cfg(feature = "sqlite")]
-pub fn load_items(
-    mut commands: Commands,
-    save_info: Res<SaveGame>,
-    db: NonSend<Database>,
-) -> Result<(), DatabaseError> {
-    let game_id = save_info.game_id.0;
-
-    let items = db
-        .connection
+pub fn load_items(db: &Database, game_id: GameID) -> Result<Vec<Item>, DatabaseError> {
+    db.connection
         .prepare("SELECT type FROM Item WHERE game_id = :game_id")?
-        .query_map((game_id,), |row| {
+        .query_map((game_id.0,), |row| {
             let i_type = row.get::<_, String>(0)?;
             let i_type = ron::from_str(&i_type).unwrap();
 
             Ok(i_type)
         })?
-        .collect::<Result<Vec<Item>, _>>()?;
+        .collect()
+}
 
-    commands.insert_resource(Items(items));
+#[cfg(test)]
+mod items_tests {
+    use super::*;
 
-    Ok(())
+    #[test]
+    fn test_take() {
+        let mut items = Items(vec![Item::VisionPotion, Item::HealingPotion]);
+
+        assert!(items.take(Item::HealingPotion));
+        assert!(!items.take(Item::HealingPotion));
+        assert_eq!(items.0, vec![Item::VisionPotion]);
+    }
+
+    #[test]
+    fn test_counts() {
+        let items = Items(vec![
+            Item::VisionPotion,
+            Item::HealingPotion,
+            Item::VisionPotion,
+        ]);
+
+        assert_eq!(
+            items.counts(),
+            vec![(Item::VisionPotion, 2), (Item::HealingPotion, 1)]
+        );
+        assert!(Items::default().counts().is_empty());
+    }
+
+    #[test]
+    fn test_use_item_events() {
+        use bevy::ecs::system::RunSystemOnce;
+        use std::num::NonZero;
+
+        let mut world = World::new();
+        world.init_resource::<Events<CombatEvent>>();
+        world.init_resource::<RunStats>();
+        world.insert_resource(Items(vec![Item::HealingPotion, Item::HealingPotion]));
+        let map_size = TilemapSize { x: 1, y: 1 };
+        world.spawn((map_size, TileStorage::empty(map_size), MapTilemap));
+        world.spawn((TilePos { x: 0, y: 0 }, CurrentRoom));
+
+        let warrior = world
+            .spawn((
+                ActorName::Warrior,
+                Team::Player,
+                Health::with_current(NonZero::new(70), NonZero::new(80).unwrap()),
+            ))
+            .id();
+        let events = |world: &mut World| {
+            let mut events = world.resource_mut::<Events<CombatEvent>>();
+            events.drain().collect::<Vec<_>>()
+        };
+
+        // nothing is sent outside of a fight
+        world
+            .run_system_once_with(use_item, (Item::HealingPotion, warrior))
+            .unwrap();
+        assert!(events(&mut world).is_empty());
+
+        world.entity_mut(warrior).insert(ActingActor);
+        world.get_mut::<Health>(warrior).unwrap().damage(20);
+        world
+            .run_system_once_with(use_item, (Item::HealingPotion, warrior))
+            .unwrap();
+        let actor = Combatant {
+            entity: warrior,
+            name: ActorName::Warrior,
+            team: Team::Player,
+        };
+        assert_eq!(
+            events(&mut world),
+            vec![
+                CombatEvent::UseItem {
+                    actor,
+                    item: Item::HealingPotion
+                },
+                CombatEvent::Heal {
+                    healer: actor,
+                    target: actor,
+                    amount: 20
+                },
+            ]
+        );
+    }
 }
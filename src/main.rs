@@ -2,6 +2,7 @@ mod actor;
 mod animation;
 mod camera;
 mod controls;
+mod daily_run;
 mod database;
 mod game;
 mod generate_map;
@@ -9,10 +10,10 @@ mod health_bar;
 mod items;
 mod menu;
 mod room;
-#[cfg(feature = "sqlite")]
 mod saving;
 mod sky;
 mod spawn_map;
+mod stats;
 mod style;
 mod tile;
 mod util;
@@ -44,8 +45,9 @@ pub mod prelude {
     pub use crate::health_bar::*;
     pub use crate::items::{Item, Items};
     pub use crate::room::{RoomInfo, RoomTile, RoomTilemap, RoomType};
-    #[cfg(feature = "sqlite")]
-    pub use crate::saving::{GameID, SaveGame, SaveGameInfo};
+    pub use crate::saving::{
+        GameID, SaveBackend, SaveGame, SaveGameInfo, SnapshotID, SnapshotInfo, SnapshotKind,
+    };
     pub use crate::style::{Icons, Style};
     pub use crate::tile::*;
     pub use crate::util::*;
@@ -54,6 +56,7 @@ pub mod prelude {
 use animation::AnimationPlugin;
 use camera::CameraPlugin;
 use controls::ControlsPlugin;
+use daily_run::DailyRunPlugin;
 use database::DatabasePlugin;
 use game::GamePlugin;
 use generate_map::GenerateMapPlugin;
@@ -61,6 +64,7 @@ use health_bar::HpPlugin;
 use menu::MenuPlugin;
 use prelude::*;
 use sky::SkyPlugin;
+use stats::StatsPlugin;
 use style::StylePlugin;
 use tile::TilePlugin;
 //use attack_options::AttackOptionsPlugin;
@@ -115,9 +119,12 @@ fn main() {
     app.init_state::<AppState>();
     // Local Plugins
     app.add_plugins(DatabasePlugin)
+        .add_plugins(ActorPlugin)
         .add_plugins(AnimationPlugin)
         .add_plugins(TilePlugin)
         .add_plugins(GamePlugin)
+        .add_plugins(DailyRunPlugin)
+        .add_plugins(StatsPlugin)
         .add_plugins(StylePlugin)
         .add_plugins(ControlsPlugin)
         .add_plugins(MenuPlugin)
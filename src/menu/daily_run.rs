@@ -0,0 +1,164 @@
+use super::MenuState;
+use super::new_game::{NewGameState, begin_generation};
+use crate::daily_run::{daily_seed_text, daily_settings, leaderboard, today};
+use crate::generate_map::GenerationSettings;
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// How many results of the day are shown.
+const LEADERBOARD_LENGTH: usize = 10;
+
+pub struct MenuDailyRunPlugin;
+
+impl Plugin for MenuDailyRunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuState::DailyRun), daily_run_enter);
+    }
+}
+
+fn daily_run_enter(mut commands: Commands, style: Res<Style>, db: NonSend<Database>) {
+    let date = today();
+    let results = leaderboard(&*db, date).unwrap_or_else(|err| {
+        warn!("Failed to read the daily run leaderboard with {err}");
+        Vec::new()
+    });
+
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let button_text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            StateScoped(MenuState::DailyRun),
+        ))
+        .with_children(|builder| {
+            builder
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|builder| {
+                    builder.spawn((
+                        Text::new(format!("Daily Run for {date}")),
+                        button_text_style.clone(),
+                    ));
+                    builder.spawn((
+                        Text::new(format!("seed: {}", daily_seed_text(date))),
+                        style.font(24.0),
+                        TextColor(style.text_color),
+                        Node {
+                            margin: UiRect::bottom(Val::Px(20.0)),
+                            ..default()
+                        },
+                    ));
+
+                    if results.is_empty() {
+                        builder.spawn((
+                            Text::new("Nobody has finished today's run yet"),
+                            style.font(24.0),
+                            TextColor(style.text_color),
+                        ));
+                    }
+
+                    for (rank, result) in results.iter().take(LEADERBOARD_LENGTH).enumerate() {
+                        builder.spawn((
+                            Text::new(format!(
+                                "{}. {} in {} turns, {} rooms cleared (game {})",
+                                rank + 1,
+                                result.outcome,
+                                result.turns,
+                                result.rooms_cleared,
+                                result.game_id.0,
+                            )),
+                            style.font(24.0),
+                            TextColor(style.text_color),
+                        ));
+                    }
+                });
+
+            builder
+                .spawn((
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(80.0),
+                        padding: UiRect::all(Val::Px(5.0)),
+                        position_type: PositionType::Absolute,
+                        align_items: AlignItems::Center,
+                        justify_items: JustifyItems::Center,
+                        align_self: AlignSelf::End,
+                        ..default()
+                    },
+                    BackgroundColor(style.background_color),
+                ))
+                .with_children(|builder| {
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new("Back"),
+                                button_text_style.clone(),
+                                Pickable::IGNORE
+                            )],
+                        ))
+                        .observe(change_state_on_click(
+                            PointerButton::Primary,
+                            MenuState::Main,
+                        ));
+
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new("Play"),
+                                button_text_style.clone(),
+                                Pickable::IGNORE
+                            )],
+                        ))
+                        .observe(play_daily_run_click);
+                });
+        });
+}
+
+/// Generates today's map, using the same screens as a new game.
+fn play_daily_run_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut settings: ResMut<GenerationSettings>,
+    mut next_menu_state: ResMut<NextState<MenuState>>,
+    mut next_new_game_state: ResMut<NextState<NewGameState>>,
+    db: NonSend<Database>,
+) {
+    let PointerButton::Primary = click.button else {
+        return;
+    };
+    click.propagate(false);
+
+    *settings = daily_settings(today());
+    begin_generation(&mut commands, &db, &settings);
+
+    next_menu_state.set(MenuState::NewGame);
+    next_new_game_state.set(NewGameState::GeneratingWorld);
+}
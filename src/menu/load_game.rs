@@ -14,20 +14,22 @@ impl Plugin for MenuLoadGamePlugin {
         #[cfg(feature = "debug")]
         app.add_systems(Update, log_transitions::<LoadGameState>);
         app.add_systems(
-            OnEnter(MenuState::LoadGame),
-            (get_save_games, load_game_enter).chain(),
+            OnEnter(LoadGameState::Main),
+            (
+                remove_resource::<PromptTarget>,
+                (get_save_games, load_game_enter).chain(),
+            ),
         )
         .add_systems(OnExit(MenuState::LoadGame), remove_resource::<SaveGames>)
         .add_systems(OnEnter(LoadGameState::Prompt), prompt_enter)
-        .add_systems(
-            OnEnter(LoadGameState::Main),
-            remove_resource::<PromptTarget>,
-        )
         .add_systems(Update, escape_out.run_if(in_state(MenuState::LoadGame)))
         .add_systems(
             OnEnter(LoadGameState::Loading),
             (prep_loading, crate::saving::load_game).chain(),
         );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(OnEnter(LoadGameState::Import), import_enter);
     }
 }
 
@@ -39,6 +41,9 @@ pub enum LoadGameState {
     Main,
     Prompt,
     Loading,
+    /// Lists the exported save files that can be imported.
+    #[cfg(not(target_arch = "wasm32"))]
+    Import,
 }
 
 #[derive(Resource)]
@@ -47,8 +52,21 @@ pub struct SaveGames(pub Box<[SaveGameInfo]>);
 #[derive(Resource)]
 struct PromptTarget(pub GameID);
 
+/// Shows the result of an action in the menu, like exporting a save.
+#[derive(Component)]
+struct StatusText;
+
+/// A snapshot of the [`PromptTarget`] that can be restored.
+#[derive(Component)]
+struct SnapshotButton(pub SnapshotID);
+
+/// A save file that can be imported.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Component)]
+struct ImportFileButton(pub std::path::PathBuf);
+
 fn get_save_games(mut commands: Commands, db: NonSend<Database>) {
-    let games = SaveGameInfo::get_all(&db).unwrap();
+    let games = db.save_infos().unwrap();
 
     commands.insert_resource(SaveGames(games));
 }
@@ -73,6 +91,8 @@ fn escape_out(
         match *controls_state.get() {
             L::Main => next_menu_state.set(MenuState::Main),
             L::Prompt | L::Loading => next_load_game_state.set(LoadGameState::Main),
+            #[cfg(not(target_arch = "wasm32"))]
+            L::Import => next_load_game_state.set(LoadGameState::Main),
         }
     }
 }
@@ -125,7 +145,7 @@ fn load_game_enter(mut commands: Commands, style: Res<Style>, saves: Res<SaveGam
                 justify_content: JustifyContent::Center,
                 ..default()
             },
-            StateScoped(MenuState::LoadGame),
+            StateScoped(LoadGameState::Main),
         ))
         .with_children(|builder| {
             if saves.0.len() == 0 {
@@ -198,6 +218,23 @@ fn load_game_enter(mut commands: Commands, style: Res<Style>, saves: Res<SaveGam
                             PointerButton::Primary,
                             MenuState::Main,
                         ));
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new("Import"),
+                                button_text_style.clone(),
+                                Pickable::IGNORE
+                            )],
+                        ))
+                        .observe(change_state_on_click(
+                            PointerButton::Primary,
+                            LoadGameState::Import,
+                        ));
                 });
         });
 }
@@ -265,7 +302,7 @@ fn game_entry(builder: &mut ChildSpawnerCommands<'_>, style: &Style, game: SaveG
                             ));
 
                             builder.spawn((
-                                Text::new(format!("seed: {:X}", game.world_seed)),
+                                Text::new(format!("seed: {}", game.seed_label())),
                                 style.font(24.0),
                                 Pickable::IGNORE,
                             ));
@@ -274,7 +311,17 @@ fn game_entry(builder: &mut ChildSpawnerCommands<'_>, style: &Style, game: SaveG
         });
 }
 
-fn prompt_enter(mut commands: Commands, style: Res<Style>) {
+fn prompt_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    db: NonSend<Database>,
+    target: Res<PromptTarget>,
+) {
+    let snapshots = db.snapshots(target.0).unwrap_or_else(|err| {
+        warn!("Failed to read snapshots of game {}: {err}", target.0.0);
+        Box::new([])
+    });
+
     let button_text_style = (
         style.font(33.0),
         TextColor(style.text_color),
@@ -346,10 +393,273 @@ fn prompt_enter(mut commands: Commands, style: Res<Style>) {
                             PointerButton::Primary,
                             LoadGameState::Main,
                         ));
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(200.0),
+                                height: Val::Px(65.0),
+                                margin: UiRect::all(Val::Px(5.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                align_self: AlignSelf::Center,
+                                ..default()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(Text::new("Export"), button_text_style.clone())],
+                        ))
+                        .observe(export_on_click);
+
+                    if !snapshots.is_empty() {
+                        builder.spawn((
+                            Text::new("Restore from"),
+                            style.font(33.0),
+                            TextColor(style.title_color),
+                        ));
+                    }
+
+                    builder
+                        .spawn(Node {
+                            max_height: Val::Percent(40.0),
+                            padding: UiRect::all(Val::Px(5.0)),
+                            row_gap: Val::Px(5.0),
+                            align_items: AlignItems::Center,
+                            overflow: Overflow::scroll_y(),
+                            flex_direction: FlexDirection::Column,
+                            ..default()
+                        })
+                        .observe(update_scroll_position_event)
+                        .with_children(|builder| {
+                            for snapshot in snapshots {
+                                builder
+                                    .spawn((
+                                        Node {
+                                            min_height: Val::Px(40.0),
+                                            align_items: AlignItems::Center,
+                                            padding: UiRect::px(20.0, 20.0, 5.0, 5.0),
+                                            ..default()
+                                        },
+                                        Button,
+                                        BackgroundColor(style.button_color),
+                                        AccessibilityNode(Accessible::new(Role::ListItem)),
+                                        SnapshotButton(snapshot.id),
+                                        Pickable {
+                                            should_block_lower: false,
+                                            is_hoverable: true,
+                                        },
+                                        children![(
+                                            Text::new(format!(
+                                                "{}: {}",
+                                                snapshot.kind,
+                                                snapshot.saved.format("%Y/%m/%d %H:%M:%S")
+                                            )),
+                                            style.font(24.0),
+                                            Pickable::IGNORE
+                                        )],
+                                    ))
+                                    .observe(restore_on_click);
+                            }
+                        });
+
+                    builder.spawn((
+                        Text::default(),
+                        StatusText,
+                        style.font(24.0),
+                        TextColor(style.text_color),
+                    ));
                 });
         });
 }
 
+fn restore_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    db: NonSend<Database>,
+    target: Res<PromptTarget>,
+    snapshots: Query<&SnapshotButton>,
+    mut status: Single<&mut Text, With<StatusText>>,
+    mut next_state: ResMut<NextState<LoadGameState>>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    let Ok(SnapshotButton(id)) = snapshots.get(click.target()) else {
+        return;
+    };
+
+    match crate::saving::restore_snapshot(&*db, target.0, *id) {
+        Ok(()) => next_state.set(LoadGameState::Loading),
+        Err(err) => {
+            warn!(
+                "Failed to restore snapshot {} of game {}: {err}",
+                id.0, target.0.0
+            );
+            status.0 = format!("Failed to restore: {err}");
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn export_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    db: NonSend<Database>,
+    target: Res<PromptTarget>,
+    mut status: Single<&mut Text, With<StatusText>>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    status.0 = match crate::saving::export_save(&db, target.0) {
+        Ok(path) => format!("Exported to {}", path.display()),
+        Err(err) => {
+            warn!("Failed to export game {}: {err}", target.0.0);
+            format!("Failed to export: {err}")
+        }
+    };
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn import_enter(mut commands: Commands, style: Res<Style>) {
+    let files = crate::saving::exported_saves().unwrap_or_else(|err| {
+        warn!("Failed to read exported saves: {err}");
+        Vec::new()
+    });
+
+    let button_text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+
+    commands
+        .spawn((
+            Node {
+                display: Display::Flex,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            StateScoped(LoadGameState::Import),
+            BackgroundColor(style.background_color),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new(format!(
+                    "Save files in {}",
+                    crate::saving::export_directory().display()
+                )),
+                style.font(24.0),
+                TextColor(style.title_color),
+            ));
+
+            builder
+                .spawn(Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(70.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    overflow: Overflow::scroll_y(),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                })
+                .observe(update_scroll_position_event)
+                .with_children(|builder| {
+                    if files.is_empty() {
+                        builder.spawn((Text::new("No Save Files"), TextColor(style.title_color)));
+                    }
+
+                    for file in files {
+                        let name = file
+                            .file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_default();
+
+                        builder
+                            .spawn((
+                                Node {
+                                    min_height: Val::Px(50.0),
+                                    align_items: AlignItems::Center,
+                                    padding: UiRect::px(20.0, 20.0, 5.0, 5.0),
+                                    ..default()
+                                },
+                                Button,
+                                BackgroundColor(style.button_color),
+                                AccessibilityNode(Accessible::new(Role::ListItem)),
+                                ImportFileButton(file),
+                                Pickable {
+                                    should_block_lower: false,
+                                    is_hoverable: true,
+                                },
+                                children![(Text::new(name), style.font(24.0), Pickable::IGNORE)],
+                            ))
+                            .observe(import_on_click);
+                    }
+                });
+
+            builder.spawn((
+                Text::default(),
+                StatusText,
+                style.font(24.0),
+                TextColor(style.text_color),
+            ));
+
+            builder
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(65.0),
+                        margin: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                    children![(Text::new("Back"), button_text_style.clone())],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    LoadGameState::Main,
+                ));
+        });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn import_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    db: NonSend<Database>,
+    files: Query<&ImportFileButton>,
+    mut status: Single<&mut Text, With<StatusText>>,
+    mut next_state: ResMut<NextState<LoadGameState>>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    let Ok(ImportFileButton(path)) = files.get(click.target()) else {
+        return;
+    };
+
+    match crate::saving::import_save(&db, path) {
+        Ok(_) => next_state.set(LoadGameState::Main),
+        Err(err) => {
+            warn!("Failed to import {}: {err}", path.display());
+            status.0 = format!("Failed to import: {err}");
+        }
+    }
+}
+
 fn prep_loading(mut commands: Commands, db: NonSend<Database>, target: Res<PromptTarget>) {
     commands.insert_resource(SaveGame::load(&db, target.0));
 }
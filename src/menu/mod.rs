@@ -2,18 +2,20 @@
 //! TODO: Implement title screen and pausing separately.
 
 pub mod controls;
-#[cfg(feature = "sqlite")]
+pub mod daily_run;
 pub mod load_game;
 pub mod new_game;
+pub mod statistics;
 
 use crate::embed_asset;
 use crate::prelude::*;
 use bevy::input_focus::InputFocus;
 use bevy::{input::mouse::MouseScrollUnit, prelude::*};
 use controls::*;
-#[cfg(feature = "sqlite")]
+use daily_run::*;
 use load_game::*;
 use new_game::*;
+use statistics::*;
 
 const TITLE_IMAGE_PATH: &str = "embedded://assets/sprites/title.png";
 
@@ -30,8 +32,9 @@ impl Plugin for MenuPlugin {
         app.add_plugins(MenuControlsPlugin)
             .add_plugins(MenuNewGamePlugin);
 
-        #[cfg(feature = "sqlite")]
-        app.add_plugins(MenuLoadGamePlugin);
+        app.add_plugins(MenuLoadGamePlugin)
+            .add_plugins(MenuDailyRunPlugin)
+            .add_plugins(MenuStatisticsPlugin);
 
         app.add_systems(
             Update,
@@ -55,8 +58,9 @@ pub enum MenuState {
     Sound,
     Controls,
     NewGame,
-    #[cfg(feature = "sqlite")]
     LoadGame,
+    DailyRun,
+    Statistics,
 }
 
 /// Tag component used to mark which setting is currently selected
@@ -83,10 +87,9 @@ fn escape_out(
                 // they implement it themselves
                 | M::NewGame
                 | M::Controls => {}
-            #[cfg(feature = "sqlite")]
             M::LoadGame => {}
 
-            M::Settings => next_state.set(MenuState::Main),
+            M::Settings | M::DailyRun | M::Statistics => next_state.set(MenuState::Main),
             M::Sound | M::Display => next_state.set(MenuState::Settings),
         }
     }
@@ -124,7 +127,46 @@ fn quit_game_on_click(
     }
 }
 
-fn main_enter(mut commands: Commands, style: Res<Style>, asset_server: Res<AssetServer>) {
+/// Marks the continue button with the save game it loads.
+#[derive(Component)]
+struct ContinueGame(GameID);
+
+/// Loads the most recent save game.
+fn continue_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    target: Query<&ContinueGame>,
+) {
+    click.propagate(false);
+
+    let PointerButton::Primary = click.button else {
+        return;
+    };
+
+    let Ok(ContinueGame(game_id)) = target.get(click.target()) else {
+        return;
+    };
+    let game_id = *game_id;
+
+    commands.queue(move |world: &mut World| {
+        let save = SaveGame::load(world.non_send_resource::<Database>(), game_id);
+        world.insert_resource(save);
+        crate::saving::load_game(world);
+    });
+}
+
+fn main_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    asset_server: Res<AssetServer>,
+    db: NonSend<Database>,
+) {
+    let latest_save = db
+        .save_infos()
+        .inspect_err(|e| warn!("Failed to get save games with {e}"))
+        .ok()
+        .and_then(|saves| saves.first().map(|save| save.id));
+
     // Common style for all buttons on the screen
     let button_node = Node {
         width: Val::Px(300.0),
@@ -166,16 +208,41 @@ fn main_enter(mut commands: Commands, style: Res<Style>, asset_server: Res<Asset
                             ..default()
                         },
                     ));
+
+                    if let Some(game_id) = latest_save {
+                        builder
+                            .spawn((
+                                Button,
+                                button_node.clone(),
+                                BackgroundColor(style.button_color),
+                                ContinueGame(game_id),
+                                children![(
+                                    Text::new("Continue"),
+                                    button_text_font.clone(),
+                                    TextColor(style.text_color),
+                                    Pickable::IGNORE
+                                ),],
+                            ))
+                            .observe(continue_on_click);
+                    }
+
                     [
                         (
                             change_state_on_click(PointerButton::Primary, MenuState::NewGame),
                             "New Game",
                         ),
-                        #[cfg(feature = "sqlite")]
+                        (
+                            change_state_on_click(PointerButton::Primary, MenuState::DailyRun),
+                            "Daily Run",
+                        ),
                         (
                             change_state_on_click(PointerButton::Primary, MenuState::LoadGame),
                             "Load Game",
                         ),
+                        (
+                            change_state_on_click(PointerButton::Primary, MenuState::Statistics),
+                            "Statistics",
+                        ),
                         (
                             change_state_on_click(PointerButton::Primary, MenuState::Settings),
                             "Settings",
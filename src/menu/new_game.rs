@@ -1,26 +1,35 @@
 use super::MenuState;
-use crate::generate_map::GenerationSettings;
+use crate::generate_map::{
+    GenerationError, GenerationSettings, GenerationTask, MAX_SEED_LEN, MapGeneratorKind, parse_seed,
+};
 use crate::prelude::*;
 use crate::room::CurrentRoom;
+use crate::stats::RunStats;
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
 use bevy::input_focus::InputFocus;
 use bevy::prelude::*;
-use bevy_ui_text_input::{TextInputContents, TextInputFilter, TextInputMode, TextInputNode};
+use bevy_ui_text_input::{TextInputContents, TextInputMode, TextInputNode};
+use strum::IntoEnumIterator;
 
 pub struct MenuNewGamePlugin;
 impl Plugin for MenuNewGamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_sub_state::<NewGameState>();
+        app.add_sub_state::<NewGameState>()
+            .init_resource::<GenerationSettings>();
         #[cfg(feature = "debug")]
         app.add_systems(Update, log_transitions::<NewGameState>);
         app.add_systems(OnEnter(NewGameState::Main), new_game_enter)
+            .add_systems(OnEnter(NewGameState::ChooseParty), choose_party_enter)
             .add_systems(
                 OnEnter(NewGameState::GeneratingWorld),
                 (generating_world_enter, setup_party),
             )
             .add_systems(Update, escape_out.run_if(in_state(MenuState::NewGame)))
+            .add_systems(Update, seed_message.run_if(in_state(NewGameState::Main)))
             .add_systems(
                 Update,
-                progress_check.run_if(in_state(NewGameState::GeneratingWorld)),
+                (progress_check, update_progress_bar)
+                    .run_if(in_state(NewGameState::GeneratingWorld)),
             );
     }
 }
@@ -31,11 +40,15 @@ impl Plugin for MenuNewGamePlugin {
 pub enum NewGameState {
     #[default]
     Main,
+    /// Picking the classes in the [`Party`].
+    ChooseParty,
     GeneratingWorld,
 }
 
 #[derive(Resource, Default)]
 pub struct GenerationProgress {
+    /// How far along the world generation is, from 0 to 1.
+    pub world: f32,
     pub world_done: bool,
     pub characters_done: bool,
 }
@@ -52,6 +65,134 @@ pub struct WorldNameTextBox;
 #[derive(Component)]
 pub struct WorldSeedTextBox;
 
+/// Says whether the seed in the [`WorldSeedTextBox`] can be used.
+#[derive(Component)]
+struct SeedMessage;
+
+const EMPTY_SEED_MESSAGE: &str = "Leave empty for a random seed";
+
+/// The filled part of the world generation progress bar.
+#[derive(Component)]
+struct ProgressBarFill;
+
+#[derive(Component)]
+struct ProgressText;
+
+/// Adds or removes a class from the [`Party`] when clicked.
+#[derive(Component)]
+struct PartyButton(ActorName);
+
+#[derive(Component)]
+struct PartySizeText;
+
+/// Changes a [`Setting`] to its next option when clicked.
+#[derive(Component)]
+struct SettingButton(Setting);
+
+/// The parts of the [`GenerationSettings`] that can be picked in the menu.
+#[derive(Clone, Copy)]
+enum Setting {
+    Generator,
+    Radius,
+    Pillars,
+    EnemyDensity,
+    EmptyRooms,
+    CombatRooms,
+    PitRooms,
+    ItemRooms,
+}
+
+const RADIUS_OPTIONS: &[u32] = &[3, 4, 5, 6, 7];
+const PILLAR_OPTIONS: &[u32] = &[2, 3, 4, 5, 6];
+const WEIGHT_OPTIONS: &[u32] = &[0, 1, 2, 3];
+const ENEMY_DENSITY_OPTIONS: &[(f64, &str)] = &[
+    (0.5, "Few"),
+    (1.0, "Normal"),
+    (1.5, "Many"),
+    (2.0, "Swarming"),
+];
+
+impl Setting {
+    const ALL: [Setting; 8] = [
+        Setting::Generator,
+        Setting::Radius,
+        Setting::Pillars,
+        Setting::EnemyDensity,
+        Setting::EmptyRooms,
+        Setting::CombatRooms,
+        Setting::PitRooms,
+        Setting::ItemRooms,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Setting::Generator => "Generator",
+            Setting::Radius => "Map Radius",
+            Setting::Pillars => "Pillars",
+            Setting::EnemyDensity => "Enemies",
+            Setting::EmptyRooms => "Empty Rooms",
+            Setting::CombatRooms => "Combat Rooms",
+            Setting::PitRooms => "Pit Rooms",
+            Setting::ItemRooms => "Item Rooms",
+        }
+    }
+
+    fn value(&self, settings: &GenerationSettings) -> String {
+        let map = &settings.map;
+        match self {
+            Setting::Generator => settings.generator.to_string(),
+            Setting::Radius => map.radius.to_string(),
+            Setting::Pillars => map.pillars.to_string(),
+            Setting::EnemyDensity => ENEMY_DENSITY_OPTIONS
+                .iter()
+                .find(|(density, _)| *density == map.enemy_density)
+                .map_or_else(
+                    || map.enemy_density.to_string(),
+                    |(_, name)| name.to_string(),
+                ),
+            Setting::EmptyRooms => map.room_weights.empty.to_string(),
+            Setting::CombatRooms => map.room_weights.combat.to_string(),
+            Setting::PitRooms => map.room_weights.pit.to_string(),
+            Setting::ItemRooms => map.room_weights.item.to_string(),
+        }
+    }
+
+    fn next(&self, settings: &mut GenerationSettings) {
+        let map = &mut settings.map;
+        let weights = &mut map.room_weights;
+        match self {
+            Setting::Generator => {
+                settings.generator = next_option(
+                    &MapGeneratorKind::iter().collect::<Vec<_>>(),
+                    settings.generator,
+                )
+            }
+            Setting::Radius => map.radius = next_option(RADIUS_OPTIONS, map.radius),
+            Setting::Pillars => map.pillars = next_option(PILLAR_OPTIONS, map.pillars),
+            Setting::EnemyDensity => {
+                let densities = ENEMY_DENSITY_OPTIONS
+                    .iter()
+                    .map(|(density, _)| *density)
+                    .collect::<Vec<_>>();
+                map.enemy_density = next_option(&densities, map.enemy_density);
+            }
+            Setting::EmptyRooms => weights.empty = next_option(WEIGHT_OPTIONS, weights.empty),
+            Setting::CombatRooms => weights.combat = next_option(WEIGHT_OPTIONS, weights.combat),
+            Setting::PitRooms => weights.pit = next_option(WEIGHT_OPTIONS, weights.pit),
+            Setting::ItemRooms => weights.item = next_option(WEIGHT_OPTIONS, weights.item),
+        }
+    }
+}
+
+/// The option after `current`, going back to the first after the last.
+fn next_option<T: Copy + PartialEq>(options: &[T], current: T) -> T {
+    let next = options
+        .iter()
+        .position(|option| *option == current)
+        .map_or(0, |i| (i + 1) % options.len());
+    options[next]
+}
+
 fn progress_check(
     mut commands: Commands,
     progress: Res<GenerationProgress>,
@@ -73,17 +214,17 @@ fn progress_check(
 
         commands.entity(current_room).insert(CurrentRoom);
 
-        #[cfg(feature = "sqlite")]
         commands.run_system_cached(crate::saving::save_game);
         next_state.set(AppState::Game);
     }
 }
 
 fn escape_out(
+    mut commands: Commands,
     new_game_state: Res<State<NewGameState>>,
     mut input_focus: ResMut<InputFocus>,
-    mut next_new_game_state: ResMut<NextState<NewGameState>>,
     mut next_menu_state: ResMut<NextState<MenuState>>,
+    mut next_new_game_state: ResMut<NextState<NewGameState>>,
     key: Res<ControlState>,
 ) {
     if key.just_pressed(Control::Pause) {
@@ -97,30 +238,57 @@ fn escape_out(
             S::Main => {
                 next_menu_state.set(MenuState::Main);
             }
-            S::GeneratingWorld => {
+            S::ChooseParty => {
                 next_new_game_state.set(NewGameState::Main);
             }
+            S::GeneratingWorld => {
+                commands.run_system_cached(abort_generation);
+            }
         }
     }
 }
 
-fn cancel_generation(
-    mut click: Trigger<Pointer<Click>>,
-    mut commands: Commands,
-    room_map_q: Query<Entity, With<RoomTilemap>>,
-    party: Query<Entity, With<Actor>>,
-    mut next_new_game_state: ResMut<NextState<NewGameState>>,
-) {
+fn cancel_generation(mut click: Trigger<Pointer<Click>>, mut commands: Commands) {
     let PointerButton::Primary = click.button else {
         return;
     };
 
+    commands.run_system_cached(abort_generation);
+
+    click.propagate(false);
+}
+
+/// The room and map tilemaps of a game.
+type TilemapFilter = Or<(With<RoomTilemap>, With<MapTilemap>)>;
+
+/// Throws away the game being generated and goes back to the new game menu.
+pub fn abort_generation(
+    mut commands: Commands,
+    room_map_q: Query<Entity, TilemapFilter>,
+    party: Query<Entity, With<Actor>>,
+    mut next_new_game_state: ResMut<NextState<NewGameState>>,
+    save_game: Option<Res<SaveGame>>,
+    generation_task: Option<Res<GenerationTask>>,
+    db: NonSend<Database>,
+) {
     next_new_game_state.set(NewGameState::Main);
 
+    // stop the background task rather than waiting for a map nobody will play
+    if let Some(generation_task) = generation_task {
+        generation_task.tracker.cancel();
+        commands.remove_resource::<GenerationTask>();
+    }
+
+    // the game never started, so don't leave it in the save games
+    if let Some(save_game) = save_game
+        && let Err(err) = db.delete_save(save_game.game_id)
+    {
+        warn!("Failed to delete cancelled save game with {err}");
+    }
+
     commands.remove_resource::<GenerationProgress>();
-    #[cfg(feature = "sqlite")]
     commands.remove_resource::<SaveGame>();
-    commands.remove_resource::<GenerationSettings>();
+    commands.remove_resource::<RunStats>();
 
     for entity in room_map_q.iter() {
         commands.entity(entity).despawn();
@@ -129,8 +297,43 @@ fn cancel_generation(
     for entity in party.iter() {
         commands.entity(entity).despawn();
     }
+}
 
+/// Keeps the seed from the [`WorldSeedTextBox`] and goes on to choosing the party.
+fn choose_party_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut next_new_game_state: ResMut<NextState<NewGameState>>,
+    contents_query: Query<&TextInputContents, With<WorldSeedTextBox>>,
+    mut settings: ResMut<GenerationSettings>,
+) {
+    let PointerButton::Primary = click.button else {
+        return;
+    };
     click.propagate(false);
+
+    let text = contents_query
+        .single()
+        .inspect_err(|e| warn!("Failed to get seed from textbox with {e}"))
+        .map(|contents| contents.get().trim().to_string())
+        .unwrap_or_default();
+
+    // the seed message already says what is wrong with it
+    let (seed, seed_text) = match parse_seed(&text) {
+        Ok(Some(seed)) => (seed, text),
+        Ok(None) => {
+            let seed = getrandom::u64().unwrap_or(0x5eed_0f0e_feee);
+            (seed, format!("{seed:X}"))
+        }
+        Err(err) => {
+            warn!("Can't generate a world with seed {text:?}: {err}");
+            return;
+        }
+    };
+    settings.seed = seed;
+    settings.seed_text = seed_text;
+    settings.daily_run = None;
+
+    next_new_game_state.set(NewGameState::ChooseParty);
 }
 
 fn generate_world_click(
@@ -138,34 +341,151 @@ fn generate_world_click(
     mut commands: Commands,
     mut next_new_game_state: ResMut<NextState<NewGameState>>,
     db: NonSend<Database>,
-    contents_query: Query<&TextInputContents, With<WorldSeedTextBox>>,
+    settings: Res<GenerationSettings>,
+) {
+    let PointerButton::Primary = click.button else {
+        return;
+    };
+    click.propagate(false);
+
+    begin_generation(&mut commands, &db, &settings);
+    next_new_game_state.set(NewGameState::GeneratingWorld);
+}
+
+fn party_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut settings: ResMut<GenerationSettings>,
+    buttons: Query<(&PartyButton, &Children)>,
+    mut text_q: Query<&mut Text, Without<PartySizeText>>,
+    mut size_text: Single<&mut Text, With<PartySizeText>>,
 ) {
     let PointerButton::Primary = click.button else {
         return;
     };
+    click.propagate(false);
 
-    let seed = contents_query
-        .single()
-        .inspect_err(|e| warn!("Failed to get seed from textbox with {e}"))
-        .ok()
-        .and_then(|seed| {
-            u64::from_str_radix(seed.get(), 16)
-                .inspect_err(|e| warn!("Failed to parse seed from textbox with {e}"))
-                .ok()
-        })
-        .unwrap_or_else(|| getrandom::u64().unwrap_or(0x5eed_f0e_feee));
+    let Ok((PartyButton(name), children)) = buttons.get(click.target()) else {
+        return;
+    };
+
+    if !settings.party.toggle(*name) {
+        return;
+    }
+
+    let mut text = text_q.iter_many_mut(children);
+    while let Some(mut text) = text.fetch_next() {
+        text.0 = party_label(&settings.party, *name).into();
+    }
+    size_text.0 = party_size_label(&settings.party);
+}
 
+fn party_label(party: &Party, name: ActorName) -> &'static str {
+    if party.contains(&name) {
+        "In Party"
+    } else {
+        "Left Out"
+    }
+}
+
+fn party_size_label(party: &Party) -> String {
+    format!("Party size: {}/{}", party.len(), PARTY_CLASSES.len())
+}
+
+/// Creates the save game and everything else needed before
+/// [`NewGameState::GeneratingWorld`] makes the world.
+pub fn begin_generation(commands: &mut Commands, db: &Database, settings: &GenerationSettings) {
     commands.insert_resource(GenerationProgress::default());
-    #[cfg(feature = "sqlite")]
-    commands.insert_resource(SaveGame::new(&db, seed));
-    commands.insert_resource(GenerationSettings { seed: seed });
+    commands.insert_resource(SaveGame::new(db, settings));
+    commands.insert_resource(Items::default());
+    commands.insert_resource(RunStats::default());
+}
 
-    next_new_game_state.set(NewGameState::GeneratingWorld);
+fn seed_message(
+    contents_q: Query<&TextInputContents, (With<WorldSeedTextBox>, Changed<TextInputContents>)>,
+    mut message: Single<&mut Text, With<SeedMessage>>,
+) {
+    for contents in contents_q.iter() {
+        message.0 = match parse_seed(contents.get()) {
+            Ok(None) => EMPTY_SEED_MESSAGE.into(),
+            Ok(Some(seed)) => format!("World seed {seed:X}"),
+            Err(err) => err.to_string(),
+        };
+    }
+}
 
+fn setting_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut settings: ResMut<GenerationSettings>,
+    buttons: Query<(&SettingButton, &Children)>,
+    mut text_q: Query<&mut Text>,
+) {
+    let PointerButton::Primary = click.button else {
+        return;
+    };
     click.propagate(false);
+
+    let Ok((SettingButton(setting), children)) = buttons.get(click.target()) else {
+        return;
+    };
+
+    setting.next(&mut settings);
+
+    let mut text = text_q.iter_many_mut(children);
+    while let Some(mut text) = text.fetch_next() {
+        text.0 = setting.value(&settings);
+    }
 }
 
-fn new_game_enter(mut commands: Commands, style: Res<Style>) {
+/// A label and a [`SettingButton`] showing its current value.
+fn setting_row(
+    builder: &mut ChildSpawnerCommands,
+    style: &Style,
+    settings: &GenerationSettings,
+    setting: Setting,
+) {
+    builder
+        .spawn(Node {
+            width: Val::Px(420.0),
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            margin: UiRect::vertical(Val::Px(2.0)),
+            ..default()
+        })
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new(setting.label()),
+                style.font(24.0),
+                TextColor(style.text_color),
+            ));
+
+            builder
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        padding: UiRect::px(10.0, 10.0, 5.0, 5.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                    SettingButton(setting),
+                    children![(
+                        Text::new(setting.value(settings)),
+                        style.font(24.0),
+                        TextColor(style.text_color),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(setting_click);
+        });
+}
+
+fn new_game_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    settings: Res<GenerationSettings>,
+    error: Option<Res<GenerationError>>,
+) {
     let button_node = Node {
         width: Val::Px(200.0),
         height: Val::Px(65.0),
@@ -181,6 +501,8 @@ fn new_game_enter(mut commands: Commands, style: Res<Style>) {
         TextLayout::new_with_justify(JustifyText::Center),
     );
 
+    commands.remove_resource::<GenerationError>();
+
     //let button_node_clone = button_node.clone();
     commands
         .spawn((
@@ -233,8 +555,7 @@ fn new_game_enter(mut commands: Commands, style: Res<Style>) {
                                     mode: TextInputMode::SingleLine,
                                     focus_on_pointer_down: true,
                                     unfocus_on_submit: true,
-                                    max_chars: Some(16),
-                                    filter: Some(TextInputFilter::Hex),
+                                    max_chars: Some(MAX_SEED_LEN),
                                     ..default()
                                 },
                                 button_text_style.clone(),
@@ -242,18 +563,37 @@ fn new_game_enter(mut commands: Commands, style: Res<Style>) {
                         })
                         .observe(stop_event_propagate::<Pointer<Click>>);
 
+                    builder.spawn((
+                        Text::new(EMPTY_SEED_MESSAGE),
+                        style.font(24.0),
+                        TextColor(style.text_color),
+                        SeedMessage,
+                    ));
+
+                    for setting in Setting::ALL {
+                        setting_row(builder, &style, &settings, setting);
+                    }
+
                     builder
                         .spawn((
                             Button,
                             button_node.clone(),
                             BackgroundColor(style.button_color),
                             children![(
-                                Text::new("Generate World"),
+                                Text::new("Choose Party"),
                                 button_text_style.clone(),
                                 Pickable::IGNORE
                             )],
                         ))
-                        .observe(generate_world_click);
+                        .observe(choose_party_click);
+
+                    if let Some(GenerationError(err)) = error.as_deref() {
+                        builder.spawn((
+                            Text::new(format!("Failed to generate the world: {err}")),
+                            style.font(24.0),
+                            TextColor(style.text_color),
+                        ));
+                    }
                 });
 
             builder
@@ -290,6 +630,128 @@ fn new_game_enter(mut commands: Commands, style: Res<Style>) {
         });
 }
 
+fn choose_party_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    settings: Res<GenerationSettings>,
+) {
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let button_text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            StateScoped(NewGameState::ChooseParty),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("Party"),
+                style.font(48.0),
+                TextColor(style.title_color),
+            ));
+
+            for name in PARTY_CLASSES {
+                builder
+                    .spawn(Node {
+                        width: Val::Px(420.0),
+                        justify_content: JustifyContent::SpaceBetween,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::vertical(Val::Px(2.0)),
+                        ..default()
+                    })
+                    .with_children(|builder| {
+                        builder.spawn((
+                            Text::new(name.to_string()),
+                            style.font(24.0),
+                            TextColor(style.text_color),
+                        ));
+
+                        builder
+                            .spawn((
+                                Button,
+                                Node {
+                                    width: Val::Px(200.0),
+                                    padding: UiRect::px(10.0, 10.0, 5.0, 5.0),
+                                    justify_content: JustifyContent::Center,
+                                    ..default()
+                                },
+                                BackgroundColor(style.button_color),
+                                PartyButton(name),
+                                children![(
+                                    Text::new(party_label(&settings.party, name)),
+                                    style.font(24.0),
+                                    TextColor(style.text_color),
+                                    Pickable::IGNORE
+                                )],
+                            ))
+                            .observe(party_click);
+                    });
+            }
+
+            builder.spawn((
+                Text::new(party_size_label(&settings.party)),
+                style.font(24.0),
+                TextColor(style.text_color),
+                PartySizeText,
+            ));
+
+            builder
+                .spawn(Node {
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|builder| {
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new("Back"),
+                                button_text_style.clone(),
+                                Pickable::IGNORE
+                            )],
+                        ))
+                        .observe(change_state_on_click(
+                            PointerButton::Primary,
+                            NewGameState::Main,
+                        ));
+
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new("Generate World"),
+                                button_text_style.clone(),
+                                Pickable::IGNORE
+                            )],
+                        ))
+                        .observe(generate_world_click);
+                });
+        });
+}
+
 fn generating_world_enter(mut commands: Commands, style: Res<Style>) {
     let button_node = Node {
         width: Val::Px(200.0),
@@ -335,6 +797,34 @@ fn generating_world_enter(mut commands: Commands, style: Res<Style>) {
                     BackgroundColor(style.background_color),
                 ))
                 .with_children(|builder| {
+                    builder
+                        .spawn((
+                            Node {
+                                flex_grow: 1.0,
+                                height: Val::Px(30.0),
+                                margin: UiRect::all(Val::Px(5.0)),
+                                ..default()
+                            },
+                            BackgroundColor(style.button_color),
+                        ))
+                        .with_child((
+                            Node {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            BackgroundColor(style.text_color),
+                            ProgressBarFill,
+                        ));
+                    builder.spawn((
+                        Node {
+                            width: Val::Px(100.0),
+                            ..default()
+                        },
+                        Text::new("0%"),
+                        button_text_style.clone(),
+                        ProgressText,
+                    ));
                     builder
                         .spawn((
                             Button,
@@ -351,15 +841,33 @@ fn generating_world_enter(mut commands: Commands, style: Res<Style>) {
         });
 }
 
+fn update_progress_bar(
+    progress: Res<GenerationProgress>,
+    mut fill: Single<&mut Node, With<ProgressBarFill>>,
+    mut text: Single<&mut Text, With<ProgressText>>,
+) {
+    let percent = progress.world * 100.0;
+    fill.width = Val::Percent(percent);
+    text.0 = format!("{percent:.0}%");
+}
+
 fn setup_party(
     mut commands: Commands,
     mut progress: ResMut<GenerationProgress>,
     asset_server: Res<AssetServer>,
+    registry: Res<ActorRegistry>,
+    settings: Res<GenerationSettings>,
 ) {
-    use ActorName as A;
-    for name in [A::Warrior, A::Priestess, A::Theif] {
+    for name in settings.party.iter().copied() {
         commands.spawn((
-            ActorBundle::from_name(&asset_server, name, Team::Player, Transform::IDENTITY, true),
+            ActorBundle::from_name(
+                &asset_server,
+                &registry,
+                name,
+                Team::Player,
+                Transform::IDENTITY,
+                true,
+            ),
             Visibility::Hidden,
         ));
     }
@@ -0,0 +1,73 @@
+use super::MenuState;
+use crate::prelude::*;
+use crate::stats::{LifetimeStats, StatsBackend, spawn_stats, stats_lines};
+use bevy::prelude::*;
+
+pub struct MenuStatisticsPlugin;
+
+impl Plugin for MenuStatisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuState::Statistics), statistics_enter);
+    }
+}
+
+fn statistics_enter(mut commands: Commands, style: Res<Style>, db: NonSend<Database>) {
+    let runs = db.all_run_stats().unwrap_or_else(|err| {
+        warn!("Failed to read the run stats with {err}");
+        Vec::new()
+    });
+    let lifetime = LifetimeStats::from_runs(&runs);
+
+    let mut lines = vec![
+        format!("Runs: {}", lifetime.runs),
+        format!("Victories: {}", lifetime.victories),
+        format!("Game Overs: {}", lifetime.game_overs),
+    ];
+    lines.extend(stats_lines(&lifetime.totals));
+
+    let button_text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            StateScoped(MenuState::Statistics),
+        ))
+        .with_children(|builder| {
+            builder.spawn((Text::new("Lifetime Statistics"), button_text_style.clone()));
+            spawn_stats(builder, &style, lines);
+
+            builder
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(65.0),
+                        margin: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Back"),
+                        button_text_style.clone(),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    MenuState::Main,
+                ));
+        });
+}
@@ -1,4 +1,6 @@
+use crate::generate_map::MapSettings;
 use crate::prelude::*;
+use crate::stats::RunStats;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::helpers::hex_grid::axial::AxialPos;
 use bevy_ecs_tilemap::prelude::*;
@@ -25,6 +27,13 @@ pub struct RoomInfo {
     pub cleared: bool,
     pub r_type: RoomType,
     pub rng_seed: u64,
+    /// Whether the party has been in the room.
+    #[serde(default)]
+    pub visited: bool,
+    /// Whether what is in the room is shown on the map,
+    /// i.e. after drinking a vision potion next to it.
+    #[serde(default)]
+    pub revealed: bool,
 }
 
 impl RoomInfo {
@@ -33,6 +42,8 @@ impl RoomInfo {
             cleared: false,
             r_type,
             rng_seed,
+            visited: false,
+            revealed: false,
         }
     }
 }
@@ -59,9 +70,6 @@ pub enum RoomType {
     ///
     /// When cleared, the item is automatically collected
     /// thus later visits will not grant the item again.
-    ///
-    /// TODO: Replace the `()` with the `Item` type when
-    /// that is created.
     Item(Item),
     /// The entrance room, with nothing interesting
     ///
@@ -71,16 +79,66 @@ pub enum RoomType {
     Pillar,
 }
 
+/// How likely each kind of random room is, relative to the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomWeights {
+    pub empty: u32,
+    pub combat: u32,
+    pub pit: u32,
+    pub item: u32,
+}
+
+impl Default for RoomWeights {
+    fn default() -> Self {
+        Self {
+            empty: 1,
+            combat: 1,
+            pit: 1,
+            item: 1,
+        }
+    }
+}
+
+impl RoomType {
+    /// A random room, picked using the map's [`RoomWeights`].
+    /// If every weight is zero the room is empty.
+    pub fn from_rng(rng: &mut impl Rng, map: &MapSettings) -> RoomType {
+        let RoomWeights {
+            empty,
+            combat,
+            pit,
+            item,
+        } = map.room_weights;
+
+        let total = empty + combat + pit + item;
+        if total == 0 {
+            return RoomType::EmptyRoom;
+        }
+
+        let val = rng.random_range(0..total);
+
+        if val < empty {
+            RoomType::EmptyRoom
+        } else if val < empty + combat {
+            RoomType::Combat(ActorName::get_enemies(rng, map.enemy_density))
+        } else if val < empty + combat + pit {
+            RoomType::Pit(rng.random_range(0..21))
+        } else {
+            RoomType::Item(Item::get_rand_item(rng))
+        }
+    }
+}
+
 impl RoomType {
-    pub fn from_rng(rng: &mut impl Rng) -> RoomType {
-        let val = rng.random_range(0..3);
-
-        match val {
-            0 => RoomType::EmptyRoom,
-            1 => RoomType::Combat(ActorName::get_enemies(rng)),
-            2 => RoomType::Pit(rng.random_range(0..21)),
-            //3 => RoomType::Item(Item::get_rand_item(rng)),
-            _ => unreachable!(),
+    /// The color of a revealed room on the map.
+    pub fn map_color(&self) -> Color {
+        match self {
+            RoomType::EmptyRoom => Color::srgb(0.8, 0.8, 0.8),
+            RoomType::Combat(_) => Color::srgb(0.9, 0.3, 0.3),
+            RoomType::Pit(_) => Color::srgb(0.9, 0.6, 0.2),
+            RoomType::Item(_) => Color::srgb(0.3, 0.9, 0.4),
+            RoomType::Entrance => Color::srgb(0.4, 0.6, 1.0),
+            RoomType::Pillar => Color::srgb(1.0, 0.9, 0.3),
         }
     }
 }
@@ -90,6 +148,11 @@ impl RoomType {
 #[derive(Component)]
 pub struct CurrentRoom;
 
+/// Marker for the room the player came from,
+/// which is where they go if they flee a fight.
+#[derive(Component)]
+pub struct PreviousRoom;
+
 /// Marker to indicate whether an entity should despawn
 /// when the room it was spawned in is exited.
 #[derive(Component)]
@@ -158,6 +221,7 @@ pub fn spawn_room_entities(
     mut commands: Commands,
     info: Single<&RoomInfo, With<CurrentRoom>>,
     asset_server: Res<AssetServer>,
+    registry: Res<ActorRegistry>,
     tilemap: Single<
         (
             &TilemapSize,
@@ -196,22 +260,36 @@ pub fn spawn_room_entities(
 
                 commands.spawn((
                     InRoom,
-                    ActorBundle::from_name(&asset_server, *name, Team::Enemy, transform, !cleared),
+                    ActorBundle::from_name(
+                        &asset_server,
+                        &registry,
+                        *name,
+                        Team::Enemy,
+                        transform,
+                        !cleared,
+                    ),
+                    registry.get(*name).brain,
                     Pickable::default(),
                     Visibility::Visible,
                 ));
             }
         }
-        R::Item(item) => match item {
-            Item::HealingPotion => {}
-
-            Item::VisionPotion => {}
-        },
-        R::Pit(damage) => {}
+        R::Item(_) => {}
+        R::Pit(_) => {}
         R::Pillar => {}
     }
 }
 
+pub fn mark_room_visited(
+    mut info: Single<&mut RoomInfo, With<CurrentRoom>>,
+    mut stats: ResMut<RunStats>,
+) {
+    if !info.visited {
+        stats.rooms_visited += 1;
+    }
+    info.visited = true;
+}
+
 /// Should be run after the room
 pub fn mark_room_cleared(mut info: Single<&mut RoomInfo, With<CurrentRoom>>) {
     match info.r_type {
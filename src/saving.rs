@@ -1,11 +1,18 @@
-use crate::generate_map::MapTilemap;
+use crate::generate_map::{
+    GenerationSettings, MapGeneratorKind, MapSettings, MapTilemap, seed_label,
+};
 use crate::prelude::*;
 use crate::room::CurrentRoom;
+use crate::spawn_map::RoomSave;
+use crate::stats::RunStats;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
-
-#[cfg(feature = "sqlite")]
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+use strum::Display;
+use thiserror::Error;
 
 pub struct SavePlugin;
 
@@ -26,177 +33,603 @@ pub enum SaveState {
 }
 
 /// The rowid of the save game table.
-#[derive(Deref, DerefMut, Clone, Copy)]
+#[derive(Deref, DerefMut, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GameID(pub i64);
 
 /// The global resource for the currently loaded save game.
 #[derive(Resource)]
 pub struct SaveGame {
     pub game_id: GameID,
+    pub created: DateTime<Utc>,
     /// The seed used to generate the world
     pub seed: u64,
+    /// The seed as the player typed it, see [`seed_label`]
+    pub seed_text: String,
+    /// How the world was generated
+    pub generator: MapGeneratorKind,
+    pub map: MapSettings,
     pub pillar_count: u64,
+    /// The day this is the daily run for, if it is one.
+    pub daily_run: Option<NaiveDate>,
+    /// The combat turns taken so far.
+    pub turns: u64,
 }
 
-#[cfg(feature = "sqlite")]
 impl SaveGame {
-    pub fn new(db: &Database, seed: u64) -> Self {
-        let query = "INSERT INTO SaveGame(last_saved,world_seed) VALUES(datetime('now'), ?1)";
-        db.connection.execute(query, (seed as i64,)).unwrap();
-
-        let game_id = db.connection.last_insert_rowid();
+    pub fn new(db: &Database, settings: &GenerationSettings) -> Self {
+        let save = SaveData {
+            seed_text: settings.seed_text.clone(),
+            generator: settings.generator,
+            map: settings.map.clone(),
+            daily_run: settings.daily_run,
+            ..SaveData::new(settings.seed)
+        };
+        let game_id = db.new_save(&save).unwrap();
 
         Self {
-            game_id: GameID(game_id),
-            seed,
+            game_id,
+            created: save.created,
+            seed: save.seed,
+            seed_text: save.seed_text,
+            generator: save.generator,
+            map: save.map,
             pillar_count: 0,
+            daily_run: save.daily_run,
+            turns: 0,
         }
     }
 
     pub fn load(db: &Database, game_id: GameID) -> Self {
-        let query =
-            "SELECT world_seed,pillar_count FROM SaveGame WHERE SaveGame.game_id = :game_id";
-
-        let world_seed = db
-            .connection
-            .query_one(query, (game_id.0,), |row| {
-                Ok((row.get::<_, i64>(0)?, row.get(1)?))
-            })
-            .unwrap();
+        let save = db.read_save(game_id).unwrap();
 
         Self {
             game_id,
-            seed: world_seed.0 as u64,
-            pillar_count: world_seed.1,
+            created: save.created,
+            seed: save.seed,
+            seed_text: save.seed_text,
+            generator: save.generator,
+            map: save.map,
+            pillar_count: save.pillar_count,
+            daily_run: save.daily_run,
+            turns: save.turns,
         }
     }
+}
 
-    /// Updates the [`SaveGame`] database entry with the new save time and current room
-    pub fn save(&self, db: &Database, current_room: &TilePos) -> Result<(), DatabaseError> {
-        let query = "
-        UPDATE SaveGame
-            SET last_saved = datetime('now'),
-                current_room_x = :current_room_x,
-                current_room_y = :current_room_y,
-                pillar_count = :pillar_count
-            WHERE game_id = :game_id";
-        db.connection.execute(
-            query,
-            (
-                current_room.x,
-                current_room.y,
-                self.pillar_count,
-                self.game_id.0,
-            ),
-        )?;
-        Ok(())
-    }
+/// A whole save game, independent of how the database stores it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveData {
+    pub created: DateTime<Utc>,
+    pub last_saved: DateTime<Utc>,
+    /// The seed used to generate the world
+    pub seed: u64,
+    /// The seed as the player typed it, empty for older games.
+    #[serde(default)]
+    pub seed_text: String,
+    /// How the world was generated
+    #[serde(default)]
+    pub generator: MapGeneratorKind,
+    #[serde(default)]
+    pub map: MapSettings,
+    pub pillar_count: u64,
+    #[serde(default)]
+    pub daily_run: Option<NaiveDate>,
+    #[serde(default)]
+    pub turns: u64,
+    /// The stats of the run up to the save, so going back
+    /// to it also goes back on everything done since.
+    /// None for games saved before they were kept here.
+    #[serde(default)]
+    pub run_stats: Option<RunStats>,
+    /// The room the party is in.
+    /// This is only None until the game is first saved.
+    pub current_room: Option<UVec2>,
+    pub actors: Vec<ActorSave>,
+    pub rooms: Vec<RoomSave>,
+    pub items: Vec<Item>,
 }
 
-#[cfg(not(feature = "sqlite"))]
-impl SaveGame {
-    pub fn new(_: &Database, seed: u64) -> Self {
+impl SaveData {
+    /// The save for a game that was just created.
+    pub fn new(seed: u64) -> Self {
+        let now = Utc::now();
         Self {
-            game_id: GameID(0),
+            created: now,
+            last_saved: now,
             seed,
+            seed_text: String::new(),
+            generator: MapGeneratorKind::default(),
+            map: MapSettings::default(),
+            pillar_count: 0,
+            daily_run: None,
+            turns: 0,
+            run_stats: None,
+            current_room: None,
+            actors: Vec::new(),
+            rooms: Vec::new(),
+            items: Vec::new(),
         }
     }
+}
 
-    pub fn save(&self, _: &Database) -> Result<(), DatabaseError> {
-        Ok(())
+/// How many autosaves are kept for each game.
+/// The oldest is removed when a new one is made.
+pub const AUTOSAVE_SNAPSHOTS: usize = 5;
+
+/// The id of a snapshot, unique within its game.
+/// Newer snapshots always have larger ids.
+#[derive(Deref, DerefMut, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotID(pub i64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum SnapshotKind {
+    /// Made every time a room is entered.
+    Autosave,
+    /// Made by the player, only the newest is kept.
+    #[strum(to_string = "Manual Save")]
+    Manual,
+}
+
+impl SnapshotKind {
+    /// How many snapshots of this kind are kept for each game.
+    pub fn limit(&self) -> usize {
+        match self {
+            SnapshotKind::Autosave => AUTOSAVE_SNAPSHOTS,
+            SnapshotKind::Manual => 1,
+        }
     }
 }
 
-#[cfg(feature = "sqlite")]
+#[derive(Clone)]
+pub struct SnapshotInfo {
+    pub id: SnapshotID,
+    pub kind: SnapshotKind,
+    pub saved: chrono::DateTime<chrono::Local>,
+}
+
 #[derive(Clone)]
 pub struct SaveGameInfo {
     pub id: GameID,
     pub created: chrono::DateTime<chrono::Local>,
     pub last_saved: chrono::DateTime<chrono::Local>,
     pub world_seed: u64,
+    pub seed_text: String,
 }
 
-#[cfg(feature = "sqlite")]
 impl SaveGameInfo {
-    pub fn get_all(db: &Database) -> Result<Box<[Self]>, DatabaseError> {
-        db.connection
+    /// The seed to show the player, see [`seed_label`].
+    pub fn seed_label(&self) -> String {
+        seed_label(self.world_seed, &self.seed_text)
+    }
+}
+
+/// How a database backend keeps save games.
+pub trait SaveBackend {
+    /// Stores a new save game, returning its id.
+    fn new_save(&self, save: &SaveData) -> Result<GameID, DatabaseError>;
+    /// Overwrites the save game with the given id.
+    fn write_save(&self, game_id: GameID, save: &SaveData) -> Result<(), DatabaseError>;
+    fn read_save(&self, game_id: GameID) -> Result<SaveData, DatabaseError>;
+    /// The info for every save game, newest first.
+    fn save_infos(&self) -> Result<Box<[SaveGameInfo]>, DatabaseError>;
+    /// Also deletes all of its snapshots.
+    fn delete_save(&self, game_id: GameID) -> Result<(), DatabaseError>;
+
+    /// Stores a copy of a save game that can be restored later.
+    fn add_snapshot(
+        &self,
+        game_id: GameID,
+        kind: SnapshotKind,
+        save: &SaveData,
+    ) -> Result<SnapshotID, DatabaseError>;
+    fn read_snapshot(&self, game_id: GameID, id: SnapshotID) -> Result<SaveData, DatabaseError>;
+    /// The info for every snapshot of a save game, newest first.
+    fn snapshots(&self, game_id: GameID) -> Result<Box<[SnapshotInfo]>, DatabaseError>;
+    fn delete_snapshot(&self, game_id: GameID, id: SnapshotID) -> Result<(), DatabaseError>;
+}
+
+/// Adds a snapshot, then removes the oldest snapshots
+/// of the same kind past its [`SnapshotKind::limit`].
+pub fn write_snapshot(
+    db: &impl SaveBackend,
+    game_id: GameID,
+    kind: SnapshotKind,
+    save: &SaveData,
+) -> Result<SnapshotID, DatabaseError> {
+    let id = db.add_snapshot(game_id, kind, save)?;
+
+    for old in db
+        .snapshots(game_id)?
+        .iter()
+        .filter(|snapshot| snapshot.kind == kind)
+        .skip(kind.limit())
+    {
+        db.delete_snapshot(game_id, old.id)?;
+    }
+
+    Ok(id)
+}
+
+/// Overwrites a save game with one of its snapshots,
+/// so the next time it is loaded it starts from there.
+pub fn restore_snapshot(
+    db: &impl SaveBackend,
+    game_id: GameID,
+    id: SnapshotID,
+) -> Result<(), DatabaseError> {
+    let save = db.read_snapshot(game_id, id)?;
+    db.write_save(game_id, &save)
+}
+
+#[cfg(feature = "sqlite")]
+impl Database {
+    /// Writes everything but the settings the save game was created with.
+    /// This should be done in a transaction.
+    fn write_save_data(&self, game_id: GameID, save: &SaveData) -> Result<(), DatabaseError> {
+        let run_stats = save
+            .run_stats
+            .as_ref()
+            .map(ron::to_string)
+            .transpose()
+            .map_err(|err| DatabaseError::ToSqlConversionFailure(Box::new(err)))?;
+
+        let query = "
+        UPDATE SaveGame
+            SET last_saved = :last_saved,
+                current_room_x = :current_room_x,
+                current_room_y = :current_room_y,
+                pillar_count = :pillar_count,
+                turns = :turns,
+                run_stats = :run_stats
+            WHERE game_id = :game_id";
+        self.connection.execute(
+            query,
+            (
+                save.last_saved,
+                save.current_room.map(|pos| pos.x),
+                save.current_room.map(|pos| pos.y),
+                save.pillar_count,
+                save.turns,
+                run_stats,
+                game_id.0,
+            ),
+        )?;
+
+        crate::actor::save_actors(self, game_id, &save.actors)?;
+        crate::spawn_map::save_map(self, game_id, &save.rooms)?;
+        crate::items::save_items(self, game_id, &save.items)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl SaveBackend for Database {
+    fn new_save(&self, save: &SaveData) -> Result<GameID, DatabaseError> {
+        let generator = ron::to_string(&save.generator)
+            .map_err(|err| DatabaseError::ToSqlConversionFailure(Box::new(err)))?;
+        let room_weights = ron::to_string(&save.map.room_weights)
+            .map_err(|err| DatabaseError::ToSqlConversionFailure(Box::new(err)))?;
+
+        let transaction = self.connection.unchecked_transaction()?;
+
+        let query = "
+        INSERT INTO SaveGame(
+            created,
+            last_saved,
+            world_seed,
+            generator,
+            map_radius,
+            map_pillars,
+            room_weights,
+            enemy_density,
+            seed_text,
+            daily_run
+        ) VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)";
+        self.connection.execute(
+            query,
+            (
+                save.created,
+                save.last_saved,
+                save.seed as i64,
+                generator,
+                save.map.radius,
+                save.map.pillars,
+                room_weights,
+                save.map.enemy_density,
+                &save.seed_text,
+                save.daily_run,
+            ),
+        )?;
+
+        let game_id = GameID(self.connection.last_insert_rowid());
+        self.write_save_data(game_id, save)?;
+
+        transaction.commit()?;
+        Ok(game_id)
+    }
+
+    fn write_save(&self, game_id: GameID, save: &SaveData) -> Result<(), DatabaseError> {
+        let transaction = self.connection.unchecked_transaction()?;
+        self.write_save_data(game_id, save)?;
+        transaction.commit()
+    }
+
+    fn read_save(&self, game_id: GameID) -> Result<SaveData, DatabaseError> {
+        let query = "
+        SELECT
+            created,
+            last_saved,
+            world_seed,
+            generator,
+            map_radius,
+            map_pillars,
+            room_weights,
+            enemy_density,
+            seed_text,
+            daily_run,
+            turns,
+            run_stats,
+            pillar_count,
+            current_room_x,
+            current_room_y
+        FROM SaveGame WHERE SaveGame.game_id = :game_id";
+
+        let mut save = self.connection.query_one(query, (game_id.0,), |row| {
+            let current_room_x: Option<u32> = row.get("current_room_x")?;
+            let current_room_y: Option<u32> = row.get("current_room_y")?;
+            let generator: String = row.get("generator")?;
+            let room_weights: String = row.get("room_weights")?;
+            let run_stats: Option<String> = row.get("run_stats")?;
+
+            Ok(SaveData {
+                created: row.get("created")?,
+                last_saved: row.get("last_saved")?,
+                seed: row.get::<_, i64>("world_seed")? as u64,
+                seed_text: row.get("seed_text")?,
+                generator: ron::from_str(&generator).map_err(|err| {
+                    DatabaseError::FromSqlConversionFailure(
+                        3,
+                        rusqlite::types::Type::Text,
+                        Box::new(err),
+                    )
+                })?,
+                map: MapSettings {
+                    radius: row.get("map_radius")?,
+                    pillars: row.get("map_pillars")?,
+                    room_weights: ron::from_str(&room_weights).map_err(|err| {
+                        DatabaseError::FromSqlConversionFailure(
+                            6,
+                            rusqlite::types::Type::Text,
+                            Box::new(err),
+                        )
+                    })?,
+                    enemy_density: row.get("enemy_density")?,
+                },
+                pillar_count: row.get("pillar_count")?,
+                daily_run: row.get("daily_run")?,
+                turns: row.get("turns")?,
+                run_stats: run_stats
+                    .map(|stats| ron::from_str(&stats))
+                    .transpose()
+                    .map_err(|err| {
+                        DatabaseError::FromSqlConversionFailure(
+                            11,
+                            rusqlite::types::Type::Text,
+                            Box::new(err),
+                        )
+                    })?,
+                current_room: current_room_x.zip(current_room_y).map(UVec2::from),
+                actors: Vec::new(),
+                rooms: Vec::new(),
+                items: Vec::new(),
+            })
+        })?;
+
+        save.actors = crate::actor::load_actors(self, game_id)?;
+        save.rooms = crate::spawn_map::load_map(self, game_id)?;
+        save.items = crate::items::load_items(self, game_id)?;
+
+        Ok(save)
+    }
+
+    fn save_infos(&self) -> Result<Box<[SaveGameInfo]>, DatabaseError> {
+        self.connection
             .prepare(
-                "SELECT game_id,created,last_saved,world_seed FROM SaveGame ORDER BY game_id DESC",
+                "SELECT game_id,created,last_saved,world_seed,seed_text FROM SaveGame ORDER BY game_id DESC",
             )?
             .query_map((), |row| {
                 let created: DateTime<Utc> = row.get(1)?;
                 let last_saved: DateTime<Utc> = row.get(2)?;
-                Ok(Self {
+                Ok(SaveGameInfo {
                     id: GameID(row.get(0)?),
                     created: created.into(),
                     last_saved: last_saved.into(),
                     world_seed: row.get::<_, i64>(3)? as u64,
+                    seed_text: row.get(4)?,
+                })
+            })?
+            .collect()
+    }
+
+    fn delete_save(&self, game_id: GameID) -> Result<(), DatabaseError> {
+        let transaction = self.connection.unchecked_transaction()?;
+
+        for table in [
+            "PlayerActor",
+            "RoomInfo",
+            "Item",
+            "SaveSnapshot",
+            "SaveGame",
+        ] {
+            // SAFETY: The table names are constant, so can't be used for an injection.
+            self.connection.execute(
+                &format!("DELETE FROM {table} WHERE game_id = :game_id"),
+                (game_id.0,),
+            )?;
+        }
+
+        transaction.commit()
+    }
+
+    fn add_snapshot(
+        &self,
+        game_id: GameID,
+        kind: SnapshotKind,
+        save: &SaveData,
+    ) -> Result<SnapshotID, DatabaseError> {
+        let data = ron::to_string(save)
+            .map_err(|err| DatabaseError::ToSqlConversionFailure(Box::new(err)))?;
+        let kind = ron::to_string(&kind)
+            .map_err(|err| DatabaseError::ToSqlConversionFailure(Box::new(err)))?;
+
+        let query = "
+        INSERT INTO SaveSnapshot(game_id, kind, saved, data)
+            VALUES(:game_id, :kind, :saved, :data)";
+        self.connection
+            .execute(query, (game_id.0, kind, save.last_saved, data))?;
+
+        Ok(SnapshotID(self.connection.last_insert_rowid()))
+    }
+
+    fn read_snapshot(&self, game_id: GameID, id: SnapshotID) -> Result<SaveData, DatabaseError> {
+        let query = "SELECT data FROM SaveSnapshot WHERE game_id = :game_id AND snapshot_id = :id";
+        let data: String = self
+            .connection
+            .query_one(query, (game_id.0, id.0), |row| row.get(0))?;
+
+        ron::from_str(&data).map_err(|err| {
+            DatabaseError::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(err))
+        })
+    }
+
+    fn snapshots(&self, game_id: GameID) -> Result<Box<[SnapshotInfo]>, DatabaseError> {
+        self.connection
+            .prepare(
+                "
+                SELECT snapshot_id, kind, saved FROM SaveSnapshot
+                    WHERE game_id = :game_id
+                    ORDER BY snapshot_id DESC",
+            )?
+            .query_map((game_id.0,), |row| {
+                let kind: String = row.get(1)?;
+                let saved: DateTime<Utc> = row.get(2)?;
+                Ok(SnapshotInfo {
+                    id: SnapshotID(row.get(0)?),
+                    kind: ron::from_str(&kind).map_err(|err| {
+                        DatabaseError::FromSqlConversionFailure(
+                            1,
+                            rusqlite::types::Type::Text,
+                            Box::new(err),
+                        )
+                    })?,
+                    saved: saved.into(),
                 })
             })?
             .collect()
     }
+
+    fn delete_snapshot(&self, game_id: GameID, id: SnapshotID) -> Result<(), DatabaseError> {
+        self.connection.execute(
+            "DELETE FROM SaveSnapshot WHERE game_id = :game_id AND snapshot_id = :id",
+            (game_id.0, id.0),
+        )?;
+        Ok(())
+    }
 }
 
 /// Takes the World as this should be the only thing running at the time.
 pub fn save_game(world: &mut World) {
+    save_game_as(world, SnapshotKind::Autosave);
+}
+
+/// Saves the game into the manual save slot, as well as saving it normally.
+pub fn manual_save(world: &mut World) {
+    save_game_as(world, SnapshotKind::Manual);
+}
+
+fn save_game_as(world: &mut World, kind: SnapshotKind) {
     info!("Saving Game");
-    {
-        let db = world.get_non_send_resource::<Database>().unwrap();
-        db.connection.execute_batch("BEGIN TRANSACTION;").unwrap();
-    }
 
-    world.run_system_cached(save_game_inner).unwrap();
+    let actors = world
+        .run_system_cached(crate::actor::collect_actors)
+        .unwrap();
 
-    world
-        .run_system_cached(crate::actor::save_actors)
-        .unwrap()
+    let rooms = world
+        .run_system_cached(crate::spawn_map::collect_map)
         .unwrap();
 
     world
-        .run_system_cached(crate::spawn_map::save_map)
+        .run_system_cached_with(save_game_inner, (kind, actors, rooms))
         .unwrap()
         .unwrap();
 
     world
-        .run_system_cached(crate::items::save_items)
+        .run_system_cached(crate::stats::save_run_stats)
         .unwrap()
         .unwrap();
 
-    {
-        let db = world.get_non_send_resource::<Database>().unwrap();
-        db.connection.execute_batch("COMMIT;").unwrap();
-    }
-
     info!("Game Save Successful");
 }
 
 fn save_game_inner(
+    In((kind, actors, rooms)): In<(SnapshotKind, Vec<ActorSave>, Vec<RoomSave>)>,
     db: NonSend<Database>,
     save: Res<SaveGame>,
+    items: Res<Items>,
+    stats: Res<RunStats>,
     pos: Single<&TilePos, With<CurrentRoom>>,
-) {
-    save.save(&db, *pos).unwrap();
+) -> Result<(), DatabaseError> {
+    let data = SaveData {
+        created: save.created,
+        last_saved: Utc::now(),
+        seed: save.seed,
+        seed_text: save.seed_text.clone(),
+        generator: save.generator,
+        map: save.map.clone(),
+        pillar_count: save.pillar_count,
+        daily_run: save.daily_run,
+        turns: save.turns,
+        run_stats: Some(stats.clone()),
+        current_room: Some(UVec2::new(pos.x, pos.y)),
+        actors,
+        rooms,
+        items: items.0.clone(),
+    };
+
+    db.write_save(save.game_id, &data)?;
+    write_snapshot(&*db, save.game_id, kind, &data)?;
+
+    Ok(())
 }
 
 pub fn load_game(world: &mut World) {
     info!("Loading Game");
 
+    let save = world.run_system_cached(read_save).unwrap().unwrap();
+
     world
-        .run_system_cached(crate::actor::load_actors)
-        .unwrap()
+        .run_system_cached_with(crate::actor::spawn_actors, save.actors)
         .unwrap();
 
     world
-        .run_system_cached(crate::spawn_map::load_map)
-        .unwrap()
+        .run_system_cached_with(crate::spawn_map::spawn_saved_map, (save.rooms, save.map))
         .unwrap();
 
+    world.insert_resource(Items(save.items));
+
     world
-        .run_system_cached(crate::items::load_items)
-        .unwrap()
+        .run_system_cached_with(crate::stats::load_run_stats, save.run_stats)
         .unwrap();
 
-    world.run_system_cached(load_game_inner).unwrap();
+    world
+        .run_system_cached_with(
+            set_current_room,
+            save.current_room
+                .expect("The game should be saved in a room")
+                .into(),
+        )
+        .unwrap();
 
     world
         .get_resource_mut::<NextState<AppState>>()
@@ -206,26 +639,248 @@ pub fn load_game(world: &mut World) {
     info!("Game Load Successful")
 }
 
-fn load_game_inner(
+fn read_save(db: NonSend<Database>, save: Res<SaveGame>) -> Result<SaveData, DatabaseError> {
+    db.read_save(save.game_id)
+}
+
+fn set_current_room(
+    In(pos): In<TilePos>,
     mut commands: Commands,
-    db: NonSend<Database>,
-    save: Res<SaveGame>,
     storage: Single<&TileStorage, With<MapTilemap>>,
 ) {
-    let query =
-        "SELECT current_room_x,current_room_y FROM SaveGame WHERE SaveGame.game_id = :game_id";
-
-    let pos = db
-        .connection
-        .query_one(query, (save.game_id.0,), |row| {
-            Ok(TilePos {
-                x: row.get(0)?,
-                y: row.get(1)?,
-            })
-        })
-        .unwrap();
-
     let entity = storage.get(&pos).unwrap();
 
     commands.get_entity(entity).unwrap().insert(CurrentRoom);
 }
+
+/// The version of the exported save file format.
+/// Only bump this for changes older files can't be read with.
+/// New fields in [`SaveData`] should be `#[serde(default)]` instead,
+/// so files from before them can still be imported.
+const SAVE_FILE_VERSION: u32 = 1;
+
+/// A save game in a single file, so it can be shared between players.
+#[derive(Serialize, Deserialize)]
+pub struct SaveFile {
+    /// The version of the file format, checked when it is imported.
+    pub version: u32,
+    pub save: SaveData,
+}
+
+#[derive(Error, Debug)]
+pub enum SaveFileError {
+    #[error("Save file is from version `{0}` when expected version `{SAVE_FILE_VERSION}`!")]
+    UnsupportedVersion(u32),
+    #[error("Failed to serialize save file with `{0}`")]
+    Serialize(#[from] ron::Error),
+    #[error("Failed to parse save file with `{0}`")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Database error occured: `{0}`")]
+    Database(#[from] DatabaseError),
+    #[error("Failed to access save file with `{0}`")]
+    Io(#[from] std::io::Error),
+    #[error("Save file can't be loaded as {0}")]
+    Invalid(&'static str),
+}
+
+impl SaveFile {
+    pub fn to_ron(save: &SaveData) -> Result<String, SaveFileError> {
+        let file = Self {
+            version: SAVE_FILE_VERSION,
+            save: save.clone(),
+        };
+
+        Ok(ron::ser::to_string_pretty(
+            &file,
+            ron::ser::PrettyConfig::default().struct_names(true),
+        )?)
+    }
+
+    pub fn from_ron(file: &str) -> Result<SaveData, SaveFileError> {
+        /// Only the version, so files from other versions
+        /// are reported properly instead of failing to parse.
+        #[derive(Deserialize)]
+        #[serde(rename = "SaveFile")]
+        struct FileVersion {
+            version: u32,
+        }
+
+        let FileVersion { version } = ron::from_str(file)?;
+        if version != SAVE_FILE_VERSION {
+            return Err(SaveFileError::UnsupportedVersion(version));
+        }
+
+        let save = ron::from_str::<Self>(file)?.save;
+        Self::check(&save)?;
+
+        Ok(save)
+    }
+
+    /// Checks the save can be loaded, as the file could have been edited.
+    fn check(save: &SaveData) -> Result<(), SaveFileError> {
+        let Some(current_room) = save.current_room else {
+            return Err(SaveFileError::Invalid("it isn't in a room"));
+        };
+        let size = save.map.size();
+        if current_room.x >= size.x || current_room.y >= size.y {
+            return Err(SaveFileError::Invalid("its room is outside of the map"));
+        }
+        if !save.rooms.iter().any(|room| room.position == current_room) {
+            return Err(SaveFileError::Invalid("its room isn't on the map"));
+        }
+        if !save
+            .rooms
+            .iter()
+            .any(|room| room.info.r_type == RoomType::Entrance)
+        {
+            return Err(SaveFileError::Invalid("it has no entrance"));
+        }
+        if !save.actors.iter().any(|actor| actor.health.is_alive()) {
+            return Err(SaveFileError::Invalid("it has no living actors"));
+        }
+
+        Ok(())
+    }
+}
+
+/// The directory save games are exported to and imported from.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export_directory() -> PathBuf {
+    crate::database::get_default_db_directory().join("exported_saves")
+}
+
+/// Exports a save game to a file in the [`export_directory`],
+/// returning the path of the file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export_save(db: &Database, game_id: GameID) -> Result<PathBuf, SaveFileError> {
+    let save = db.read_save(game_id)?;
+
+    let directory = export_directory();
+    std::fs::create_dir_all(&directory)?;
+
+    let path = directory.join(format!(
+        "game-{}-{}.ron",
+        game_id.0,
+        save.last_saved.format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&path, SaveFile::to_ron(&save)?)?;
+
+    Ok(path)
+}
+
+/// Imports a save file as a new save game.
+/// It is never a daily run, so it can't add to the daily results.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn import_save(db: &Database, path: &Path) -> Result<GameID, SaveFileError> {
+    let mut save = SaveFile::from_ron(&std::fs::read_to_string(path)?)?;
+    save.daily_run = None;
+
+    Ok(db.new_save(&save)?)
+}
+
+/// Every save file in the [`export_directory`], sorted by name.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn exported_saves() -> Result<Vec<PathBuf>, SaveFileError> {
+    let mut saves = match std::fs::read_dir(export_directory()) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+            .collect::<Vec<_>>(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(err.into()),
+    };
+    saves.sort();
+
+    Ok(saves)
+}
+
+#[cfg(test)]
+mod saving_tests {
+    use super::*;
+    use std::num::NonZero;
+
+    /// A save that can be loaded, with the party alive in the entrance.
+    fn loadable_save(seed: u64) -> SaveData {
+        let origin = SaveData::new(seed).map.origin();
+        let entrance = UVec2::new(origin.x, origin.y);
+        SaveData {
+            current_room: Some(entrance),
+            rooms: vec![
+                RoomSave {
+                    position: entrance,
+                    info: RoomInfo::from_type(RoomType::Entrance, 1),
+                },
+                RoomSave {
+                    position: entrance + UVec2::X,
+                    info: RoomInfo::from_type(RoomType::Pillar, 2),
+                },
+            ],
+            actors: vec![ActorSave {
+                name: ActorName::Warrior,
+                health: Health::new(NonZero::new(10).unwrap()),
+                attack: Attack::new(1..5, 0.5),
+                speed: AttackSpeed::new(3),
+                status_effects: StatusEffects::default(),
+                level: Level::default(),
+            }],
+            ..SaveData::new(seed)
+        }
+    }
+
+    #[test]
+    fn test_save_file() {
+        let mut save = loadable_save(0xfeed);
+        save.pillar_count = 3;
+        save.current_room = Some(save.rooms[1].position);
+        save.items.push(Item::VisionPotion);
+
+        let file = SaveFile::to_ron(&save).unwrap();
+        let loaded = SaveFile::from_ron(&file).unwrap();
+
+        assert_eq!(loaded.seed, 0xfeed);
+        assert_eq!(loaded.pillar_count, 3);
+        assert_eq!(loaded.current_room, Some(save.rooms[1].position));
+        assert_eq!(loaded.items, vec![Item::VisionPotion]);
+        assert_eq!(loaded.created, save.created);
+    }
+
+    #[test]
+    fn test_save_file_version() {
+        let file = SaveFile::to_ron(&loadable_save(0)).unwrap();
+        let file = file.replace(
+            &format!("version: {SAVE_FILE_VERSION}"),
+            &format!("version: {}", SAVE_FILE_VERSION + 1),
+        );
+
+        assert!(matches!(
+            SaveFile::from_ron(&file),
+            Err(SaveFileError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            SaveFile::from_ron("not a save"),
+            Err(SaveFileError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_save_file() {
+        let invalid = |edit: fn(&mut SaveData)| {
+            let mut save = loadable_save(0);
+            edit(&mut save);
+            let file = SaveFile::to_ron(&save).unwrap();
+            matches!(SaveFile::from_ron(&file), Err(SaveFileError::Invalid(_)))
+        };
+
+        assert!(!invalid(|_| {}));
+        assert!(invalid(|save| save.current_room = None));
+        assert!(invalid(|save| save.current_room = Some(UVec2::new(0, 0))));
+        assert!(invalid(|save| save.current_room = Some(UVec2::new(100, 0))));
+        assert!(invalid(|save| {
+            save.rooms
+                .retain(|room| room.info.r_type != RoomType::Entrance)
+        }));
+        assert!(invalid(|save| save.actors.clear()));
+        assert!(invalid(|save| save.actors[0].health.damage(100)));
+    }
+}
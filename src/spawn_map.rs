@@ -1,17 +1,14 @@
 use crate::generate_map::*;
 use crate::prelude::*;
+use crate::room::CurrentRoom;
 use bevy::prelude::*;
+use bevy_ecs_tilemap::helpers::hex_grid::neighbors::HexNeighbors;
 use bevy_ecs_tilemap::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 #[cfg(feature = "sqlite")]
-pub fn save_map(
-    tile_storage: Single<&TileStorage, With<MapTilemap>>,
-    info_q: Query<(&TilePos, &RoomInfo), With<MapTile>>,
-    save_info: Res<SaveGame>,
-    db: NonSend<Database>,
-) -> Result<(), DatabaseError> {
-    let game_id = save_info.game_id.0;
-
+pub fn save_map(db: &Database, game_id: GameID, rooms: &[RoomSave]) -> Result<(), DatabaseError> {
     let query = r#"
             INSERT OR REPLACE INTO RoomInfo(
                 game_id,
@@ -19,7 +16,9 @@ pub fn save_map(
                 position_y,
                 cleared,
                 r_type,
-                rng_seed
+                rng_seed,
+                revealed,
+                visited
             )
             VALUES(
                 :game_id,
@@ -27,55 +26,57 @@ pub fn save_map(
                 :position_y,
                 :cleared,
                 :r_type,
-                :rng_seed
+                :rng_seed,
+                :revealed,
+                :visited
             );
         "#;
 
     let mut query = db.connection.prepare(query)?;
 
-    for (
-        TilePos { x: pos_x, y: pos_y },
-        RoomInfo {
-            cleared,
-            r_type,
-            rng_seed,
-        },
-    ) in tile_storage
-        .iter()
-        .filter_map(|entity| *entity)
-        .filter_map(|entity| info_q.get(entity).ok())
+    for RoomSave {
+        position,
+        info:
+            RoomInfo {
+                cleared,
+                r_type,
+                rng_seed,
+                visited,
+                revealed,
+            },
+    } in rooms
     {
         let r_type = ron::to_string(&r_type).unwrap();
 
-        query.execute((game_id, pos_x, pos_y, cleared, r_type, *rng_seed as i64))?;
+        query.execute((
+            game_id.0,
+            position.x,
+            position.y,
+            cleared,
+            r_type,
+            *rng_seed as i64,
+            revealed,
+            visited,
+        ))?;
     }
 
     Ok(())
 }
 
 #[cfg(feature = "sqlite")]
-pub fn load_map(
-    mut commands: Commands,
-    db: NonSend<Database>,
-    save_game: Res<SaveGame>,
-    asset_server: Res<AssetServer>,
-) -> Result<(), DatabaseError> {
-    let tile_sprite = asset_server.load(MAP_TILE_ASSET_LOAD_PATH);
-
-    let game_id = save_game.game_id;
+pub fn load_map(db: &Database, game_id: GameID) -> Result<Vec<RoomSave>, DatabaseError> {
     let query = "
             SELECT
                 position_x,
                 position_y,
                 cleared,
                 r_type,
-                rng_seed
+                rng_seed,
+                revealed,
+                visited
             FROM RoomInfo WHERE RoomInfo.game_id = :game;
         ";
 
-    let tilemap_entity = commands.spawn_empty().id();
-    let mut tile_storage = TileStorage::empty(MAP_SIZE);
-
     db.connection
         .prepare(query)?
         .query_map((game_id.0,), |row| {
@@ -86,40 +87,165 @@ pub fn load_map(
             let r_type = ron::from_str(&r_type).unwrap_or(RoomType::EmptyRoom);
             // cast as sqlite can only store i64s
             let rng_seed = row.get::<_, i64>("rng_seed")? as u64;
+            let revealed = row.get("revealed")?;
+            let visited = row.get("visited")?;
 
-            Ok((
-                TilePos { x, y },
-                RoomInfo {
+            Ok(RoomSave {
+                position: UVec2::new(x, y),
+                info: RoomInfo {
                     cleared,
                     r_type,
                     rng_seed,
+                    visited,
+                    revealed,
                 },
-            ))
+            })
         })?
-        .map(|c| c.unwrap())
-        .for_each(|(tile_pos, room_info)| {
-            let id = commands
-                .spawn((
-                    room_info,
-                    TileBundle {
-                        position: tile_pos,
-                        tilemap_id: TilemapId(tilemap_entity),
-                        texture_index: TileTextureIndex(FLOOR_TILE_VARIENTS.start),
-                        ..Default::default()
-                    },
-                    MapTile,
-                ))
-                .id();
-            commands.entity(tilemap_entity).add_child(id);
-            tile_storage.set(&tile_pos, id);
-        });
+        .collect()
+}
+
+/// Gets every room on the map to put in a save game.
+pub fn collect_map(
+    tile_storage: Single<&TileStorage, With<MapTilemap>>,
+    info_q: Query<(&TilePos, &RoomInfo), With<MapTile>>,
+) -> Vec<RoomSave> {
+    tile_storage
+        .iter()
+        .filter_map(|entity| *entity)
+        .filter_map(|entity| info_q.get(entity).ok())
+        .map(|(pos, info)| RoomSave {
+            position: UVec2::new(pos.x, pos.y),
+            info: info.clone(),
+        })
+        .collect()
+}
+
+/// The map texture of a room.
+const MAP_ROOM_TILE: u32 = 0;
+/// The map texture of the room the party is in.
+const MAP_CURRENT_ROOM_TILE: u32 = 1;
+
+/// How rooms next to visited ones are drawn, before they are visited.
+const UNVISITED_ROOM_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.25);
+
+/// How a room is drawn on the map, or None if it is hidden.
+///
+/// Visited rooms are drawn normally, and rooms next to them are faded.
+/// Revealed rooms are always shown, tinted by what is in them.
+pub fn room_map_color(info: &RoomInfo, next_to_visited: bool) -> Option<Color> {
+    if info.revealed {
+        Some(info.r_type.map_color())
+    } else if info.visited {
+        Some(Color::WHITE)
+    } else if next_to_visited {
+        Some(UNVISITED_ROOM_COLOR)
+    } else {
+        None
+    }
+}
+
+/// Map rooms that were found, or that the party moved into.
+type ChangedRoomFilter = (With<MapTile>, Or<(Changed<RoomInfo>, Added<CurrentRoom>)>);
+
+/// A map room and how it is shown.
+type FogOfWarRoom = (
+    &'static TilePos,
+    &'static RoomInfo,
+    &'static mut TileColor,
+    &'static mut TileVisible,
+    &'static mut TileTextureIndex,
+    Has<CurrentRoom>,
+);
+
+/// Hides the rooms on the map that haven't been found yet.
+pub fn update_fog_of_war(
+    changed: Query<(), ChangedRoomFilter>,
+    map: Single<&TilemapSize, With<MapTilemap>>,
+    mut rooms: Query<FogOfWarRoom, With<MapTile>>,
+) {
+    if changed.is_empty() {
+        return;
+    }
+
+    let visited = rooms
+        .iter()
+        .filter(|(_, info, ..)| info.visited)
+        .map(|(pos, ..)| *pos)
+        .collect::<HashSet<_>>();
+
+    for (pos, info, mut color, mut visible, mut texture, current) in rooms.iter_mut() {
+        let next_to_visited =
+            HexNeighbors::<TilePos>::get_neighboring_positions_standard(pos, *map)
+                .iter()
+                .any(|neighbor| visited.contains(neighbor));
+
+        match room_map_color(info, next_to_visited) {
+            Some(room_color) => {
+                visible.0 = true;
+                *color = room_color.into();
+            }
+            None => visible.0 = false,
+        }
+
+        texture.0 = match current {
+            true => MAP_CURRENT_ROOM_TILE,
+            false => MAP_ROOM_TILE,
+        };
+    }
+}
+
+pub fn spawn_saved_map(
+    In((rooms, map)): In<(Vec<RoomSave>, MapSettings)>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    spawn_rooms(&mut commands, &asset_server, &map, rooms);
+}
+
+/// A room on the map as it is kept in a save game.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoomSave {
+    pub position: UVec2,
+    pub info: RoomInfo,
+}
+
+/// Spawns the map tilemap with the given rooms.
+fn spawn_rooms(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    map: &MapSettings,
+    rooms: impl IntoIterator<Item = RoomSave>,
+) {
+    let tile_sprite = asset_server.load(MAP_TILE_ASSET_LOAD_PATH);
+
+    let tilemap_entity = commands.spawn_empty().id();
+    let mut tile_storage = TileStorage::empty(map.size());
+
+    for RoomSave { position, info } in rooms {
+        let tile_pos = TilePos::from(position);
+        let id = commands
+            .spawn((
+                info,
+                TileBundle {
+                    position: tile_pos,
+                    tilemap_id: TilemapId(tilemap_entity),
+                    texture_index: TileTextureIndex(MAP_ROOM_TILE),
+                    visible: TileVisible(false),
+                    ..Default::default()
+                },
+                MapTile,
+            ))
+            .id();
+        commands.entity(tilemap_entity).add_child(id);
+        tile_storage.set(&tile_pos, id);
+    }
 
     commands.entity(tilemap_entity).insert((
         MapTilemap,
         TilemapBundle {
             grid_size: TILE_SIZE.into(),
             map_type: TilemapType::Hexagon(HexCoordSystem::Column),
-            size: MAP_SIZE,
+            size: map.size(),
             storage: tile_storage,
             texture: TilemapTexture::Single(tile_sprite.clone()),
             tile_size: TILE_SIZE,
@@ -128,6 +254,25 @@ pub fn load_map(
             ..Default::default()
         },
     ));
+}
 
-    Ok(())
+#[cfg(test)]
+mod spawn_map_tests {
+    use super::*;
+
+    #[test]
+    fn test_room_map_color() {
+        let mut info = RoomInfo::from_type(RoomType::Pillar, 0);
+        assert_eq!(room_map_color(&info, false), None);
+        assert_eq!(room_map_color(&info, true), Some(UNVISITED_ROOM_COLOR));
+
+        info.visited = true;
+        assert_eq!(room_map_color(&info, false), Some(Color::WHITE));
+
+        info.revealed = true;
+        assert_eq!(
+            room_map_color(&info, false),
+            Some(RoomType::Pillar.map_color())
+        );
+    }
 }
@@ -0,0 +1,476 @@
+//! Statistics for each run, shown when it ends,
+//! and added up over every run for the lifetime statistics.
+use crate::daily_run::RunOutcome;
+use crate::game::{CombatEvent, GameState};
+use crate::prelude::*;
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (count_elapsed_time, record_combat_events).run_if(in_state(AppState::Game)),
+        )
+        .add_systems(
+            OnEnter(GameState::Victory),
+            record_outcome(RunOutcome::Victory),
+        )
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            record_outcome(RunOutcome::GameOver),
+        );
+    }
+}
+
+/// What a single party member did over a run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActorStats {
+    pub name: ActorName,
+    pub damage_dealt: u64,
+    pub damage_taken: u64,
+    /// Attacks by them that missed.
+    pub misses: u64,
+    /// Attacks on them they blocked.
+    pub blocks: u64,
+}
+
+impl ActorStats {
+    fn new(name: ActorName) -> Self {
+        Self {
+            name,
+            damage_dealt: 0,
+            damage_taken: 0,
+            misses: 0,
+            blocks: 0,
+        }
+    }
+}
+
+/// What happened over a run, kept even if its save game is deleted.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunStats {
+    /// How the run ended, or None if it is still going.
+    pub outcome: Option<RunOutcome>,
+    pub rooms_visited: u64,
+    pub fights_won: u64,
+    pub pit_damage: u64,
+    pub potions_used: u64,
+    /// The time spent in the game, not counting the menus.
+    pub elapsed: Duration,
+    /// Each party member, in the order they first did something.
+    pub actors: Vec<ActorStats>,
+}
+
+impl RunStats {
+    /// The stats of the party member, adding them if they have none yet.
+    pub fn actor(&mut self, name: ActorName) -> &mut ActorStats {
+        match self.actors.iter().position(|actor| actor.name == name) {
+            Some(i) => &mut self.actors[i],
+            None => {
+                self.actors.push(ActorStats::new(name));
+                self.actors.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// The stats to go on from when a save is loaded.
+    /// The run is going again, even if it ended after the save.
+    pub fn resumed(saved: Option<RunStats>) -> Self {
+        Self {
+            outcome: None,
+            ..saved.unwrap_or_default()
+        }
+    }
+
+    /// Adds another run's stats to these, keeping the outcome.
+    pub fn add(&mut self, other: &RunStats) {
+        self.rooms_visited += other.rooms_visited;
+        self.fights_won += other.fights_won;
+        self.pit_damage += other.pit_damage;
+        self.potions_used += other.potions_used;
+        self.elapsed += other.elapsed;
+
+        for other in &other.actors {
+            let actor = self.actor(other.name);
+            actor.damage_dealt += other.damage_dealt;
+            actor.damage_taken += other.damage_taken;
+            actor.misses += other.misses;
+            actor.blocks += other.blocks;
+        }
+    }
+
+    /// Records damage done by one actor to another,
+    /// which is only kept for party members.
+    pub fn record_damage(
+        &mut self,
+        attacker: (ActorName, Team),
+        target: (ActorName, Team),
+        damage: u32,
+    ) {
+        if attacker.1 == Team::Player {
+            self.actor(attacker.0).damage_dealt += damage as u64;
+        }
+        if target.1 == Team::Player {
+            self.actor(target.0).damage_taken += damage as u64;
+        }
+    }
+
+    pub fn record_miss(&mut self, attacker: (ActorName, Team)) {
+        if attacker.1 == Team::Player {
+            self.actor(attacker.0).misses += 1;
+        }
+    }
+
+    pub fn record_block(&mut self, target: (ActorName, Team)) {
+        if target.1 == Team::Player {
+            self.actor(target.0).blocks += 1;
+        }
+    }
+
+    /// Records what happened in a fight, for the events that have stats.
+    pub fn record_event(&mut self, event: &CombatEvent) {
+        match *event {
+            CombatEvent::Attack {
+                attacker,
+                target,
+                damage,
+            } => self.record_damage(
+                (attacker.name, attacker.team),
+                (target.name, target.team),
+                damage,
+            ),
+            CombatEvent::Miss { attacker, .. } => self.record_miss((attacker.name, attacker.team)),
+            CombatEvent::Block { target, .. } => self.record_block((target.name, target.team)),
+            CombatEvent::StatusDamage { actor, damage, .. } if actor.team == Team::Player => {
+                self.actor(actor.name).damage_taken += damage as u64;
+            }
+            CombatEvent::Heal { .. }
+            | CombatEvent::UseItem { .. }
+            | CombatEvent::Ability { .. }
+            | CombatEvent::Death { .. }
+            | CombatEvent::TurnChange { .. }
+            | CombatEvent::Afflicted { .. }
+            | CombatEvent::StatusDamage { .. }
+            | CombatEvent::StatusHeal { .. }
+            | CombatEvent::Stunned { .. }
+            | CombatEvent::Wait { .. }
+            | CombatEvent::Flee { .. } => {}
+        }
+    }
+
+    /// The (dealt, taken, misses, blocks) of the whole party.
+    pub fn party_totals(&self) -> (u64, u64, u64, u64) {
+        self.actors.iter().fold((0, 0, 0, 0), |totals, actor| {
+            (
+                totals.0 + actor.damage_dealt,
+                totals.1 + actor.damage_taken,
+                totals.2 + actor.misses,
+                totals.3 + actor.blocks,
+            )
+        })
+    }
+}
+
+/// Every run's stats added together.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LifetimeStats {
+    pub runs: u64,
+    pub victories: u64,
+    pub game_overs: u64,
+    pub totals: RunStats,
+}
+
+impl LifetimeStats {
+    pub fn from_runs<'a>(runs: impl IntoIterator<Item = &'a RunStats>) -> Self {
+        let mut lifetime = Self::default();
+        for run in runs {
+            lifetime.runs += 1;
+            match run.outcome {
+                Some(RunOutcome::Victory) => lifetime.victories += 1,
+                Some(RunOutcome::GameOver) => lifetime.game_overs += 1,
+                None => {}
+            }
+            lifetime.totals.add(run);
+        }
+        lifetime
+    }
+}
+
+/// How a database backend keeps the stats of each run.
+pub trait StatsBackend {
+    fn write_run_stats(&self, game_id: GameID, stats: &RunStats) -> Result<(), DatabaseError>;
+    /// None if the game has no stats, like games from before stats were kept.
+    fn read_run_stats(&self, game_id: GameID) -> Result<Option<RunStats>, DatabaseError>;
+    /// The stats of every run, including those whose save game was deleted.
+    fn all_run_stats(&self) -> Result<Vec<RunStats>, DatabaseError>;
+}
+
+/// Writes the [`RunStats`] of the current game.
+pub fn save_run_stats(
+    db: NonSend<Database>,
+    save: Res<SaveGame>,
+    stats: Res<RunStats>,
+) -> Result<(), DatabaseError> {
+    db.write_run_stats(save.game_id, &stats)
+}
+
+/// Puts the [`RunStats`] kept in the loaded save into the resource.
+/// Games saved before they were kept there go on from their stored stats.
+pub fn load_run_stats(
+    In(saved): In<Option<RunStats>>,
+    mut commands: Commands,
+    db: NonSend<Database>,
+    save: Res<SaveGame>,
+) {
+    let stats = saved.or_else(|| {
+        db.read_run_stats(save.game_id)
+            .inspect_err(|err| warn!("Failed to read the run stats with {err}"))
+            .ok()
+            .flatten()
+    });
+    commands.insert_resource(RunStats::resumed(stats));
+}
+
+fn count_elapsed_time(mut stats: ResMut<RunStats>, time: Res<Time>) {
+    stats.elapsed += time.delta();
+}
+
+fn record_combat_events(mut events: EventReader<CombatEvent>, mut stats: ResMut<RunStats>) {
+    for event in events.read() {
+        stats.record_event(event);
+    }
+}
+
+/// Saves how the run ended, as the game isn't saved again after.
+fn record_outcome(
+    outcome: RunOutcome,
+) -> impl Fn(NonSend<Database>, Res<SaveGame>, ResMut<RunStats>) {
+    move |db, save, mut stats| {
+        stats.outcome = Some(outcome);
+        if let Err(err) = db.write_run_stats(save.game_id, &stats) {
+            warn!("Failed to save the run stats with {err}");
+        }
+    }
+}
+
+/// Like `1h 02m 03s`, leaving off the hours when there are none.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs / 3600 {
+        0 => format!("{}m {:02}s", secs / 60, secs % 60),
+        hours => format!("{hours}h {:02}m {:02}s", secs / 60 % 60, secs % 60),
+    }
+}
+
+/// A line of text for each stat, then one for each party member.
+pub fn stats_lines(stats: &RunStats) -> Vec<String> {
+    let (dealt, taken, misses, blocks) = stats.party_totals();
+
+    let mut lines = vec![
+        format!("Time: {}", format_duration(stats.elapsed)),
+        format!("Rooms visited: {}", stats.rooms_visited),
+        format!("Fights won: {}", stats.fights_won),
+        format!("Damage dealt: {dealt}"),
+        format!("Damage taken: {taken}"),
+        format!("Misses: {misses}"),
+        format!("Blocks: {blocks}"),
+        format!("Pit damage: {}", stats.pit_damage),
+        format!("Potions used: {}", stats.potions_used),
+    ];
+    lines.extend(stats.actors.iter().map(|actor| {
+        format!(
+            "{}: {} dealt, {} taken, {} misses, {} blocks",
+            actor.name, actor.damage_dealt, actor.damage_taken, actor.misses, actor.blocks
+        )
+    }));
+
+    lines
+}
+
+/// Spawns the [`stats_lines`] as a column of text.
+pub fn spawn_stats(builder: &mut ChildSpawnerCommands, style: &Style, lines: Vec<String>) {
+    builder
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(style.background_color),
+        ))
+        .with_children(|builder| {
+            for line in lines {
+                builder.spawn((
+                    Text::new(line),
+                    style.font(24.0),
+                    TextColor(style.text_color),
+                ));
+            }
+        });
+}
+
+#[cfg(feature = "sqlite")]
+impl StatsBackend for Database {
+    fn write_run_stats(&self, game_id: GameID, stats: &RunStats) -> Result<(), DatabaseError> {
+        let outcome = stats
+            .outcome
+            .map(|outcome| ron::to_string(&outcome))
+            .transpose()
+            .map_err(|err| DatabaseError::ToSqlConversionFailure(Box::new(err)))?;
+        let actors = ron::to_string(&stats.actors)
+            .map_err(|err| DatabaseError::ToSqlConversionFailure(Box::new(err)))?;
+
+        let query = "
+        INSERT OR REPLACE INTO RunStats(
+            game_id,
+            outcome,
+            rooms_visited,
+            fights_won,
+            pit_damage,
+            potions_used,
+            elapsed,
+            actors
+        ) VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)";
+        self.connection.execute(
+            query,
+            (
+                game_id.0,
+                outcome,
+                stats.rooms_visited,
+                stats.fights_won,
+                stats.pit_damage,
+                stats.potions_used,
+                stats.elapsed.as_secs_f64(),
+                actors,
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    fn read_run_stats(&self, game_id: GameID) -> Result<Option<RunStats>, DatabaseError> {
+        let mut stats = self.connection.prepare(
+            "SELECT outcome,rooms_visited,fights_won,pit_damage,potions_used,elapsed,actors
+                FROM RunStats WHERE game_id = ?1",
+        )?;
+        let mut rows = stats.query_map((game_id.0,), run_stats_from_row)?;
+        rows.next().transpose()
+    }
+
+    fn all_run_stats(&self) -> Result<Vec<RunStats>, DatabaseError> {
+        self.connection
+            .prepare(
+                "SELECT outcome,rooms_visited,fights_won,pit_damage,potions_used,elapsed,actors
+                    FROM RunStats",
+            )?
+            .query_map((), run_stats_from_row)?
+            .collect()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn run_stats_from_row(row: &rusqlite::Row) -> Result<RunStats, DatabaseError> {
+    let outcome: Option<String> = row.get(0)?;
+    let actors: String = row.get(6)?;
+
+    Ok(RunStats {
+        outcome: outcome
+            .map(|outcome| ron::from_str(&outcome))
+            .transpose()
+            .map_err(|err| {
+                DatabaseError::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Text,
+                    Box::new(err),
+                )
+            })?,
+        rooms_visited: row.get(1)?,
+        fights_won: row.get(2)?,
+        pit_damage: row.get(3)?,
+        potions_used: row.get(4)?,
+        elapsed: Duration::from_secs_f64(row.get(5)?),
+        actors: ron::from_str(&actors).map_err(|err| {
+            DatabaseError::FromSqlConversionFailure(6, rusqlite::types::Type::Text, Box::new(err))
+        })?,
+    })
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use super::*;
+
+    fn run(outcome: Option<RunOutcome>, dealt: u64) -> RunStats {
+        let mut stats = RunStats {
+            outcome,
+            rooms_visited: 4,
+            elapsed: Duration::from_secs(90),
+            ..default()
+        };
+        stats.actor(ActorName::Warrior).damage_dealt = dealt;
+        stats
+    }
+
+    #[test]
+    fn test_actor_stats() {
+        let mut stats = RunStats::default();
+        stats.actor(ActorName::Warrior).damage_dealt += 10;
+        stats.actor(ActorName::Priestess).damage_taken += 3;
+        stats.actor(ActorName::Warrior).misses += 1;
+
+        assert_eq!(stats.actors.len(), 2);
+        assert_eq!(stats.party_totals(), (10, 3, 1, 0));
+    }
+
+    #[test]
+    fn test_record_attacks() {
+        let mut stats = RunStats::default();
+        let warrior = (ActorName::Warrior, Team::Player);
+        let enemy = (ActorName::Skeleton, Team::Enemy);
+
+        stats.record_damage(warrior, enemy, 7);
+        stats.record_damage(enemy, warrior, 4);
+        stats.record_miss(enemy);
+        stats.record_block(enemy);
+        stats.record_block(warrior);
+
+        assert_eq!(stats.actors.len(), 1);
+        assert_eq!(stats.party_totals(), (7, 4, 0, 1));
+    }
+
+    #[test]
+    fn test_lifetime_stats() {
+        let runs = [
+            run(Some(RunOutcome::Victory), 10),
+            run(Some(RunOutcome::GameOver), 5),
+            run(None, 1),
+        ];
+        let lifetime = LifetimeStats::from_runs(&runs);
+
+        assert_eq!(lifetime.runs, 3);
+        assert_eq!(lifetime.victories, 1);
+        assert_eq!(lifetime.game_overs, 1);
+        assert_eq!(lifetime.totals.rooms_visited, 12);
+        assert_eq!(lifetime.totals.elapsed, Duration::from_secs(270));
+        assert_eq!(lifetime.totals.actors.len(), 1);
+        assert_eq!(lifetime.totals.actors[0].damage_dealt, 16);
+    }
+
+    #[test]
+    fn test_resumed_stats() {
+        let resumed = RunStats::resumed(Some(run(Some(RunOutcome::GameOver), 10)));
+        assert_eq!(resumed, run(None, 10));
+        assert_eq!(RunStats::resumed(None), RunStats::default());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "0m 05s");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1h 02m 03s");
+    }
+}
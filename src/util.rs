@@ -4,7 +4,6 @@ use bevy::ecs::system::ScheduleSystem;
 use bevy::input_focus::InputFocus;
 use bevy::prelude::*;
 use bevy::state::state::FreelyMutableState;
-use std::time::Duration;
 
 /// TODO: Replace with `std::f32::consts::SQRT_3` when that is stable.
 //pub const SQRT_3: f32 = 1.732050807568877293527446341505872367_f32;
@@ -99,25 +98,3 @@ pub fn clear_focus_on_click(
     input_focus.clear();
     click.propagate(false);
 }
-
-#[derive(Resource)]
-pub struct OldFixedDuration(pub Duration);
-
-/// Change the fixed update timer so that this section will go much faster.
-pub fn set_fixed_update_time(frequency: f64) -> ScheduleConfigs<ScheduleSystem> {
-    (move |mut commands: Commands, mut time: ResMut<Time<Fixed>>| {
-        commands.insert_resource(OldFixedDuration(time.timestep()));
-        time.set_timestep_hz(frequency);
-    })
-    .into_configs()
-}
-
-/// Change back the time to not affect any other fixed update things.
-pub fn restore_fixed_update_time(
-    mut commands: Commands,
-    mut time: ResMut<Time<Fixed>>,
-    old_duration: Res<OldFixedDuration>,
-) {
-    time.set_timestep(old_duration.0);
-    commands.remove_resource::<OldFixedDuration>();
-}